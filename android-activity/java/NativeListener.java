@@ -0,0 +1,34 @@
+// The source for `NativeListener.dex`, which android-activity loads at runtime
+// so it can register Java listeners without any Java code being built into the
+// application.
+//
+// To rebuild the dex file (with `android.jar` from any SDK platform, and the
+// `d8` tool from the SDK build tools):
+//
+//     javac --release 8 -cp android.jar -d classes NativeListener.java
+//     d8 --release --min-api 21 --output . classes/com/github/rust_mobile/android_activity/*.class
+//     mv classes.dex NativeListener.dex
+package com.github.rust_mobile.android_activity;
+
+import android.content.BroadcastReceiver;
+import android.content.Context;
+import android.content.Intent;
+import java.lang.reflect.InvocationHandler;
+import java.lang.reflect.Method;
+
+/**
+ * Forwards broadcasts, and the calls to listener interfaces that are
+ * implemented via {@link java.lang.reflect.Proxy}, to native methods that are
+ * registered by android-activity.
+ */
+final class NativeListener extends BroadcastReceiver implements InvocationHandler {
+    NativeListener() {
+        super();
+    }
+
+    @Override
+    public native void onReceive(Context context, Intent intent);
+
+    @Override
+    public native Object invoke(Object proxy, Method method, Object[] args);
+}
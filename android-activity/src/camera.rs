@@ -0,0 +1,52 @@
+//! Camera metadata that's relevant for laying out and rotating camera previews
+//!
+//! Full camera access is out of scope for this crate, but applications
+//! that render a camera preview need to know the orientation of each camera's
+//! sensor, relative to the device's natural orientation.
+
+/// The direction a camera faces, relative to the device's screen
+///
+/// See [the CameraMetadata docs](https://developer.android.com/reference/android/hardware/camera2/CameraMetadata#LENS_FACING_FRONT)
+///
+/// # Android Extensible Enum
+///
+/// This is a runtime [extensible enum](`crate#android-extensible-enums`) and
+/// should be handled similar to a `#[non_exhaustive]` enum to maintain
+/// forwards compatibility.
+///
+/// This implements `Into<u32>` and `From<u32>` for converting to/from Android
+/// SDK integer values.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, num_enum::FromPrimitive, num_enum::IntoPrimitive)]
+#[non_exhaustive]
+#[repr(u32)]
+pub enum LensFacing {
+    /// The camera faces the same direction as the screen
+    Front = 0,
+    /// The camera faces the opposite direction to the screen
+    Back = 1,
+    /// The camera is an external camera, with no fixed facing relative to the screen
+    External = 2,
+
+    #[doc(hidden)]
+    #[num_enum(catch_all)]
+    __Unknown(u32),
+}
+
+/// Layout-relevant characteristics of a camera
+///
+/// See [`AndroidApp::cameras()`](crate::AndroidApp::cameras)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CameraInfo {
+    /// The camera's ID, as used by the `CameraManager` API
+    pub id: String,
+
+    /// The direction the camera faces, if known
+    pub lens_facing: Option<LensFacing>,
+
+    /// The clockwise angle, in degrees (0, 90, 180 or 270), through which the
+    /// camera's output image needs to be rotated to be upright on the device
+    /// screen in its natural orientation, if known
+    pub sensor_orientation: Option<i32>,
+}
@@ -1,11 +1,93 @@
 use core::fmt;
 use std::sync::{Arc, RwLock};
 
+use bitflags::bitflags;
 use ndk::configuration::{
-    Configuration, Keyboard, KeysHidden, LayoutDir, NavHidden, Navigation, Orientation, ScreenLong,
-    ScreenSize, Touchscreen, UiModeNight, UiModeType,
+    Configuration, DiffResult, Keyboard, KeysHidden, LayoutDir, NavHidden, Navigation, Orientation,
+    ScreenLong, ScreenSize, Touchscreen, UiModeNight, UiModeType,
 };
 
+bitflags! {
+    /// A set of configuration qualifiers, such as those that changed between
+    /// two configurations
+    ///
+    /// These correspond to the `ACONFIGURATION_*` change masks used by the
+    /// NDK.
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+    pub struct ConfigChange: u32 {
+        const MCC = ndk_sys::ACONFIGURATION_MCC;
+        const MNC = ndk_sys::ACONFIGURATION_MNC;
+        const LOCALE = ndk_sys::ACONFIGURATION_LOCALE;
+        const TOUCHSCREEN = ndk_sys::ACONFIGURATION_TOUCHSCREEN;
+        const KEYBOARD = ndk_sys::ACONFIGURATION_KEYBOARD;
+        const KEYBOARD_HIDDEN = ndk_sys::ACONFIGURATION_KEYBOARD_HIDDEN;
+        const NAVIGATION = ndk_sys::ACONFIGURATION_NAVIGATION;
+        const ORIENTATION = ndk_sys::ACONFIGURATION_ORIENTATION;
+        const DENSITY = ndk_sys::ACONFIGURATION_DENSITY;
+        const SCREEN_SIZE = ndk_sys::ACONFIGURATION_SCREEN_SIZE;
+        const VERSION = ndk_sys::ACONFIGURATION_VERSION;
+        const SCREEN_LAYOUT = ndk_sys::ACONFIGURATION_SCREEN_LAYOUT;
+        const UI_MODE = ndk_sys::ACONFIGURATION_UI_MODE;
+        const SMALLEST_SCREEN_SIZE = ndk_sys::ACONFIGURATION_SMALLEST_SCREEN_SIZE;
+        const LAYOUT_DIR = ndk_sys::ACONFIGURATION_LAYOUTDIR;
+        const SCREEN_ROUND = ndk_sys::ACONFIGURATION_SCREEN_ROUND;
+        const COLOR_MODE = ndk_sys::ACONFIGURATION_COLOR_MODE;
+
+        /// The font scale, from [`AndroidApp::font_scale()`](crate::AndroidApp::font_scale)
+        ///
+        /// This isn't part of `AConfiguration`, and so is only reported for
+        /// [`MainEvent::ConfigChanged`](crate::MainEvent::ConfigChanged) events.
+        const FONT_SCALE = 1 << 31;
+    }
+}
+
+impl From<DiffResult> for ConfigChange {
+    fn from(diff: DiffResult) -> Self {
+        Self::from_bits_retain(diff.0)
+    }
+}
+
+/// Returns [`ConfigChange::FONT_SCALE`] if the font scale changed
+fn font_scale_change(previous: f32, font_scale: f32) -> ConfigChange {
+    if previous != font_scale {
+        ConfigChange::FONT_SCALE
+    } else {
+        ConfigChange::empty()
+    }
+}
+
+/// A coarse classification of the screen size, for choosing between phone and
+/// tablet layouts
+///
+/// The buckets match the breakpoints of the Material Design
+/// [window size classes](https://developer.android.com/develop/ui/compose/layouts/adaptive/use-window-size-classes),
+/// and are applied to the configuration's `smallestScreenWidthDp`.
+///
+/// See [`AndroidApp::screen_size_class()`](crate::AndroidApp::screen_size_class)
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum ScreenSizeClass {
+    /// Less than 600dp, such as a phone
+    Compact,
+    /// At least 600dp and less than 840dp, such as a small tablet or unfolded foldable
+    Medium,
+    /// At least 840dp, such as a large tablet or desktop
+    Expanded,
+}
+
+impl ScreenSizeClass {
+    /// Buckets a width, in density-independent pixels, into a size class
+    pub fn from_width_dp(width_dp: i32) -> Self {
+        if width_dp < 600 {
+            Self::Compact
+        } else if width_dp < 840 {
+            Self::Medium
+        } else {
+            Self::Expanded
+        }
+    }
+}
+
 /// A (cheaply clonable) reference to this application's [`ndk::configuration::Configuration`]
 ///
 /// This provides a thread-safe way to access the latest configuration state for
@@ -16,6 +98,9 @@ use ndk::configuration::{
 #[derive(Clone)]
 pub struct ConfigurationRef {
     config: Arc<RwLock<Configuration>>,
+    /// The font scale isn't part of `AConfiguration` and is queried separately
+    /// by the backend each time the configuration changes
+    font_scale: Arc<RwLock<f32>>,
 }
 impl PartialEq for ConfigurationRef {
     fn eq(&self, other: &Self) -> bool {
@@ -23,7 +108,7 @@ impl PartialEq for ConfigurationRef {
             true
         } else {
             let other_guard = other.config.read().unwrap();
-            self.config.read().unwrap().eq(&*other_guard)
+            self.config.read().unwrap().eq(&*other_guard) && self.font_scale() == other.font_scale()
         }
     }
 }
@@ -41,13 +126,25 @@ impl ConfigurationRef {
     pub(crate) fn new(config: Configuration) -> Self {
         Self {
             config: Arc::new(RwLock::new(config)),
+            font_scale: Arc::new(RwLock::new(1.0)),
         }
     }
 
+    /// Updates the font scale, returning `ConfigChange::FONT_SCALE` if it changed
+    pub(crate) fn set_font_scale(&self, font_scale: f32) -> ConfigChange {
+        let previous = std::mem::replace(&mut *self.font_scale.write().unwrap(), font_scale);
+        font_scale_change(previous, font_scale)
+    }
+
     pub(crate) fn replace(&self, src: Configuration) {
         self.config.write().unwrap().copy(&src);
     }
 
+    /// Returns the set of qualifiers that differ between this configuration and `other`
+    pub(crate) fn diff(&self, other: &Configuration) -> ConfigChange {
+        self.config.read().unwrap().diff(other).into()
+    }
+
     // Returns a deep copy of the full application configuration
     pub fn copy(&self) -> Configuration {
         let mut dest = Configuration::new();
@@ -66,6 +163,20 @@ impl ConfigurationRef {
         self.config.read().unwrap().density()
     }
 
+    /// Returns the user's preferred scaling factor for fonts
+    ///
+    /// This is relative to the base density scaling, so `1.0` represents the
+    /// default font size and a larger value indicates that the user has
+    /// requested larger text (e.g. via accessibility settings).
+    ///
+    /// `AConfiguration` doesn't include the font scale, so this is read from
+    /// the Activity's `Configuration.fontScale` and refreshed before each
+    /// [`MainEvent::ConfigChanged`](crate::MainEvent::ConfigChanged) event
+    /// (with [`ConfigChange::FONT_SCALE`] set if it changed).
+    pub fn font_scale(&self) -> f32 {
+        *self.font_scale.read().unwrap()
+    }
+
     /// Returns the keyboard type.
     pub fn keyboard(&self) -> Keyboard {
         self.config.read().unwrap().keyboard()
@@ -81,11 +192,36 @@ impl ConfigurationRef {
         self.config.read().unwrap().language()
     }
 
+    /// Returns the locale as a language tag, such as `"en-US"` (or just `"en"`
+    /// if no country is set), if a language is set
+    ///
+    /// This combines [`Self::language()`] and [`Self::country()`].
+    pub fn locale(&self) -> Option<String> {
+        let config = self.config.read().unwrap();
+        let language = config.language()?;
+        Some(match config.country() {
+            Some(country) => format!("{language}-{country}"),
+            None => language,
+        })
+    }
+
     /// Returns the layout direction
+    ///
+    /// This follows the locale, and is updated (along with [`Self::language()`]
+    /// and [`Self::country()`]) before a
+    /// [`MainEvent::ConfigChanged`](crate::MainEvent::ConfigChanged) event is
+    /// delivered, with [`ConfigChange::LAYOUT_DIR`] set if it changed.
     pub fn layout_direction(&self) -> LayoutDir {
         self.config.read().unwrap().layout_direction()
     }
 
+    /// Returns `true` if the layout direction is right-to-left
+    ///
+    /// See [`Self::layout_direction()`]
+    pub fn is_rtl(&self) -> bool {
+        self.layout_direction() == LayoutDir::Rtl
+    }
+
     /// Returns the mobile country code.
     pub fn mcc(&self) -> i32 {
         self.config.read().unwrap().mcc()
@@ -137,15 +273,62 @@ impl ConfigurationRef {
         self.config.read().unwrap().smallest_screen_width_dp()
     }
 
+    /// Returns the [`ScreenSizeClass`] for [`Self::smallest_screen_width_dp()`], if known
+    pub fn screen_size_class(&self) -> Option<ScreenSizeClass> {
+        self.smallest_screen_width_dp()
+            .map(ScreenSizeClass::from_width_dp)
+    }
+
     pub fn touchscreen(&self) -> Touchscreen {
         self.config.read().unwrap().touchscreen()
     }
 
+    /// Returns whether the UI is in night mode (a dark theme)
+    ///
+    /// This is [`UiModeNight::Any`] if the night mode is undefined.
+    ///
+    /// The configuration is updated before a
+    /// [`MainEvent::ConfigChanged`](crate::MainEvent::ConfigChanged) event is
+    /// delivered (with [`ConfigChange::UI_MODE`] set when the user toggles the
+    /// dark theme), so it's enough to re-check this when handling that event.
     pub fn ui_mode_night(&self) -> UiModeNight {
         self.config.read().unwrap().ui_mode_night()
     }
 
+    /// Returns `Some(true)` if the UI is in night mode (a dark theme),
+    /// `Some(false)` if not, or `None` if the night mode is undefined
+    ///
+    /// See [`Self::ui_mode_night()`]
+    pub fn is_night_mode(&self) -> Option<bool> {
+        match self.ui_mode_night() {
+            UiModeNight::Yes => Some(true),
+            UiModeNight::No => Some(false),
+            _ => None,
+        }
+    }
+
     pub fn ui_mode_type(&self) -> UiModeType {
         self.config.read().unwrap().ui_mode_type()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_combines_qualifiers_with_font_scale() {
+        let diff = ConfigChange::from(DiffResult(
+            ndk_sys::ACONFIGURATION_ORIENTATION | ndk_sys::ACONFIGURATION_SCREEN_SIZE,
+        ));
+        assert_eq!(diff, ConfigChange::ORIENTATION | ConfigChange::SCREEN_SIZE);
+        assert!(!diff.contains(ConfigChange::FONT_SCALE));
+
+        // A font scale change alone (which isn't part of `AConfiguration`)
+        // must still be reported
+        let font_scale_only = ConfigChange::from(DiffResult(0)) | font_scale_change(1.0, 1.3);
+        assert_eq!(font_scale_only, ConfigChange::FONT_SCALE);
+
+        assert!((ConfigChange::from(DiffResult(0)) | font_scale_change(1.3, 1.3)).is_empty());
+    }
+}
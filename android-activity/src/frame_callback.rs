@@ -0,0 +1,221 @@
+use std::ffi::{c_long, c_void};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::notify::{self, LooperNotifier, Notifications};
+
+type PostFrameCallback64 = unsafe extern "C" fn(
+    *mut ndk_sys::AChoreographer,
+    unsafe extern "C" fn(i64, *mut c_void),
+    *mut c_void,
+);
+type PostFrameCallback = unsafe extern "C" fn(
+    *mut ndk_sys::AChoreographer,
+    unsafe extern "C" fn(c_long, *mut c_void),
+    *mut c_void,
+);
+type GetInstance = unsafe extern "C" fn() -> *mut ndk_sys::AChoreographer;
+type RegisterRefreshRateCallback = unsafe extern "C" fn(
+    *mut ndk_sys::AChoreographer,
+    unsafe extern "C" fn(i64, *mut c_void),
+    *mut c_void,
+);
+
+/// The `AChoreographer` functions, which are looked up at runtime since they
+/// aren't available on all the Android versions that we support
+#[derive(Debug, Clone, Copy)]
+struct Choreographer {
+    get_instance: GetInstance,
+    /// `AChoreographer_postFrameCallback64()` (API level 29)
+    post_frame_callback64: Option<PostFrameCallback64>,
+    /// `AChoreographer_postFrameCallback()` (API level 24), which truncates
+    /// the frame time to a 32-bit `long` on 32-bit devices
+    post_frame_callback: Option<PostFrameCallback>,
+    /// `AChoreographer_registerRefreshRateCallback()` (API level 30)
+    register_refresh_rate_callback: Option<RegisterRefreshRateCallback>,
+}
+
+impl Choreographer {
+    fn load() -> Option<Self> {
+        unsafe {
+            let lib = libc::dlopen(
+                b"libandroid.so\0".as_ptr() as _,
+                libc::RTLD_NOW | libc::RTLD_NOLOAD,
+            );
+            if lib.is_null() {
+                return None;
+            }
+            let symbol = |name: &[u8]| libc::dlsym(lib, name.as_ptr() as _);
+
+            let get_instance = symbol(b"AChoreographer_getInstance\0");
+            if get_instance.is_null() {
+                log::warn!("AChoreographer is not supported on this device");
+                return None;
+            }
+            let post_frame_callback64 = symbol(b"AChoreographer_postFrameCallback64\0");
+            let post_frame_callback = symbol(b"AChoreographer_postFrameCallback\0");
+            let register_refresh_rate_callback =
+                symbol(b"AChoreographer_registerRefreshRateCallback\0");
+            Some(Self {
+                get_instance: std::mem::transmute::<*mut c_void, GetInstance>(get_instance),
+                post_frame_callback64: (!post_frame_callback64.is_null()).then(|| {
+                    std::mem::transmute::<*mut c_void, PostFrameCallback64>(post_frame_callback64)
+                }),
+                post_frame_callback: (!post_frame_callback.is_null()).then(|| {
+                    std::mem::transmute::<*mut c_void, PostFrameCallback>(post_frame_callback)
+                }),
+                register_refresh_rate_callback: (!register_refresh_rate_callback.is_null()).then(
+                    || {
+                        std::mem::transmute::<*mut c_void, RegisterRefreshRateCallback>(
+                            register_refresh_rate_callback,
+                        )
+                    },
+                ),
+            })
+        }
+    }
+}
+
+/// Tracks requests for a vsync frame callback, for
+/// [`AndroidApp::request_frame_callback()`](crate::AndroidApp::request_frame_callback)
+///
+/// `AChoreographer` needs to be used from a thread with an `ALooper`, so
+/// requests (which may come from any thread) are only posted to the
+/// choreographer from within `poll_events()`. The callback itself is run by
+/// the main thread's looper, where it records the frame time and notifies the
+/// looper, so that `poll_events()` can deliver a `MainEvent::Frame`.
+///
+/// This also registers a refresh rate callback with the choreographer (on
+/// Android 11 and newer), which notifies the looper so that `poll_events()`
+/// can check for a
+/// `MainEvent::DisplayModeChanged` when the display's refresh rate changes.
+#[derive(Debug)]
+pub(crate) struct FrameCallbacks {
+    choreographer: Option<Choreographer>,
+    /// Whether the application has requested a callback that hasn't been
+    /// posted yet
+    requested: AtomicBool,
+    /// Whether a callback has been posted that hasn't run yet
+    posted: AtomicBool,
+    /// The frame time of a callback that hasn't been delivered yet
+    frame_time_nanos: Mutex<Option<i64>>,
+    /// Whether the refresh rate callback has been registered
+    refresh_rate_registered: AtomicBool,
+    notifier: Arc<LooperNotifier>,
+}
+
+impl FrameCallbacks {
+    pub(crate) fn new(notifier: Arc<LooperNotifier>) -> Arc<Self> {
+        Arc::new(Self {
+            choreographer: Choreographer::load(),
+            requested: AtomicBool::new(false),
+            posted: AtomicBool::new(false),
+            frame_time_nanos: Mutex::new(None),
+            refresh_rate_registered: AtomicBool::new(false),
+            notifier,
+        })
+    }
+
+    /// Requests a frame callback, which will be posted on the next call to
+    /// [`Self::post_requested()`]
+    ///
+    /// Returns `false` if frame callbacks aren't supported.
+    pub(crate) fn request(&self) -> bool {
+        if self.choreographer.is_none() {
+            return false;
+        }
+        self.requested.store(true, Ordering::SeqCst);
+        true
+    }
+
+    /// Posts a requested callback to the current thread's choreographer
+    ///
+    /// Must be called on the main thread, which has an `ALooper`. Requests
+    /// that are made while a callback is already posted are coalesced into
+    /// that callback.
+    pub(crate) fn post_requested(self: &Arc<Self>) {
+        let Some(choreographer) = self.choreographer else {
+            return;
+        };
+        self.register_refresh_rate_callback(&choreographer);
+        if !self.requested.swap(false, Ordering::SeqCst) || self.posted.swap(true, Ordering::SeqCst)
+        {
+            return;
+        }
+
+        unsafe {
+            let instance = (choreographer.get_instance)();
+            if instance.is_null() {
+                log::error!("Failed to get AChoreographer instance for the main thread");
+                self.posted.store(false, Ordering::SeqCst);
+                return;
+            }
+
+            // The callback takes ownership of this reference
+            let data = Arc::into_raw(Arc::clone(self)) as *mut c_void;
+            if let Some(post_frame_callback64) = choreographer.post_frame_callback64 {
+                post_frame_callback64(instance, frame_callback64, data);
+            } else if let Some(post_frame_callback) = choreographer.post_frame_callback {
+                post_frame_callback(instance, frame_callback, data);
+            } else {
+                drop(Arc::from_raw(data as *const Self));
+                self.posted.store(false, Ordering::SeqCst);
+            }
+        }
+    }
+
+    /// Registers the refresh rate callback, the first time this is called
+    ///
+    /// The registration lasts as long as the current thread's choreographer,
+    /// and so this has to be called on the main thread, like
+    /// [`Self::post_requested()`].
+    fn register_refresh_rate_callback(&self, choreographer: &Choreographer) {
+        let Some(register_refresh_rate_callback) = choreographer.register_refresh_rate_callback
+        else {
+            return;
+        };
+        if self.refresh_rate_registered.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        unsafe {
+            let instance = (choreographer.get_instance)();
+            if instance.is_null() {
+                log::error!("Failed to get AChoreographer instance for the main thread");
+                return;
+            }
+            // The callback can't be unregistered once the thread exits, so it
+            // doesn't have any data that it could outlive
+            register_refresh_rate_callback(instance, refresh_rate_callback, std::ptr::null_mut());
+        }
+    }
+
+    /// Takes the frame time of a callback that has run since the last call,
+    /// if any
+    pub(crate) fn take_frame(&self) -> Option<i64> {
+        self.frame_time_nanos.lock().unwrap().take()
+    }
+
+    fn on_frame(&self, frame_time_nanos: i64) {
+        *self.frame_time_nanos.lock().unwrap() = Some(frame_time_nanos);
+        self.posted.store(false, Ordering::SeqCst);
+        // `ALooper_pollAll()` handles the choreographer's callbacks internally
+        // and would otherwise keep waiting, so notify it to deliver the frame
+        self.notifier.notify(Notifications::FRAME);
+    }
+}
+
+unsafe extern "C" fn refresh_rate_callback(_vsync_period_nanos: i64, _data: *mut c_void) {
+    notify::notify_current(Notifications::REFRESH_RATE);
+}
+
+unsafe extern "C" fn frame_callback64(frame_time_nanos: i64, data: *mut c_void) {
+    let callbacks = Arc::from_raw(data as *const FrameCallbacks);
+    callbacks.on_frame(frame_time_nanos);
+}
+
+unsafe extern "C" fn frame_callback(frame_time_nanos: c_long, data: *mut c_void) {
+    let callbacks = Arc::from_raw(data as *const FrameCallbacks);
+    // `c_long` is only 32 bits on 32-bit targets
+    #[allow(clippy::useless_conversion)]
+    callbacks.on_frame(i64::from(frame_time_nanos));
+}
@@ -13,11 +13,13 @@
 // The `Class` was also bound differently to `android-ndk-rs` considering how the class is defined
 // by masking bits from the `Source`.
 
-use crate::activity_impl::ffi::{GameActivityKeyEvent, GameActivityMotionEvent};
+use crate::activity_impl::ffi::{self, GameActivityKeyEvent, GameActivityMotionEvent};
 use crate::input::{
-    Axis, Button, ButtonState, EdgeFlags, KeyAction, KeyEventFlags, Keycode, MetaState,
-    MotionAction, MotionEventFlags, Pointer, PointersIter, Source, ToolType,
+    Axis, Button, ButtonState, DecodedKeyAction, DecodedMotionAction, EdgeFlags, KeyAction,
+    KeyEventFlags, Keycode, MetaState, MotionAction, MotionClassification, MotionEventFlags,
+    MotionSample, MotionSamplesIter, Pointer, PointersIter, Source, ToolType,
 };
+use crate::input_devices;
 
 // Note: try to keep this wrapper API compatible with the AInputEvent API if possible
 
@@ -45,6 +47,7 @@ impl<'a> MotionEvent<'a> {
 
     /// Get the source of the event.
     ///
+    /// The source may be a combination of sources (see [`Source::contains()`]).
     #[inline]
     pub fn source(&self) -> Source {
         let source = self.ga_event.source as u32;
@@ -58,6 +61,12 @@ impl<'a> MotionEvent<'a> {
         self.ga_event.deviceId
     }
 
+    /// Get the id of the display that the event was targeted at (see [`DEFAULT_DISPLAY_ID`](crate::input::DEFAULT_DISPLAY_ID))
+    #[inline]
+    pub fn display_id(&self) -> i32 {
+        crate::input::DEFAULT_DISPLAY_ID
+    }
+
     /// Returns the motion action associated with the event.
     ///
     /// See [the MotionEvent docs](https://developer.android.com/reference/android/view/MotionEvent#getActionMasked())
@@ -95,6 +104,17 @@ impl<'a> MotionEvent<'a> {
         index as usize
     }
 
+    /// Returns the motion action, decoded together with the index of the
+    /// pointer for [`MotionAction::PointerDown`] and [`MotionAction::PointerUp`]
+    /// actions
+    ///
+    /// This saves having to separately call [`Self::pointer_index()`], which
+    /// is meaningless for other actions, when tracking multi-touch pointers.
+    #[inline]
+    pub fn decoded_action(&self) -> DecodedMotionAction {
+        DecodedMotionAction::new(self.action(), self.pointer_index())
+    }
+
     /*
     /// Returns the pointer id associated with the given pointer index.
     ///
@@ -140,6 +160,171 @@ impl<'a> MotionEvent<'a> {
         }
     }
 
+    /// The pointer that went down or up, for [`Down`](MotionAction::Down),
+    /// [`Up`](MotionAction::Up), [`PointerDown`](MotionAction::PointerDown) and
+    /// [`PointerUp`](MotionAction::PointerUp) actions
+    ///
+    /// This is the pointer at [`Self::pointer_index()`], whose
+    /// [`pointer_id()`](Pointer::pointer_id) can be used to track the pointer
+    /// across subsequent events. For other actions this is the first pointer.
+    #[inline]
+    pub fn action_pointer(&self) -> Pointer<'_> {
+        self.pointer_at_index(self.pointer_index())
+    }
+
+    /// Finds the pointer with the given (stable) pointer ID in this event
+    ///
+    /// Unlike pointer indices, which can change from one event to the next,
+    /// a pointer's ID stays the same for as long as it's down. Returns `None`
+    /// if there's no such pointer in this event (such as after it has gone
+    /// up).
+    #[inline]
+    pub fn pointer_by_id(&self, pointer_id: i32) -> Option<Pointer<'_>> {
+        self.pointers()
+            .find(|pointer| pointer.pointer_id() == pointer_id)
+    }
+
+    /// Returns the pressure of the pointer at the given index, normally in
+    /// the range `0.0` to `1.0`
+    ///
+    /// Returns `None` if the event's device doesn't report
+    /// [`Axis::Pressure`] for the event's source (such as for a mouse), instead
+    /// of a meaningless value. If the device can't be queried then this falls
+    /// back to returning `None` for mouse and unknown tools.
+    ///
+    /// _Note: touchscreens that don't sense pressure may still report a
+    /// nominal pressure of `1.0` for fingers, which can't be distinguished
+    /// here._
+    ///
+    /// _Note: with GameActivity this axis must first be enabled via
+    /// [`AndroidApp::enable_motion_axis()`](crate::AndroidApp::enable_motion_axis)._
+    ///
+    /// Panics if the pointer index is out of bounds.
+    #[inline]
+    pub fn pressure(&self, pointer_index: usize) -> Option<f32> {
+        let pointer = self.pointer_at_index(pointer_index);
+        let has_pressure = input_devices::has_pressure_axis(self.device_id(), self.source().into())
+            .unwrap_or_else(|| !matches!(pointer.tool_type(), ToolType::Mouse | ToolType::Unknown));
+        has_pressure.then(|| pointer.pressure())
+    }
+
+    /// Returns the tilt of the stylus at the given index away from the
+    /// perpendicular, in radians (see [`Pointer::tilt()`])
+    ///
+    /// _Note: with GameActivity this axis must first be enabled via
+    /// [`AndroidApp::enable_motion_axis()`](crate::AndroidApp::enable_motion_axis)._
+    ///
+    /// Panics if the pointer index is out of bounds.
+    #[inline]
+    pub fn tilt(&self, pointer_index: usize) -> f32 {
+        self.pointer_at_index(pointer_index).tilt()
+    }
+
+    /// Returns the orientation of the pointer at the given index, in radians
+    ///
+    /// For a stylus this is the direction that it's pointing in, where `0.0`
+    /// is "up" and positive values are clockwise.
+    ///
+    /// _Note: with GameActivity this axis must first be enabled via
+    /// [`AndroidApp::enable_motion_axis()`](crate::AndroidApp::enable_motion_axis)._
+    ///
+    /// Panics if the pointer index is out of bounds.
+    #[inline]
+    pub fn orientation(&self, pointer_index: usize) -> f32 {
+        self.pointer_at_index(pointer_index).orientation()
+    }
+
+    /// Returns the value of the given axis for the pointer at the given index
+    ///
+    /// For [`Source::Joystick`] events from a gamepad, the left stick is
+    /// reported via [`Axis::X`] and [`Axis::Y`], the right stick usually via
+    /// [`Axis::Z`] and [`Axis::Rz`], the D-pad via [`Axis::HatX`] and
+    /// [`Axis::HatY`] and the triggers via [`Axis::Ltrigger`] and
+    /// [`Axis::Rtrigger`] (or [`Axis::Brake`] and [`Axis::Gas`]). See
+    /// [`InputDeviceInfo::motion_range()`](crate::input::InputDeviceInfo::motion_range)
+    /// for each axis's dead zone.
+    ///
+    /// _Note: with GameActivity, axes other than [`Axis::X`] and [`Axis::Y`]
+    /// (such as the [`Axis::Z`] and [`Axis::Rz`] axes of a gamepad's right
+    /// stick) must first be enabled via
+    /// [`AndroidApp::enable_motion_axis()`](crate::AndroidApp::enable_motion_axis)._
+    ///
+    /// Panics if the pointer index is out of bounds.
+    #[inline]
+    pub fn axis_value(&self, axis: Axis, pointer_index: usize) -> f32 {
+        self.pointer_at_index(pointer_index).axis_value(axis)
+    }
+
+    /// Returns the `(horizontal, vertical)` scroll amounts of a mouse wheel
+    /// (or touchpad scroll gesture), for [`MotionAction::Scroll`] events
+    ///
+    /// These are the [`Axis::Hscroll`] and [`Axis::Vscroll`] axes, which are
+    /// relative values, normally between `-1.0` and `1.0`. A positive vertical
+    /// value means scrolling up (such as to zoom in).
+    ///
+    /// _Note: with GameActivity the [`Axis::Hscroll`] and [`Axis::Vscroll`]
+    /// axes must first be enabled via
+    /// [`AndroidApp::enable_motion_axis()`](crate::AndroidApp::enable_motion_axis)._
+    #[inline]
+    pub fn scroll(&self) -> (f32, f32) {
+        let pointer = self.pointer_at_index(0);
+        (
+            pointer.axis_value(Axis::Hscroll),
+            pointer.axis_value(Axis::Vscroll),
+        )
+    }
+
+    /// Returns `true` if this event reports relative motion, such as from a
+    /// trackball ([`Source::Trackball`]) or a relative mouse
+    /// ([`Source::MouseRelative`]), rather than absolute positions
+    #[inline]
+    pub fn is_relative(&self) -> bool {
+        self.source().is_trackball_class()
+    }
+
+    /// Returns `true` if this event was delivered while the pointer is captured
+    ///
+    /// While the pointer is captured (see
+    /// [`AndroidApp::request_pointer_capture()`](crate::AndroidApp::request_pointer_capture))
+    /// mouse events have a [`Source::MouseRelative`] source and touchpad events
+    /// have a [`Source::Touchpad`] source, and neither are delivered otherwise.
+    #[inline]
+    pub fn is_captured(&self) -> bool {
+        matches!(self.source(), Source::MouseRelative | Source::Touchpad)
+    }
+
+    /// Returns the relative `(x, y)` motion of the pointer at the given index
+    ///
+    /// For relative events (see [`MotionEvent::is_relative()`]) this is the
+    /// event's X and Y axes, which are already relative. Otherwise this is the
+    /// [`Axis::RelativeX`] and [`Axis::RelativeY`] axes, which report the
+    /// motion of a mouse since the last event (see [`Pointer::relative_x()`]),
+    /// such as for mouse-look in a first-person game.
+    ///
+    /// _Note: with GameActivity the [`Axis::RelativeX`] and [`Axis::RelativeY`]
+    /// axes must first be enabled via
+    /// [`AndroidApp::enable_motion_axis()`](crate::AndroidApp::enable_motion_axis)._
+    ///
+    /// Panics if the pointer index is out of bounds.
+    #[inline]
+    pub fn relative_axis_value(&self, pointer_index: usize) -> (f32, f32) {
+        let pointer = self.pointer_at_index(pointer_index);
+        if self.is_relative() {
+            (pointer.x(), pointer.y())
+        } else {
+            (pointer.relative_x(), pointer.relative_y())
+        }
+    }
+
+    /// Returns the type of tool, such as a finger, stylus or eraser, for the
+    /// pointer at the given index
+    ///
+    /// Panics if the pointer index is out of bounds.
+    #[inline]
+    pub fn tool_type(&self, pointer_index: usize) -> ToolType {
+        self.pointer_at_index(pointer_index).tool_type()
+    }
+
     /*
     /// Returns the size of the history contained in this event.
     ///
@@ -190,6 +375,13 @@ impl<'a> MotionEvent<'a> {
         self.ga_event.downTime
     }
 
+    /// Returns [`Self::down_time()`] as a [`Duration`](std::time::Duration) in the
+    /// [`AndroidApp::uptime()`](crate::AndroidApp::uptime) (`CLOCK_MONOTONIC`) time base
+    #[inline]
+    pub fn down_uptime(&self) -> std::time::Duration {
+        crate::util::event_time_to_duration(self.down_time())
+    }
+
     /// Returns a bitfield indicating which edges were touched by this event.
     ///
     /// See [the NDK
@@ -201,6 +393,9 @@ impl<'a> MotionEvent<'a> {
 
     /// Returns the time of this event, in the `java.lang.System.nanoTime()` time base
     ///
+    /// See [`AndroidApp::input_event_age()`](crate::AndroidApp::input_event_age) for
+    /// calculating how long ago the event occurred.
+    ///
     /// See [the NDK
     /// docs](https://developer.android.com/ndk/reference/group/input#amotionevent_geteventtime)
     #[inline]
@@ -208,6 +403,13 @@ impl<'a> MotionEvent<'a> {
         self.ga_event.eventTime
     }
 
+    /// Returns [`Self::event_time()`] as a [`Duration`](std::time::Duration) in the
+    /// [`AndroidApp::uptime()`](crate::AndroidApp::uptime) (`CLOCK_MONOTONIC`) time base
+    #[inline]
+    pub fn event_uptime(&self) -> std::time::Duration {
+        crate::util::event_time_to_duration(self.event_time())
+    }
+
     /// The flags associated with a motion event.
     ///
     /// See [the NDK
@@ -217,6 +419,102 @@ impl<'a> MotionEvent<'a> {
         MotionEventFlags(self.ga_event.flags as u32)
     }
 
+    /// Returns `true` if this event was delivered while the window was
+    /// (partially) obscured by another visible window above it.
+    ///
+    /// Security-sensitive UIs (e.g. for payments or passwords) can use this
+    /// to ignore touches that may have been induced by an overlay
+    /// ("tapjacking").
+    ///
+    /// This is a shorthand for `self.flags().window_is_obscured()`.
+    #[inline]
+    pub fn is_obscured(&self) -> bool {
+        self.flags().window_is_obscured()
+    }
+
+    /// Returns the classification of the gesture that this event is part of,
+    /// such as a deep press or an ambiguous gesture.
+    ///
+    /// Always returns [`MotionClassification::None`] on Android versions older
+    /// than 10 (API level 29).
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#amotionevent_getclassification)
+    #[inline]
+    pub fn classification(&self) -> MotionClassification {
+        if crate::AndroidApp::sdk_version() < 29 {
+            return MotionClassification::None;
+        }
+        (self.ga_event.classification as u32).into()
+    }
+
+    /// Returns the number of historical samples that were batched into this
+    /// event, not counting the event's current sample
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#amotionevent_gethistorysize)
+    #[inline]
+    pub fn history_size(&self) -> usize {
+        self.ga_event.historySize.max(0) as usize
+    }
+
+    /// An iterator over every sample of every pointer in this event, including
+    /// the historical samples that were batched into it
+    ///
+    /// High-frequency input, such as touch drawing, may be reported at a
+    /// higher rate than events are delivered, in which case the intermediate
+    /// samples are batched into the event's history. This yields the historical
+    /// samples, oldest first, followed by the event's current sample.
+    ///
+    /// _Note: GameActivity only reports the values of axes that have been
+    /// enabled via `GameActivityPointerAxes_enableAxis()` (by default `x` and
+    /// `y`), and other axes read as zero._
+    #[inline]
+    pub fn history_iter(&self) -> MotionSamplesIter<'_> {
+        MotionSamplesIter::new(self)
+    }
+
+    pub(crate) fn sample(
+        &self,
+        history_index: Option<usize>,
+        pointer_index: usize,
+    ) -> MotionSample {
+        let pointer = &self.ga_event.pointers[pointer_index];
+        let axis_value = |axis: Axis| -> f32 {
+            let axis: u32 = axis.into();
+            match history_index {
+                Some(history_index) => unsafe {
+                    ffi::GameActivityMotionEvent_getHistoricalAxisValue(
+                        self.ga_event,
+                        axis as _,
+                        pointer_index as _,
+                        history_index as _,
+                    )
+                },
+                None => pointer.axisValues[axis as usize],
+            }
+        };
+        let event_time = match history_index {
+            Some(history_index) => unsafe {
+                *self.ga_event.historicalEventTimesNanos.add(history_index)
+            },
+            None => self.ga_event.eventTime,
+        };
+        MotionSample {
+            pointer_index,
+            pointer_id: pointer.id,
+            history_index,
+            event_time,
+            x: axis_value(Axis::X),
+            y: axis_value(Axis::Y),
+            pressure: axis_value(Axis::Pressure),
+            size: axis_value(Axis::Size),
+            touch_major: axis_value(Axis::TouchMajor),
+            touch_minor: axis_value(Axis::TouchMinor),
+            orientation: axis_value(Axis::Orientation),
+        }
+    }
+
     /* Missing from GameActivity currently...
     /// Returns the offset in the x direction between the coordinates and the raw coordinates
     ///
@@ -663,6 +961,7 @@ impl<'a> KeyEvent<'a> {
 
     /// Get the source of the event.
     ///
+    /// The source may be a combination of sources (see [`Source::contains()`]).
     #[inline]
     pub fn source(&self) -> Source {
         let source = self.ga_event.source as u32;
@@ -676,6 +975,12 @@ impl<'a> KeyEvent<'a> {
         self.ga_event.deviceId
     }
 
+    /// Get the id of the display that the event was targeted at (see [`DEFAULT_DISPLAY_ID`](crate::input::DEFAULT_DISPLAY_ID))
+    #[inline]
+    pub fn display_id(&self) -> i32 {
+        crate::input::DEFAULT_DISPLAY_ID
+    }
+
     /// Returns the key action associated with the event.
     ///
     /// See [the KeyEvent docs](https://developer.android.com/reference/android/view/KeyEvent#getAction())
@@ -685,6 +990,17 @@ impl<'a> KeyEvent<'a> {
         action.into()
     }
 
+    /// Returns the key action, decoded together with the repeat count for
+    /// [`KeyAction::Multiple`] events.
+    ///
+    /// Text handling applications should handle [`DecodedKeyAction::Multiple`]
+    /// events (by repeating the key `count` times) to avoid dropping characters
+    /// from some input methods.
+    #[inline]
+    pub fn decoded_action(&self) -> DecodedKeyAction {
+        DecodedKeyAction::new(self.action(), self.repeat_count())
+    }
+
     #[inline]
     pub fn action_button(&self) -> KeyAction {
         let action = self.ga_event.action as u32;
@@ -701,9 +1017,19 @@ impl<'a> KeyEvent<'a> {
         self.ga_event.downTime
     }
 
+    /// Returns [`Self::down_time()`] as a [`Duration`](std::time::Duration) in the
+    /// [`AndroidApp::uptime()`](crate::AndroidApp::uptime) (`CLOCK_MONOTONIC`) time base
+    #[inline]
+    pub fn down_uptime(&self) -> std::time::Duration {
+        crate::util::event_time_to_duration(self.down_time())
+    }
+
     /// Returns the time this event occured.  This is on the scale of
     /// `java.lang.System.nanoTime()`, which has nanosecond precision, but no defined start time.
     ///
+    /// See [`AndroidApp::input_event_age()`](crate::AndroidApp::input_event_age) for
+    /// calculating how long ago the event occurred.
+    ///
     /// See [the NDK
     /// docs](https://developer.android.com/ndk/reference/group/input#akeyevent_geteventtime)
     #[inline]
@@ -711,6 +1037,13 @@ impl<'a> KeyEvent<'a> {
         self.ga_event.eventTime
     }
 
+    /// Returns [`Self::event_time()`] as a [`Duration`](std::time::Duration) in the
+    /// [`AndroidApp::uptime()`](crate::AndroidApp::uptime) (`CLOCK_MONOTONIC`) time base
+    #[inline]
+    pub fn event_uptime(&self) -> std::time::Duration {
+        crate::util::event_time_to_duration(self.event_time())
+    }
+
     /// Returns the keycode associated with this key event
     ///
     /// See [the NDK
@@ -730,8 +1063,22 @@ impl<'a> KeyEvent<'a> {
         self.ga_event.repeatCount
     }
 
+    /// Returns `true` if this is an auto-repeat of a key that's being held down
+    ///
+    /// This is equivalent to `repeat_count() > 0`. Games may want to ignore
+    /// repeats for movement keys, while text fields should normally honor
+    /// them.
+    #[inline]
+    pub fn is_repeat(&self) -> bool {
+        self.repeat_count() > 0
+    }
+
     /// Returns the hardware keycode of a key.  This varies from device to device.
     ///
+    /// Unlike [`Self::key_code()`], which depends on the keyboard layout, the scan code
+    /// identifies the physical key that was pressed, which can be useful for mapping
+    /// keys by position (such as for emulators or key remapping).
+    ///
     /// See [the NDK
     /// docs](https://developer.android.com/ndk/reference/group/input#akeyevent_getscancode)
     #[inline]
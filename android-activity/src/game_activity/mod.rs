@@ -1,14 +1,15 @@
 #![cfg(feature = "game-activity")]
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::marker::PhantomData;
-use std::ops::Deref;
+use std::ops::{Deref, Range};
 use std::panic::catch_unwind;
 use std::ptr;
 use std::ptr::NonNull;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Weak;
 use std::sync::{Arc, Mutex, RwLock};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use libc::c_void;
 use log::{error, trace};
@@ -23,11 +24,24 @@ use ndk::configuration::Configuration;
 use ndk::native_window::NativeWindow;
 
 use crate::error::InternalResult;
+use crate::frame_callback::FrameCallbacks;
 use crate::input::{Axis, KeyCharacterMap, KeyCharacterMapBinding};
+use crate::input_devices::InputDeviceTracker;
 use crate::jni_utils::{self, CloneJavaVM};
-use crate::util::{abort_on_panic, forward_stdio_to_logcat, log_panic, try_get_path_from_ptr};
+use crate::keep_screen_on::KeepScreenOn;
+use crate::listener::JavaListeners;
+use crate::notify::{LooperNotifier, Notifications, LOOPER_ID_NOTIFY};
+use crate::stats::LoopStatsCounters;
+use crate::util::{
+    abort_on_panic, drain_stdio_to_logcat, log_panic, try_get_path_from_ptr, utf16_to_byte_offset,
+    TEARDOWN_TIMEOUT,
+};
+use crate::visibility::VisibilityState;
+use crate::window_size::WindowSizeTracker;
 use crate::{
-    AndroidApp, ConfigurationRef, InputStatus, MainEvent, PollEvent, Rect, WindowManagerFlags,
+    AndroidApp, AnrRiskCallbackFn, Backend, ConfigChange, ConfigurationRef, CustomCmdHandler,
+    CustomCmdHandlerFn, DisplayMode, InputStatus, LifecycleState, MainEvent, PollEvent, Rect,
+    StreamType, Visibility, WindowManagerFlags,
 };
 
 mod ffi;
@@ -46,36 +60,51 @@ pub struct StateSaver<'a> {
 
 impl<'a> StateSaver<'a> {
     pub fn store(&self, state: &'a [u8]) {
+        self.store_with(state.len(), |buf| buf.copy_from_slice(state));
+    }
+
+    /// Stores `len` bytes of state that are written in place by `write`
+    ///
+    /// This avoids copying the state via an intermediate buffer. If state has
+    /// already been stored for this [MainEvent::SaveState] event then its
+    /// allocation is reused, if possible. If the buffer can't be allocated
+    /// then an error is logged, `write` isn't called and any previously
+    /// stored state is kept.
+    pub fn store_with(&self, len: usize, write: impl FnOnce(&mut [u8])) {
         // android_native_app_glue specifically expects savedState to have been allocated
         // via libc::malloc since it will automatically handle freeing the data once it
         // has been handed over to the Java Activity / main thread.
         unsafe {
             let app_ptr = self.app.native_app.as_ptr();
 
-            // In case the application calls store() multiple times for some reason we
-            // make sure to free any pre-existing state...
-            if !(*app_ptr).savedState.is_null() {
-                libc::free((*app_ptr).savedState);
-                (*app_ptr).savedState = ptr::null_mut();
-                (*app_ptr).savedStateSize = 0;
+            if len == 0 {
+                if !(*app_ptr).savedState.is_null() {
+                    libc::free((*app_ptr).savedState);
+                    (*app_ptr).savedState = ptr::null_mut();
+                    (*app_ptr).savedStateSize = 0;
+                }
+                write(&mut []);
+                return;
             }
 
-            let buf = libc::malloc(state.len());
+            // In case the application calls store() multiple times for some reason we
+            // resize any pre-existing state, which can usually be done in place
+            let buf = libc::realloc((*app_ptr).savedState, len);
             if buf.is_null() {
-                panic!("Failed to allocate save_state buffer");
+                // realloc() leaves the previous allocation (and so any previously
+                // stored state) untouched on failure
+                error!("Failed to allocate {len} byte save_state buffer");
+                return;
             }
+            (*app_ptr).savedState = buf;
+            (*app_ptr).savedStateSize = len as _;
 
             // Since it's a byte array there's no special alignment requirement here.
             //
             // Since we re-define `buf` we ensure it's not possible to access the buffer
             // via its original pointer for the lifetime of the slice.
-            {
-                let buf: &mut [u8] = std::slice::from_raw_parts_mut(buf.cast(), state.len());
-                buf.copy_from_slice(state);
-            }
-
-            (*app_ptr).savedState = buf;
-            (*app_ptr).savedStateSize = state.len() as _;
+            let buf: &mut [u8] = std::slice::from_raw_parts_mut(buf.cast(), len);
+            write(buf);
         }
     }
 }
@@ -100,8 +129,22 @@ impl<'a> StateLoader<'a> {
             }
         }
     }
+
+    /// Returns the length of whatever state was saved during the last
+    /// [MainEvent::SaveState] event, without copying it
+    pub fn saved_state_len(&self) -> usize {
+        unsafe {
+            let app_ptr = self.app.native_app.as_ptr();
+            if !(*app_ptr).savedState.is_null() {
+                (*app_ptr).savedStateSize
+            } else {
+                0
+            }
+        }
+    }
 }
 
+/// A means to wake up the main thread while it is blocked waiting for I/O
 #[derive(Clone)]
 pub struct AndroidAppWaker {
     // The looper pointer is owned by the android_app and effectively
@@ -113,6 +156,12 @@ unsafe impl Send for AndroidAppWaker {}
 unsafe impl Sync for AndroidAppWaker {}
 
 impl AndroidAppWaker {
+    /// Interrupts the main thread if it is blocked within [`AndroidApp::poll_events()`]
+    ///
+    /// If [`AndroidApp::poll_events()`] is interrupted it will invoke the poll
+    /// callback with a [PollEvent::Wake][wake_event] event.
+    ///
+    /// [wake_event]: crate::PollEvent::Wake
     pub fn wake(&self) {
         unsafe {
             ALooper_wake(self.looper.as_ptr());
@@ -135,16 +184,41 @@ impl AndroidApp {
         // and need to keep in mind that the Drop handler is going to call
         // AConfiguration_delete()
         let config = Configuration::clone_from_ptr(NonNull::new_unchecked((*ptr.as_ptr()).config));
+        let config = ConfigurationRef::new(config);
+        config.set_font_scale(ffi::GameActivity_getFontScale((*ptr.as_ptr()).activity));
+
+        let notifier = LooperNotifier::new((*ptr.as_ptr()).looper);
+        let java_listeners =
+            JavaListeners::register(&jvm, (*(*ptr.as_ptr()).activity).javaGameActivity as _);
 
         Self {
             inner: Arc::new(RwLock::new(AndroidAppInner {
                 jvm,
                 native_app: NativeAppGlue { ptr },
-                config: ConfigurationRef::new(config),
+                config,
                 native_window: Default::default(),
+                stopped: AtomicBool::new(false),
                 key_map_binding: Arc::new(key_map_binding),
                 key_maps: Mutex::new(HashMap::new()),
                 input_receiver: Mutex::new(None),
+                stats: Default::default(),
+                start_uptime: crate::AndroidApp::uptime(),
+                keep_screen_on: Default::default(),
+                visibility: Default::default(),
+                terminated_window_size: Mutex::new(None),
+                window_size: Default::default(),
+                input_devices: Default::default(),
+                text_input: Mutex::new(None),
+                frame_callbacks: FrameCallbacks::new(notifier.clone()),
+                notifier,
+                java_listeners,
+                display_mode: Mutex::new(None),
+                custom_cmd_handler: RwLock::new(None),
+                interactive: AtomicBool::new(true),
+                pointer_capture_requested: AtomicBool::new(false),
+                clip_timestamp: Mutex::new(None),
+                stream_volumes: Mutex::new(None),
+                ime_insets: Mutex::new(Rect::empty()),
             })),
         }
     }
@@ -167,6 +241,16 @@ unsafe impl Sync for NativeAppGlue {}
 impl NativeAppGlue {
     // TODO: move into a trait
     pub fn text_input_state(&self) -> TextInputState {
+        unsafe {
+            let app_ptr = self.as_ptr();
+            (*app_ptr).textInputState = 0;
+        }
+        self.peek_text_input_state()
+    }
+
+    /// Reads the text input state without clearing the `textInputState` flag,
+    /// so it's still delivered as an `InputEvent::TextEvent`
+    pub fn peek_text_input_state(&self) -> TextInputState {
         unsafe {
             let activity = (*self.as_ptr()).activity;
             let mut out_state = TextInputState {
@@ -176,9 +260,6 @@ impl NativeAppGlue {
             };
             let out_ptr = &mut out_state as *mut TextInputState;
 
-            let app_ptr = self.as_ptr();
-            (*app_ptr).textInputState = 0;
-
             // NEON WARNING:
             //
             // It's not clearly documented but the GameActivity API over the
@@ -258,6 +339,10 @@ pub struct AndroidAppInner {
     config: ConfigurationRef,
     native_window: RwLock<Option<NativeWindow>>,
 
+    /// Set after a `Stop` command so that we can emit a `Restart` event before
+    /// the next `Start` (GameActivity doesn't forward `onRestart`)
+    stopped: AtomicBool,
+
     /// Shared JNI bindings for the `KeyCharacterMap` class
     key_map_binding: Arc<KeyCharacterMapBinding>,
 
@@ -270,6 +355,68 @@ pub struct AndroidAppInner {
     /// InputReceiver reference which we track to ensure
     /// we don't hand out more than one receiver at a time
     input_receiver: Mutex<Option<Weak<InputReceiver>>>,
+
+    /// Event loop statistics, for profiling
+    pub(crate) stats: Arc<LoopStatsCounters>,
+
+    /// The [`AndroidApp::uptime()`] when this `AndroidApp` was created, as a
+    /// base for `event_time_since_start()`
+    pub(crate) start_uptime: Duration,
+
+    /// Tracks when to set `FLAG_KEEP_SCREEN_ON` for `set_keep_screen_on()`
+    keep_screen_on: KeepScreenOn,
+
+    /// Lifecycle and focus state for `visibility()`
+    visibility: VisibilityState,
+
+    /// The size of the last terminated window, for synthesizing `SurfaceRecreated`
+    /// events when a new window replaces it
+    terminated_window_size: Mutex<Option<(i32, i32)>>,
+
+    /// The last window size reported to the application, for coalescing
+    /// resizes that come with a configuration change
+    window_size: WindowSizeTracker,
+
+    /// The attached input devices, for `InputDeviceAdded`, `InputDeviceRemoved`
+    /// and `InputDeviceChanged` events
+    input_devices: InputDeviceTracker,
+
+    /// The last text and composing region reported to the application, for
+    /// coalescing `TextInput` events
+    text_input: Mutex<Option<(String, Option<Range<usize>>)>>,
+
+    /// Requested vsync callbacks, for `MainEvent::Frame` events
+    frame_callbacks: Arc<FrameCallbacks>,
+
+    /// Notifies the looper of frame callbacks and other synthesized events
+    notifier: Arc<LooperNotifier>,
+
+    /// Java listeners that notify the looper, such as for `ScreenStateChanged`
+    /// events
+    java_listeners: JavaListeners,
+
+    /// The last known display mode, for `DisplayModeChanged` events
+    display_mode: Mutex<Option<DisplayMode>>,
+
+    /// Handler for command bytes from `AndroidApp::CUSTOM_CMD_MIN` upwards
+    custom_cmd_handler: RwLock<Option<CustomCmdHandler>>,
+
+    /// The last known `PowerManager.isInteractive()` state, for `ScreenStateChanged` events
+    interactive: AtomicBool,
+
+    /// Whether pointer capture has been requested (and not released), for
+    /// `PointerCaptureChanged` events when the system releases it
+    pub(crate) pointer_capture_requested: AtomicBool,
+
+    /// The last known timestamp of the primary clip (`0` if there was none), for
+    /// `ClipboardChanged` events, or `None` before the window has first gained focus
+    clip_timestamp: Mutex<Option<i64>>,
+
+    /// The last known volumes of `StreamType::TRACKED`, for `VolumeChanged` events
+    stream_volumes: Mutex<Option<Vec<i32>>>,
+
+    /// The last known IME insets, for `ImeVisibilityChanged` events
+    ime_insets: Mutex<Rect>,
 }
 
 impl AndroidAppInner {
@@ -278,6 +425,11 @@ impl AndroidAppInner {
         unsafe { (*(*app_ptr).activity).vm as _ }
     }
 
+    pub fn native_activity_sdk_version(&self) -> i32 {
+        // `GameActivity` doesn't initialize its `sdkVersion` field
+        AndroidApp::sdk_version()
+    }
+
     pub fn activity_as_ptr(&self) -> *mut c_void {
         let app_ptr = self.native_app.as_ptr();
         unsafe { (*(*app_ptr).activity).javaGameActivity as _ }
@@ -306,16 +458,26 @@ impl AndroidAppInner {
                 -1
             };
             trace!("Calling ALooper_pollAll, timeout = {timeout_milliseconds}");
+            self.frame_callbacks.post_requested();
+            let poll_start = Instant::now();
             let id = ALooper_pollAll(
                 timeout_milliseconds,
                 &mut fd,
                 &mut events,
                 &mut source as *mut *mut core::ffi::c_void,
             );
+            if timeout_milliseconds != 0 {
+                self.stats.blocked(poll_start.elapsed());
+            }
             match id {
                 ffi::ALOOPER_POLL_WAKE => {
                     trace!("ALooper_pollAll returned POLL_WAKE");
 
+                    if let Some(text_input) = self.poll_text_input() {
+                        trace!("Invoking callback for synthesized TextInput event");
+                        callback(PollEvent::Main(text_input));
+                    }
+
                     if ffi::android_app_input_available_wake_up(native_app.as_ptr()) {
                         log::debug!("Notifying Input Available");
                         callback(PollEvent::Main(MainEvent::InputAvailable));
@@ -337,6 +499,10 @@ impl AndroidAppInner {
                     // not something we can recover from
                     panic!("ALooper_pollAll returned POLL_ERROR");
                 }
+                LOOPER_ID_NOTIFY => {
+                    trace!("ALooper_pollAll returned ID_NOTIFY");
+                    self.handle_notifications(&mut callback);
+                }
                 id if id >= 0 => {
                     match id as u32 {
                         ffi::NativeAppGlueLooperId_LOOPER_ID_MAIN => {
@@ -344,6 +510,12 @@ impl AndroidAppInner {
                             let source: *mut ffi::android_poll_source = source.cast();
                             if !source.is_null() {
                                 let cmd_i = ffi::android_app_read_cmd(native_app.as_ptr());
+                                self.stats.command_processed();
+
+                                if cmd_i >= AndroidApp::CUSTOM_CMD_MIN as i8 {
+                                    self.handle_custom_cmd(cmd_i as u8);
+                                    return;
+                                }
 
                                 let cmd = match cmd_i as u32 {
                                     //NativeAppGlueAppCmd_UNUSED_APP_CMD_INPUT_CHANGED => AndroidAppMainEvent::InputChanged,
@@ -354,7 +526,11 @@ impl AndroidAppInner {
                                         MainEvent::TerminateWindow {}
                                     }
                                     ffi::NativeAppGlueAppCmd_APP_CMD_WINDOW_RESIZED => {
-                                        MainEvent::WindowResized {}
+                                        // Filled in below, once the new window size is known
+                                        MainEvent::WindowResized {
+                                            width: 0,
+                                            height: 0,
+                                        }
                                     }
                                     ffi::NativeAppGlueAppCmd_APP_CMD_WINDOW_REDRAW_NEEDED => {
                                         MainEvent::RedrawNeeded {}
@@ -369,10 +545,23 @@ impl AndroidAppInner {
                                         MainEvent::LostFocus
                                     }
                                     ffi::NativeAppGlueAppCmd_APP_CMD_CONFIG_CHANGED => {
-                                        MainEvent::ConfigChanged {}
+                                        // Filled in below, once the new configuration is loaded
+                                        MainEvent::ConfigChanged {
+                                            changes: ConfigChange::empty(),
+                                            screen_width_dp: None,
+                                            screen_height_dp: None,
+                                        }
                                     }
                                     ffi::NativeAppGlueAppCmd_APP_CMD_LOW_MEMORY => {
-                                        MainEvent::LowMemory
+                                        // native_app_glue sends this command for each
+                                        // `onTrimMemory` callback, which we intercept to
+                                        // record the level
+                                        match TRIM_MEMORY_LEVELS.lock().unwrap().pop_front() {
+                                            Some(level) => MainEvent::TrimMemory {
+                                                level: level.into(),
+                                            },
+                                            None => MainEvent::LowMemory,
+                                        }
                                     }
                                     ffi::NativeAppGlueAppCmd_APP_CMD_START => MainEvent::Start,
                                     ffi::NativeAppGlueAppCmd_APP_CMD_RESUME => MainEvent::Resume {
@@ -396,11 +585,18 @@ impl AndroidAppInner {
 
                                 trace!("Calling android_app_pre_exec_cmd({cmd_i})");
                                 ffi::android_app_pre_exec_cmd(native_app.as_ptr(), cmd_i);
+                                if let MainEvent::TerminateWindow { .. } = cmd {
+                                    self.track_window_recreation(false);
+                                }
+                                let mut config_changes = ConfigChange::empty();
                                 match cmd {
                                     MainEvent::ConfigChanged { .. } => {
-                                        self.config.replace(Configuration::clone_from_ptr(
+                                        let latest = Configuration::clone_from_ptr(
                                             NonNull::new_unchecked((*native_app.as_ptr()).config),
-                                        ));
+                                        );
+                                        config_changes =
+                                            self.config.diff(&latest) | self.update_font_scale();
+                                        self.config.replace(latest);
                                     }
                                     MainEvent::InitWindow { .. } => {
                                         let win_ptr = (*native_app.as_ptr()).window;
@@ -415,11 +611,193 @@ impl AndroidAppInner {
                                     MainEvent::TerminateWindow { .. } => {
                                         *self.native_window.write().unwrap() = None;
                                     }
+                                    MainEvent::Start => {
+                                        self.visibility.set_started(true);
+                                        // Android always calls `onRestart` before `onStart`
+                                        // when restarting a stopped Activity
+                                        if self.stopped.swap(false, Ordering::SeqCst) {
+                                            trace!(
+                                                "Invoking callback for synthesized Restart event"
+                                            );
+                                            callback(PollEvent::Main(MainEvent::Restart));
+                                        }
+                                    }
+                                    MainEvent::Stop => {
+                                        self.stopped.store(true, Ordering::SeqCst);
+                                        self.visibility.set_started(false);
+                                    }
+                                    MainEvent::GainedFocus => self.visibility.set_focused(true),
+                                    MainEvent::LostFocus => self.visibility.set_focused(false),
                                     _ => {}
                                 }
 
-                                trace!("Invoking callback for ID_MAIN command = {:?}", cmd);
-                                callback(PollEvent::Main(cmd));
+                                let keep_screen_on_flags = match cmd {
+                                    MainEvent::Resume { .. } => {
+                                        self.keep_screen_on.set_resumed(true)
+                                    }
+                                    MainEvent::Pause => self.keep_screen_on.set_resumed(false),
+                                    MainEvent::GainedFocus => self.keep_screen_on.set_focused(true),
+                                    MainEvent::LostFocus => self.keep_screen_on.set_focused(false),
+                                    _ => None,
+                                };
+                                if let Some((add_flags, remove_flags)) = keep_screen_on_flags {
+                                    self.set_window_flags(add_flags, remove_flags);
+                                }
+
+                                let window_size = self
+                                    .native_window()
+                                    .map(|window| (window.width(), window.height()));
+                                let mut forward_cmd = true;
+                                let mut config_resized = None;
+                                let cmd = match cmd {
+                                    MainEvent::InitWindow { .. } => {
+                                        self.window_size.window_initialized(window_size);
+                                        cmd
+                                    }
+                                    MainEvent::TerminateWindow { .. } => {
+                                        self.window_size.window_terminated();
+                                        cmd
+                                    }
+                                    MainEvent::WindowResized { .. } => {
+                                        forward_cmd = self.window_size.window_resized(window_size);
+                                        let (width, height) = window_size.unwrap_or_default();
+                                        MainEvent::WindowResized { width, height }
+                                    }
+                                    MainEvent::ConfigChanged { .. } => {
+                                        if self.window_size.config_changed(window_size) {
+                                            let (width, height) = window_size.unwrap_or_default();
+                                            config_resized =
+                                                Some(MainEvent::WindowResized { width, height });
+                                        }
+                                        MainEvent::ConfigChanged {
+                                            changes: config_changes,
+                                            screen_width_dp: self.config.screen_width_dp(),
+                                            screen_height_dp: self.config.screen_height_dp(),
+                                        }
+                                    }
+                                    _ => cmd,
+                                };
+
+                                let surface_recreated = match cmd {
+                                    MainEvent::InitWindow { .. } => {
+                                        self.track_window_recreation(true)
+                                    }
+                                    MainEvent::Destroy => {
+                                        *self.terminated_window_size.lock().unwrap() = None;
+                                        self.java_listeners
+                                            .unregister(&self.jvm, self.activity_as_ptr() as _);
+                                        None
+                                    }
+                                    _ => None,
+                                };
+
+                                let screen_state_changed = match cmd {
+                                    MainEvent::Start
+                                    | MainEvent::Stop
+                                    | MainEvent::Pause
+                                    | MainEvent::Resume { .. }
+                                    | MainEvent::LostFocus
+                                    | MainEvent::GainedFocus => self.check_screen_state(),
+                                    _ => None,
+                                };
+                                if let Some(screen_state_changed) = screen_state_changed {
+                                    trace!(
+                                        "Invoking callback for synthesized ScreenStateChanged event"
+                                    );
+                                    callback(PollEvent::Main(screen_state_changed));
+                                }
+
+                                let clipboard_changed = match cmd {
+                                    MainEvent::GainedFocus => self.check_clipboard(),
+                                    _ => None,
+                                };
+
+                                // The system releases pointer capture when the window loses focus
+                                let pointer_capture_lost = matches!(cmd, MainEvent::LostFocus)
+                                    && self.pointer_capture_requested.swap(false, Ordering::SeqCst);
+
+                                let volumes_changed = match cmd {
+                                    MainEvent::Resume { .. } | MainEvent::GainedFocus => {
+                                        self.check_volumes()
+                                    }
+                                    _ => Vec::new(),
+                                };
+
+                                let input_devices_changed = match cmd {
+                                    MainEvent::Resume { .. } | MainEvent::GainedFocus => {
+                                        self.check_input_devices()
+                                    }
+                                    _ => Vec::new(),
+                                };
+
+                                let display_mode_changed = match cmd {
+                                    MainEvent::Resume { .. }
+                                    | MainEvent::GainedFocus
+                                    | MainEvent::ConfigChanged { .. }
+                                    | MainEvent::WindowResized { .. } => self.check_display_mode(),
+                                    _ => None,
+                                };
+
+                                let config_changed = match cmd {
+                                    MainEvent::Resume { .. } | MainEvent::GainedFocus => {
+                                        self.check_config()
+                                    }
+                                    _ => None,
+                                };
+
+                                let ime_changed = match cmd {
+                                    MainEvent::InsetsChanged { .. } => self.check_ime(),
+                                    _ => None,
+                                };
+
+                                if forward_cmd {
+                                    trace!("Invoking callback for ID_MAIN command = {:?}", cmd);
+                                    callback(PollEvent::Main(cmd));
+                                } else {
+                                    trace!("Ignoring WindowResized already reported with ConfigChanged");
+                                }
+                                if let Some(ime_changed) = ime_changed {
+                                    trace!("Invoking callback for synthesized ImeVisibilityChanged event");
+                                    callback(PollEvent::Main(ime_changed));
+                                }
+                                if let Some(config_resized) = config_resized {
+                                    trace!("Invoking callback for synthesized WindowResized event");
+                                    callback(PollEvent::Main(config_resized));
+                                }
+                                if let Some(config_changed) = config_changed {
+                                    trace!("Invoking callback for synthesized ConfigChanged event");
+                                    callback(PollEvent::Main(config_changed));
+                                }
+                                for volume_changed in volumes_changed {
+                                    trace!("Invoking callback for synthesized VolumeChanged event");
+                                    callback(PollEvent::Main(volume_changed));
+                                }
+                                for input_device_changed in input_devices_changed {
+                                    trace!("Invoking callback for synthesized {input_device_changed:?} event");
+                                    callback(PollEvent::Main(input_device_changed));
+                                }
+                                if let Some(display_mode_changed) = display_mode_changed {
+                                    trace!("Invoking callback for synthesized DisplayModeChanged event");
+                                    callback(PollEvent::Main(display_mode_changed));
+                                }
+                                if pointer_capture_lost {
+                                    trace!("Invoking callback for synthesized PointerCaptureChanged event");
+                                    callback(PollEvent::Main(MainEvent::PointerCaptureChanged {
+                                        captured: false,
+                                    }));
+                                }
+                                if let Some(clipboard_changed) = clipboard_changed {
+                                    trace!(
+                                        "Invoking callback for synthesized ClipboardChanged event"
+                                    );
+                                    callback(PollEvent::Main(clipboard_changed));
+                                }
+                                if let Some(surface_recreated) = surface_recreated {
+                                    trace!(
+                                        "Invoking callback for synthesized SurfaceRecreated event"
+                                    );
+                                    callback(PollEvent::Main(surface_recreated));
+                                }
 
                                 trace!("Calling android_app_post_exec_cmd({cmd_i})");
                                 ffi::android_app_post_exec_cmd(native_app.as_ptr(), cmd_i);
@@ -450,6 +828,220 @@ impl AndroidAppInner {
         }
     }
 
+    pub fn set_keep_screen_on(&self, keep_on: bool) {
+        if let Some((add_flags, remove_flags)) = self.keep_screen_on.set_requested(keep_on) {
+            self.set_window_flags(add_flags, remove_flags);
+        }
+    }
+
+    pub fn visibility(&self) -> Visibility {
+        self.visibility.get()
+    }
+
+    pub fn is_window_focused(&self) -> bool {
+        self.visibility.is_focused()
+    }
+
+    pub fn lifecycle_state(&self) -> LifecycleState {
+        let activity_state = unsafe {
+            let app_ptr = self.native_app.as_ptr();
+            let mutex: *mut libc::pthread_mutex_t = ptr::addr_of_mut!((*app_ptr).mutex).cast();
+            libc::pthread_mutex_lock(mutex);
+            let activity_state = (*app_ptr).activityState;
+            libc::pthread_mutex_unlock(mutex);
+            activity_state
+        };
+        match activity_state as u32 {
+            ffi::NativeAppGlueAppCmd_APP_CMD_START => LifecycleState::Start,
+            ffi::NativeAppGlueAppCmd_APP_CMD_RESUME => LifecycleState::Resume,
+            ffi::NativeAppGlueAppCmd_APP_CMD_PAUSE => LifecycleState::Pause,
+            ffi::NativeAppGlueAppCmd_APP_CMD_STOP => LifecycleState::Stop,
+            _ => LifecycleState::Init,
+        }
+    }
+
+    /// Tracks window termination + re-initialization so we can synthesize a
+    /// `SurfaceRecreated` event after an `InitWindow` that replaces a window
+    fn track_window_recreation(&self, init: bool) -> Option<MainEvent<'static>> {
+        let mut terminated_size = self.terminated_window_size.lock().unwrap();
+        let window_size = self
+            .native_window()
+            .map(|window| (window.width(), window.height()));
+        if init {
+            let old_size = terminated_size.take()?;
+            let new_size = window_size?;
+            Some(MainEvent::SurfaceRecreated { old_size, new_size })
+        } else {
+            *terminated_size = window_size;
+            None
+        }
+    }
+
+    /// Re-reads the configuration from the `AssetManager` so we can synthesize
+    /// a `ConfigChanged` event for changes that weren't reported via
+    /// `onConfigurationChanged`
+    ///
+    /// This is checked when our `ComponentCallbacks2` are notified of an
+    /// application-level change, and whenever the Activity resumes or gains
+    /// focus.
+    fn check_config(&self) -> Option<MainEvent<'static>> {
+        let latest = Configuration::from_asset_manager(&self.asset_manager());
+        let config = self.config();
+        let changes = config.diff(&latest) | self.update_font_scale();
+        if changes.is_empty() {
+            return None;
+        }
+        config.replace(latest);
+        Some(MainEvent::ConfigChanged {
+            changes,
+            screen_width_dp: config.screen_width_dp(),
+            screen_height_dp: config.screen_height_dp(),
+        })
+    }
+
+    /// Checks the attached input devices so we can synthesize
+    /// `InputDeviceAdded`, `InputDeviceRemoved` and `InputDeviceChanged` events
+    fn check_input_devices(&self) -> Vec<MainEvent<'static>> {
+        match jni_utils::input_device_sources(&self.jvm) {
+            Ok(devices) => self.input_devices.update(devices),
+            Err(err) => {
+                error!("Failed to query input devices: {err:?}");
+                Vec::new()
+            }
+        }
+    }
+
+    /// Checks the display's current mode so we can synthesize
+    /// `DisplayModeChanged` events
+    fn check_display_mode(&self) -> Option<MainEvent<'static>> {
+        if AndroidApp::sdk_version() < 23 {
+            return None;
+        }
+        let mode = match jni_utils::display_mode(&self.jvm, self.activity_as_ptr() as _) {
+            Ok(mode) => mode,
+            Err(err) => {
+                error!("Failed to query display mode: {err:?}");
+                return None;
+            }
+        };
+        let previous = self.display_mode.lock().unwrap().replace(mode.clone());
+        match previous {
+            Some(previous) if previous != mode => Some(MainEvent::DisplayModeChanged { mode }),
+            _ => None,
+        }
+    }
+
+    /// Checks the volume of each `StreamType::TRACKED` stream so we can
+    /// synthesize `VolumeChanged` events
+    fn check_volumes(&self) -> Vec<MainEvent<'static>> {
+        let streams = StreamType::TRACKED.map(i32::from);
+        let volumes =
+            match jni_utils::stream_volumes(&self.jvm, self.activity_as_ptr() as _, &streams) {
+                Ok(volumes) => volumes,
+                Err(err) => {
+                    error!("Failed to query stream volumes: {err:?}");
+                    return Vec::new();
+                }
+            };
+        let previous = self.stream_volumes.lock().unwrap().replace(volumes.clone());
+        let Some(previous) = previous else {
+            return Vec::new();
+        };
+        StreamType::TRACKED
+            .iter()
+            .zip(previous.iter().zip(&volumes))
+            .filter(|(_, (previous, volume))| previous != volume)
+            .map(|(&stream, _)| MainEvent::VolumeChanged { stream })
+            .collect()
+    }
+
+    /// Checks for changes to the IME insets after the window insets have changed
+    fn check_ime(&self) -> Option<MainEvent<'static>> {
+        let insets = self.ime_insets();
+        let previous = std::mem::replace(&mut *self.ime_insets.lock().unwrap(), insets.clone());
+        (previous != insets).then(|| MainEvent::ImeVisibilityChanged {
+            visible: insets != Rect::empty(),
+            insets,
+        })
+    }
+
+    /// Checks whether GameActivity has flagged a change to the text input
+    /// state, so we can synthesize a `TextInput` event from it
+    ///
+    /// The `textInputState` flag is left set, so the state is still delivered
+    /// as an `InputEvent::TextEvent` by the input iterator, which means we
+    /// have to remember the last reported text to avoid repeating the event.
+    fn poll_text_input(&self) -> Option<MainEvent<'static>> {
+        // XXX: See the note about the lack of atomic ops for this flag in
+        // `InputIteratorInner::next`
+        if unsafe { (*self.native_app.as_ptr()).textInputState } == 0 {
+            return None;
+        }
+        let state = self.native_app.peek_text_input_state();
+        let composing = state.compose_region.map(|span| {
+            let start = utf16_to_byte_offset(&state.text, span.start.min(span.end));
+            let end = utf16_to_byte_offset(&state.text, span.start.max(span.end));
+            start..end
+        });
+        let current = (state.text, composing);
+        let mut last = self.text_input.lock().unwrap();
+        if last.as_ref() == Some(&current) {
+            return None;
+        }
+        *last = Some(current.clone());
+        let (text, composing) = current;
+        Some(MainEvent::TextInput { text, composing })
+    }
+
+    /// Checks for changes to the primary clip, which is only accessible while
+    /// the window is focused
+    fn check_clipboard(&self) -> Option<MainEvent<'static>> {
+        if AndroidApp::sdk_version() < 26 {
+            return None;
+        }
+        let timestamp =
+            match jni_utils::primary_clip_timestamp(&self.jvm, self.activity_as_ptr() as _) {
+                Ok(timestamp) => timestamp.unwrap_or(0),
+                Err(err) => {
+                    error!("Failed to query primary clip: {err:?}");
+                    return None;
+                }
+            };
+        let previous = self.clip_timestamp.lock().unwrap().replace(timestamp);
+        matches!(previous, Some(previous) if previous != timestamp)
+            .then_some(MainEvent::ClipboardChanged)
+    }
+
+    /// Handles a notification from our `OnPrimaryClipChangedListener`
+    ///
+    /// The new timestamp is recorded (if it's accessible) so that the change
+    /// isn't reported again when the window next gains focus.
+    fn on_clip_changed(&self) -> MainEvent<'static> {
+        if AndroidApp::sdk_version() >= 26 {
+            match jni_utils::primary_clip_timestamp(&self.jvm, self.activity_as_ptr() as _) {
+                Ok(timestamp) => {
+                    *self.clip_timestamp.lock().unwrap() = Some(timestamp.unwrap_or(0));
+                }
+                Err(err) => error!("Failed to query primary clip: {err:?}"),
+            }
+        }
+        MainEvent::ClipboardChanged
+    }
+
+    /// Checks `PowerManager.isInteractive()` so we can synthesize a
+    /// `ScreenStateChanged` event if the screen has turned off or on
+    fn check_screen_state(&self) -> Option<MainEvent<'static>> {
+        let interactive = match jni_utils::is_interactive(&self.jvm, self.activity_as_ptr() as _) {
+            Ok(interactive) => interactive,
+            Err(err) => {
+                error!("Failed to query whether the screen is interactive: {err:?}");
+                return None;
+            }
+        };
+        let previous = self.interactive.swap(interactive, Ordering::SeqCst);
+        (previous != interactive).then_some(MainEvent::ScreenStateChanged { interactive })
+    }
+
     // TODO: move into a trait
     pub fn show_soft_input(&self, show_implicit: bool) {
         unsafe {
@@ -543,6 +1135,10 @@ impl AndroidAppInner {
         Ok(key_map)
     }
 
+    pub fn reattach_input(&self) {
+        // NOP: GameActivity input isn't delivered via an `AInputQueue` attached to our looper
+    }
+
     pub fn enable_motion_axis(&mut self, axis: Axis) {
         let axis: u32 = axis.into();
         unsafe { ffi::GameActivityPointerAxes_enableAxis(axis as i32) }
@@ -553,6 +1149,79 @@ impl AndroidAppInner {
         unsafe { ffi::GameActivityPointerAxes_disableAxis(axis as i32) }
     }
 
+    /// Delivers the events for notifications from other threads, such as frame
+    /// callbacks, after the looper has returned `LOOPER_ID_NOTIFY`
+    fn handle_notifications<F>(&self, callback: &mut F)
+    where
+        F: FnMut(PollEvent<'_>),
+    {
+        let notifications = self.notifier.take();
+        if notifications.contains(Notifications::SCREEN_STATE) {
+            if let Some(screen_state_changed) = self.check_screen_state() {
+                trace!("Invoking callback for synthesized ScreenStateChanged event");
+                callback(PollEvent::Main(screen_state_changed));
+            }
+        }
+        if notifications.contains(Notifications::CONFIG) {
+            if let Some(config_changed) = self.check_config() {
+                trace!("Invoking callback for synthesized ConfigChanged event");
+                callback(PollEvent::Main(config_changed));
+            }
+        }
+        if notifications.contains(Notifications::INPUT_DEVICES) {
+            for input_device_changed in self.check_input_devices() {
+                trace!("Invoking callback for synthesized {input_device_changed:?} event");
+                callback(PollEvent::Main(input_device_changed));
+            }
+        }
+        if notifications.contains(Notifications::VOLUME) {
+            for volume_changed in self.check_volumes() {
+                trace!("Invoking callback for synthesized VolumeChanged event");
+                callback(PollEvent::Main(volume_changed));
+            }
+        }
+        if notifications.contains(Notifications::CLIPBOARD) {
+            trace!("Invoking callback for synthesized ClipboardChanged event");
+            callback(PollEvent::Main(self.on_clip_changed()));
+        }
+        if notifications.contains(Notifications::REFRESH_RATE) {
+            if let Some(display_mode_changed) = self.check_display_mode() {
+                trace!("Invoking callback for synthesized DisplayModeChanged event");
+                callback(PollEvent::Main(display_mode_changed));
+            }
+        }
+        if notifications.contains(Notifications::FRAME) {
+            if let Some(frame_time_nanos) = self.frame_callbacks.take_frame() {
+                trace!("Invoking callback for synthesized Frame event");
+                callback(PollEvent::Main(MainEvent::Frame { frame_time_nanos }));
+            }
+        }
+    }
+
+    pub fn set_anr_risk_callback(&self, _callback: Option<(Duration, AnrRiskCallbackFn)>) {
+        // NOP: The JVM main thread synchronization is handled by the C glue layer
+    }
+
+    pub fn set_custom_cmd_handler(&self, handler: Option<CustomCmdHandlerFn>) {
+        *self.custom_cmd_handler.write().unwrap() = handler.map(CustomCmdHandler);
+    }
+
+    fn handle_custom_cmd(&self, cmd: u8) {
+        // Clone the handler so it's not called while holding the lock, in case
+        // it (un)registers a handler itself
+        let handler = self.custom_cmd_handler.read().unwrap().clone();
+        if let Some(handler) = handler {
+            trace!("Invoking custom command handler for cmd = {cmd}");
+            (handler.0)(cmd);
+        } else {
+            error!("Ignoring custom command {cmd} with no registered handler");
+        }
+    }
+
+    pub fn backend(&self) -> Backend {
+        Backend::GameActivity
+    }
+
     pub fn create_waker(&self) -> AndroidAppWaker {
         unsafe {
             // From the application's pov we assume the app_ptr and looper pointer
@@ -564,10 +1233,42 @@ impl AndroidAppInner {
         }
     }
 
+    pub fn wake(&self) {
+        unsafe {
+            let app_ptr = self.native_app.as_ptr();
+            ALooper_wake((*app_ptr).looper);
+        }
+    }
+
+    pub fn request_frame_callback(&self) -> bool {
+        if !self.frame_callbacks.request() {
+            return false;
+        }
+        // The request is posted from `poll_events()`, so wake up the main
+        // thread if it may be blocked waiting for events
+        if unsafe { ndk_sys::ALooper_forThread() }.is_null() {
+            self.wake();
+        }
+        true
+    }
+
     pub fn config(&self) -> ConfigurationRef {
         self.config.clone()
     }
 
+    /// Checks the font scale after a configuration change, returning
+    /// `ConfigChange::FONT_SCALE` if it changed
+    fn update_font_scale(&self) -> ConfigChange {
+        self.config.set_font_scale(self.font_scale())
+    }
+
+    pub fn font_scale(&self) -> f32 {
+        unsafe {
+            let activity = (*self.native_app.as_ptr()).activity;
+            ffi::GameActivity_getFontScale(activity)
+        }
+    }
+
     pub fn content_rect(&self) -> Rect {
         unsafe {
             let app_ptr = self.native_app.as_ptr();
@@ -580,6 +1281,19 @@ impl AndroidAppInner {
         }
     }
 
+    pub fn ime_insets(&self) -> Rect {
+        unsafe {
+            let activity = (*self.native_app.as_ptr()).activity;
+            let mut insets: ndk_sys::ARect = Rect::empty().into();
+            ffi::GameActivity_getWindowInsets(
+                activity,
+                ffi::GameCommonInsetsType_GAMECOMMON_INSETS_TYPE_IME,
+                &mut insets,
+            );
+            insets.into()
+        }
+    }
+
     pub fn asset_manager(&self) -> AssetManager {
         unsafe {
             let app_ptr = self.native_app.as_ptr();
@@ -894,7 +1608,63 @@ pub unsafe extern "C" fn GameActivity_onCreate(
     saved_state: *mut ::std::os::raw::c_void,
     saved_state_size: libc::size_t,
 ) {
+    // We're running on the Java main thread, which lets us register a
+    // callback with its looper
+    crate::ui_thread::init();
+
+    // The `ndk_context` is associated with the lifetime of the Activity (not
+    // the `android_main` thread) and is released in `on_destroy`, so that it
+    // remains valid for other threads even if `android_main` returns early
+    let jvm = (*activity).vm;
+    let java_activity: jobject = (*activity).javaGameActivity;
+    ndk_context::initialize_android_context(jvm.cast(), java_activity.cast());
+
     GameActivity_onCreate_C(activity, saved_state, saved_state_size);
+
+    // Wrap the native_app_glue `onDestroy` callback so we can release the
+    // `ndk_context` once the `android_main` thread has exited
+    let callbacks = (*activity).callbacks;
+    *GLUE_ON_DESTROY.lock().unwrap() = (*callbacks).onDestroy;
+    (*callbacks).onDestroy = Some(on_destroy);
+
+    // Wrap the native_app_glue `onTrimMemory` callback, which doesn't pass on
+    // the level, so we can report it via `MainEvent::TrimMemory`
+    TRIM_MEMORY_LEVELS.lock().unwrap().clear();
+    *GLUE_ON_TRIM_MEMORY.lock().unwrap() = (*callbacks).onTrimMemory;
+    (*callbacks).onTrimMemory = Some(on_trim_memory);
+}
+
+type OnTrimMemoryCallback = unsafe extern "C" fn(activity: *mut ffi::GameActivity, level: i32);
+static GLUE_ON_TRIM_MEMORY: Mutex<Option<OnTrimMemoryCallback>> = Mutex::new(None);
+
+/// Trim memory levels, in the same order as the `APP_CMD_LOW_MEMORY` commands
+/// that native_app_glue sends for them
+static TRIM_MEMORY_LEVELS: Mutex<VecDeque<i32>> = Mutex::new(VecDeque::new());
+
+unsafe extern "C" fn on_trim_memory(activity: *mut ffi::GameActivity, level: i32) {
+    abort_on_panic(|| {
+        let glue_on_trim_memory = *GLUE_ON_TRIM_MEMORY.lock().unwrap();
+        if let Some(glue_on_trim_memory) = glue_on_trim_memory {
+            TRIM_MEMORY_LEVELS.lock().unwrap().push_back(level);
+            glue_on_trim_memory(activity, level);
+        }
+    })
+}
+
+type OnDestroyCallback = unsafe extern "C" fn(activity: *mut ffi::GameActivity);
+static GLUE_ON_DESTROY: Mutex<Option<OnDestroyCallback>> = Mutex::new(None);
+
+unsafe extern "C" fn on_destroy(activity: *mut ffi::GameActivity) {
+    abort_on_panic(|| {
+        // Note: native_app_glue's `onDestroy` blocks until the `android_main`
+        // thread has exited
+        let glue_on_destroy = *GLUE_ON_DESTROY.lock().unwrap();
+        if let Some(glue_on_destroy) = glue_on_destroy {
+            glue_on_destroy(activity);
+        }
+
+        ndk_context::release_android_context();
+    })
 }
 
 extern "Rust" {
@@ -907,21 +1677,27 @@ extern "Rust" {
 #[no_mangle]
 pub unsafe extern "C" fn _rust_glue_entry(native_app: *mut ffi::android_app) {
     abort_on_panic(|| {
-        let _join_log_forwarder = forward_stdio_to_logcat();
+        #[cfg(feature = "native-stdout-stderr-logging")]
+        let _join_log_forwarder = crate::util::forward_stdio_to_logcat();
 
         let jvm = unsafe {
             let jvm = (*(*native_app).activity).vm;
-            let activity: jobject = (*(*native_app).activity).javaGameActivity;
-            ndk_context::initialize_android_context(jvm.cast(), activity.cast());
-
-            let jvm = CloneJavaVM::from_raw(jvm).unwrap();
-            // Since this is a newly spawned thread then the JVM hasn't been attached
-            // to the thread yet. Attach before calling the applications main function
-            // so they can safely make JNI calls
-            jvm.attach_current_thread_permanently().unwrap();
-            jvm
+            CloneJavaVM::from_raw(jvm).unwrap()
         };
 
+        // Since this is a newly spawned thread then the JVM hasn't been attached
+        // to the thread yet. Attach before calling the applications main function
+        // so they can safely make JNI calls
+        if !jvm.attach_main_thread() {
+            // Without JNI we can't even create the `AndroidApp`, so we finish the
+            // Activity instead of running `android_main`
+            log::error!("JNI is unavailable for the android_main thread; finishing Activity without running android_main");
+            unsafe {
+                ffi::GameActivity_finish((*native_app).activity);
+            }
+            return;
+        }
+
         unsafe {
             // Name thread - this needs to happen here after attaching to a JVM thread,
             // since that changes the thread name to something like "Thread-2".
@@ -951,11 +1727,13 @@ pub unsafe extern "C" fn _rust_glue_entry(native_app: *mut ffi::android_app) {
             //  to the main thread of the process where the Java finish call will take place"
             ffi::GameActivity_finish((*native_app).activity);
 
+            // Bounded, so that a wedged forwarder thread can't stop us from
+            // returning, which is what unblocks the Activity's `onDestroy`
+            drain_stdio_to_logcat(TEARDOWN_TIMEOUT);
+
             // This should detach automatically but lets detach explicitly to avoid depending
             // on the TLS trickery in `jni-rs`
             jvm.detach_current_thread();
-
-            ndk_context::release_android_context();
         }
     })
 }
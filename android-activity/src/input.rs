@@ -6,6 +6,17 @@ use crate::InputStatus;
 mod sdk;
 pub use sdk::*;
 
+/// The id of the primary display
+/// ([`Display.DEFAULT_DISPLAY`](https://developer.android.com/reference/android/view/Display#DEFAULT_DISPLAY)),
+/// as reported by `MotionEvent::display_id()` and `KeyEvent::display_id()`
+///
+/// _Note: the display id of input events isn't exposed by the NDK's
+/// `AInputEvent` API (for `NativeActivity`) or by `GameActivity`'s input
+/// events, so the event `display_id()` methods always return this id for now.
+/// Since an Activity's window is only ever on one display, events can be
+/// routed to the display that the Activity is currently on instead._
+pub const DEFAULT_DISPLAY_ID: i32 = 0;
+
 /// An enum representing the source of an [`MotionEvent`] or [`KeyEvent`]
 ///
 /// See [the InputDevice docs](https://developer.android.com/reference/android/view/InputDevice#SOURCE_ANY)
@@ -91,6 +102,20 @@ bitflags! {
 }
 
 impl Source {
+    /// Returns `true` if all the bits of `other` are set in this source
+    ///
+    /// Events (and devices) can have a combination of sources, such as a
+    /// keyboard that also has a D-pad, that doesn't match any single variant.
+    /// This checks for a specific source within such a combination, such as
+    /// to tell a mouse ([`Source::Mouse`]) or stylus ([`Source::Stylus`]) apart
+    /// from a finger on a touchscreen ([`Source::Touchscreen`]).
+    #[inline]
+    pub fn contains(self, other: Source) -> bool {
+        let bits: u32 = self.into();
+        let other: u32 = other.into();
+        bits & other == other
+    }
+
     #[inline]
     pub fn is_button_class(self) -> bool {
         let class = SourceFlags::from_bits_truncate(self.into());
@@ -118,6 +143,163 @@ impl Source {
     }
 }
 
+/// Describes whether an input event came from a genuine touchscreen or from a
+/// virtual device, such as for events injected by a test framework or remote
+/// control tool
+///
+/// See [`AndroidApp::input_origin()`](crate::AndroidApp::input_origin)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct InputOrigin {
+    /// The raw `InputDevice` source bitmask of the event
+    pub source: u32,
+
+    /// Whether the event's device is virtual, according to
+    /// [`InputDevice.isVirtual()`](https://developer.android.com/reference/android/view/InputDevice#isVirtual())
+    ///
+    /// This is also `true` if the device is unknown, which is the case for
+    /// most injected events.
+    pub is_virtual_device: bool,
+
+    /// Whether the event came from a touchscreen (its source includes
+    /// [`Source::Touchscreen`]) that's a real, physical device
+    pub is_genuine_touch: bool,
+}
+
+/// The range of values reported for one axis of an input device
+///
+/// See [`InputDevice.MotionRange`](https://developer.android.com/reference/android/view/InputDevice.MotionRange)
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct MotionRange {
+    /// The axis that this range applies to
+    pub axis: Axis,
+
+    /// The raw `InputDevice` source bitmask that reports this axis
+    pub source: u32,
+
+    /// The minimum value of the axis
+    pub min: f32,
+
+    /// The maximum value of the axis
+    pub max: f32,
+
+    /// The extent of the center flat position, within which the axis should
+    /// be considered centered (such as for the dead zone of a joystick)
+    pub flat: f32,
+
+    /// The error tolerance (noise) of the axis
+    pub fuzz: f32,
+
+    /// The resolution of the axis, in units per millimeter (or per radian for
+    /// angular axes), or `0.0` if unknown
+    pub resolution: f32,
+}
+
+impl MotionRange {
+    /// Applies the range's [`flat`](Self::flat) dead zone to an axis value,
+    /// returning `0.0` for values within the dead zone
+    ///
+    /// This avoids drift from analog sticks that don't report exactly `0.0`
+    /// while centered.
+    pub fn apply_flat(&self, value: f32) -> f32 {
+        if value.abs() <= self.flat {
+            0.0
+        } else {
+            value
+        }
+    }
+}
+
+/// Describes an attached input device, such as a keyboard, gamepad or
+/// touchscreen, and its capabilities
+///
+/// See [`AndroidApp::input_device_info()`](crate::AndroidApp::input_device_info)
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct InputDeviceInfo {
+    /// The device ID, as reported by `device_id()` for events from this device
+    pub id: i32,
+
+    /// The user-visible name of the device
+    pub name: String,
+
+    /// The raw `InputDevice` source bitmask of all the sources the device supports
+    ///
+    /// See [`Self::has_source()`]
+    pub sources: u32,
+
+    /// The USB vendor ID of the device, or `0` if unknown
+    pub vendor_id: i32,
+
+    /// The USB product ID of the device, or `0` if unknown
+    pub product_id: i32,
+
+    /// Whether the device is virtual, rather than a physical device
+    pub is_virtual: bool,
+
+    /// The ranges of the axes that the device reports
+    pub motion_ranges: Vec<MotionRange>,
+}
+
+impl InputDeviceInfo {
+    /// Returns `true` if the device supports the given source
+    ///
+    /// For example, a gamepad supports [`Source::Gamepad`] and usually also
+    /// [`Source::Joystick`] and [`Source::Dpad`].
+    pub fn has_source(&self, source: Source) -> bool {
+        let source: u32 = source.into();
+        self.sources & source == source
+    }
+
+    /// Returns `true` if the device reports the given axis, for any source
+    pub fn has_axis(&self, axis: Axis) -> bool {
+        self.motion_ranges.iter().any(|range| range.axis == axis)
+    }
+
+    /// Returns the range of the given axis, for the first source that reports it
+    ///
+    /// See [`Self::motion_range_for_source()`] for devices that report the
+    /// same axis for multiple sources.
+    pub fn motion_range(&self, axis: Axis) -> Option<MotionRange> {
+        self.motion_ranges
+            .iter()
+            .find(|range| range.axis == axis)
+            .copied()
+    }
+
+    /// Returns the range of the given axis, as reported for the given source
+    ///
+    /// For example, pass [`Source::Joystick`] for the ranges of a gamepad's
+    /// analog sticks and triggers.
+    pub fn motion_range_for_source(&self, axis: Axis, source: Source) -> Option<MotionRange> {
+        let source: u32 = source.into();
+        self.motion_ranges
+            .iter()
+            .find(|range| range.axis == axis && range.source & source == source)
+            .copied()
+    }
+
+    /// Returns `true` if the device is a gamepad or joystick with two analog
+    /// sticks
+    ///
+    /// The left stick is reported via [`Axis::X`] and [`Axis::Y`], and the
+    /// right stick via either [`Axis::Z`] and [`Axis::Rz`] (on most devices)
+    /// or [`Axis::Rx`] and [`Axis::Ry`].
+    pub fn has_dual_analog_sticks(&self) -> bool {
+        let joystick: u32 = Source::Joystick.into();
+        let has_joystick_axis = |axis| {
+            self.motion_ranges
+                .iter()
+                .any(|range| range.axis == axis && range.source & joystick == joystick)
+        };
+        has_joystick_axis(Axis::X)
+            && has_joystick_axis(Axis::Y)
+            && ((has_joystick_axis(Axis::Z) && has_joystick_axis(Axis::Rz))
+                || (has_joystick_axis(Axis::Rx) && has_joystick_axis(Axis::Ry)))
+    }
+}
+
 /// A bitfield representing the state of modifier keys during an event.
 ///
 /// See [the NDK docs](https://developer.android.com/ndk/reference/group/input#anonymous-enum-25)
@@ -125,6 +307,60 @@ impl Source {
 pub struct MetaState(pub u32);
 
 impl MetaState {
+    pub const NONE: Self = Self(ndk_sys::AMETA_NONE);
+    pub const ALT_ON: Self = Self(ndk_sys::AMETA_ALT_ON);
+    pub const ALT_LEFT_ON: Self = Self(ndk_sys::AMETA_ALT_LEFT_ON);
+    pub const ALT_RIGHT_ON: Self = Self(ndk_sys::AMETA_ALT_RIGHT_ON);
+    pub const SHIFT_ON: Self = Self(ndk_sys::AMETA_SHIFT_ON);
+    pub const SHIFT_LEFT_ON: Self = Self(ndk_sys::AMETA_SHIFT_LEFT_ON);
+    pub const SHIFT_RIGHT_ON: Self = Self(ndk_sys::AMETA_SHIFT_RIGHT_ON);
+    pub const SYM_ON: Self = Self(ndk_sys::AMETA_SYM_ON);
+    pub const FUNCTION_ON: Self = Self(ndk_sys::AMETA_FUNCTION_ON);
+    pub const CTRL_ON: Self = Self(ndk_sys::AMETA_CTRL_ON);
+    pub const CTRL_LEFT_ON: Self = Self(ndk_sys::AMETA_CTRL_LEFT_ON);
+    pub const CTRL_RIGHT_ON: Self = Self(ndk_sys::AMETA_CTRL_RIGHT_ON);
+    pub const META_ON: Self = Self(ndk_sys::AMETA_META_ON);
+    pub const META_LEFT_ON: Self = Self(ndk_sys::AMETA_META_LEFT_ON);
+    pub const META_RIGHT_ON: Self = Self(ndk_sys::AMETA_META_RIGHT_ON);
+    pub const CAPS_LOCK_ON: Self = Self(ndk_sys::AMETA_CAPS_LOCK_ON);
+    pub const NUM_LOCK_ON: Self = Self(ndk_sys::AMETA_NUM_LOCK_ON);
+    pub const SCROLL_LOCK_ON: Self = Self(ndk_sys::AMETA_SCROLL_LOCK_ON);
+
+    /// The generic (either side) Shift, Ctrl, Alt and Meta modifiers, see
+    /// [`Self::modifiers()`]
+    const MODIFIERS: u32 = ndk_sys::AMETA_SHIFT_ON
+        | ndk_sys::AMETA_CTRL_ON
+        | ndk_sys::AMETA_ALT_ON
+        | ndk_sys::AMETA_META_ON;
+
+    /// Returns `true` if all of the bits in `other` are set
+    #[inline]
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns `true` if no modifiers or lock states are set
+    #[inline]
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns just the generic Shift, Ctrl, Alt and Meta modifiers,
+    /// without left/right variants or lock states
+    ///
+    /// This is useful for matching keyboard shortcuts, which shouldn't depend
+    /// on (for example) whether Caps Lock is on:
+    ///
+    /// ```ignore
+    /// if key_event.meta_state().modifiers() == MetaState::CTRL_ON | MetaState::SHIFT_ON {
+    ///     // Ctrl+Shift+<key>
+    /// }
+    /// ```
+    #[inline]
+    pub fn modifiers(self) -> Self {
+        Self(self.0 & Self::MODIFIERS)
+    }
+
     #[inline]
     pub fn alt_on(self) -> bool {
         self.0 & ndk_sys::AMETA_ALT_ON != 0
@@ -195,6 +431,15 @@ impl MetaState {
     }
 }
 
+impl std::ops::BitOr for MetaState {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
 impl From<ndk::event::MetaState> for MetaState {
     fn from(value: ndk::event::MetaState) -> Self {
         Self(value.0)
@@ -238,6 +483,63 @@ pub enum MotionAction {
     __Unknown(u32),
 }
 
+/// A [`MotionAction`] that's been decoded together with the index of the
+/// pointer that the action applies to, for multi-touch down and up actions
+///
+/// See [`MotionEvent::decoded_action()`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodedMotionAction {
+    /// The first pointer has gone down
+    Down,
+    /// The last pointer has gone up
+    Up,
+    Move,
+    Cancel,
+    Outside,
+    /// A non-primary pointer, at the given pointer index, has gone down
+    PointerDown {
+        index: usize,
+    },
+    /// A non-primary pointer, at the given pointer index, has gone up
+    PointerUp {
+        index: usize,
+    },
+    HoverMove,
+    Scroll,
+    HoverEnter,
+    HoverExit,
+    ButtonPress,
+    ButtonRelease,
+    /// An action that isn't recognised by this crate
+    Unknown(u32),
+}
+
+impl DecodedMotionAction {
+    pub(crate) fn new(action: MotionAction, pointer_index: usize) -> Self {
+        match action {
+            MotionAction::Down => Self::Down,
+            MotionAction::Up => Self::Up,
+            MotionAction::Move => Self::Move,
+            MotionAction::Cancel => Self::Cancel,
+            MotionAction::Outside => Self::Outside,
+            MotionAction::PointerDown => Self::PointerDown {
+                index: pointer_index,
+            },
+            MotionAction::PointerUp => Self::PointerUp {
+                index: pointer_index,
+            },
+            MotionAction::HoverMove => Self::HoverMove,
+            MotionAction::Scroll => Self::Scroll,
+            MotionAction::HoverEnter => Self::HoverEnter,
+            MotionAction::HoverExit => Self::HoverExit,
+            MotionAction::ButtonPress => Self::ButtonPress,
+            MotionAction::ButtonRelease => Self::ButtonRelease,
+            action => Self::Unknown(action.into()),
+        }
+    }
+}
+
 /// Identifies buttons that are associated with motion events.
 ///
 /// See [the NDK
@@ -395,9 +697,14 @@ impl ButtonState {
         self.0 & ndk_sys::AMOTION_EVENT_BUTTON_SECONDARY != 0
     }
     #[inline]
-    pub fn teriary(self) -> bool {
+    pub fn tertiary(self) -> bool {
         self.0 & ndk_sys::AMOTION_EVENT_BUTTON_TERTIARY != 0
     }
+    #[deprecated = "Use `tertiary()` instead"]
+    #[inline]
+    pub fn teriary(self) -> bool {
+        self.tertiary()
+    }
     #[inline]
     pub fn back(self) -> bool {
         self.0 & ndk_sys::AMOTION_EVENT_BUTTON_BACK != 0
@@ -460,6 +767,8 @@ impl From<ndk::event::EdgeFlags> for EdgeFlags {
 pub struct MotionEventFlags(pub u32);
 
 impl MotionEventFlags {
+    /// The window was (partially) obscured by another visible window above
+    /// it when this event was dispatched.
     #[inline]
     pub fn window_is_obscured(self) -> bool {
         self.0 & ndk_sys::AMOTION_EVENT_FLAG_WINDOW_IS_OBSCURED != 0
@@ -472,6 +781,53 @@ impl From<ndk::event::MotionEventFlags> for MotionEventFlags {
     }
 }
 
+/// A classification of the gesture that a [`MotionEvent`] is part of.
+///
+/// See [the MotionEvent docs](https://developer.android.com/reference/android/view/MotionEvent#getClassification())
+///
+/// # Android Extensible Enum
+///
+/// This is a runtime [extensible enum](`crate#android-extensible-enums`) and
+/// should be handled similar to a `#[non_exhaustive]` enum to maintain
+/// forwards compatibility.
+///
+/// Implements `Into<u32>` and `From<u32>` for converting to/from Android SDK
+/// integer values.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, num_enum::FromPrimitive, num_enum::IntoPrimitive)]
+#[non_exhaustive]
+#[repr(u32)]
+pub enum MotionClassification {
+    /// No additional information is available about the current motion event stream.
+    None = ndk_sys::AMotionClassification::AMOTION_EVENT_CLASSIFICATION_NONE.0,
+
+    /// The user's intent with respect to the current event stream is not yet determined.
+    ///
+    /// Gestures such as scrolling or long-press should be handled conservatively
+    /// (e.g. by delaying them) until the classification changes.
+    AmbiguousGesture =
+        ndk_sys::AMotionClassification::AMOTION_EVENT_CLASSIFICATION_AMBIGUOUS_GESTURE.0,
+
+    /// The current event stream represents the user intentionally pressing harder
+    /// on the screen, which should be used to accelerate a long-press.
+    DeepPress = ndk_sys::AMotionClassification::AMOTION_EVENT_CLASSIFICATION_DEEP_PRESS.0,
+
+    /// The current event stream represents the user swiping with two fingers on a touchpad.
+    TwoFingerSwipe =
+        ndk_sys::AMotionClassification::AMOTION_EVENT_CLASSIFICATION_TWO_FINGER_SWIPE.0,
+
+    /// The current event stream represents the user swiping with three or more fingers on a touchpad.
+    MultiFingerSwipe =
+        ndk_sys::AMotionClassification::AMOTION_EVENT_CLASSIFICATION_MULTI_FINGER_SWIPE.0,
+
+    /// The current event stream represents the user pinching with two fingers on a touchpad.
+    Pinch = ndk_sys::AMotionClassification::AMOTION_EVENT_CLASSIFICATION_PINCH.0,
+
+    #[doc(hidden)]
+    #[num_enum(catch_all)]
+    __Unknown(u32),
+}
+
 /// Key actions.
 ///
 /// See [the NDK docs](https://developer.android.com/ndk/reference/group/input#anonymous-enum-27)
@@ -498,6 +854,44 @@ pub enum KeyAction {
     __Unknown(u32),
 }
 
+/// A [`KeyAction`] that's been decoded together with the key event's repeat
+/// count, which has a different meaning for [`KeyAction::Multiple`] events
+///
+/// See [`KeyEvent::decoded_action()`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodedKeyAction {
+    /// The key has been pressed down
+    Down,
+
+    /// The key has been released
+    Up,
+
+    /// Multiple duplicate key events have occurred in a row, where `count`
+    /// is the number of times the key was pressed
+    ///
+    /// _Note: `ACTION_MULTIPLE` events for a complex character sequence (with
+    /// [`Keycode::Unknown`]) carry the characters via `KeyEvent.getCharacters()`,
+    /// which isn't available via the NDK._
+    Multiple { count: i32 },
+
+    /// An action that isn't recognised by this crate
+    Unknown(u32),
+}
+
+impl DecodedKeyAction {
+    pub(crate) fn new(action: KeyAction, repeat_count: i32) -> Self {
+        match action {
+            KeyAction::Down => Self::Down,
+            KeyAction::Up => Self::Up,
+            KeyAction::Multiple => Self::Multiple {
+                count: repeat_count,
+            },
+            action => Self::Unknown(action.into()),
+        }
+    }
+}
+
 /// Key codes.
 ///
 /// See [the NDK docs](https://developer.android.com/ndk/reference/group/input#anonymous-enum-39)
@@ -886,6 +1280,18 @@ pub struct TextSpan {
     pub end: usize,
 }
 
+/// The state of the text being edited by an input method (IME)
+///
+/// With GameActivity, this is delivered with each [`InputEvent::TextEvent`] as
+/// the IME commits or composes text (following a
+/// [`MainEvent::TextInput`](crate::MainEvent::TextInput) event). While the IME is composing text (such as while
+/// entering CJK characters or before an autocomplete suggestion is accepted)
+/// the composed text is marked by [`Self::compose_region`], and once it's
+/// committed the compose region is cleared (see [`Self::is_composing()`]).
+///
+/// _Note: NativeActivity doesn't support input methods, since that would need
+/// an `InputConnection` implemented in Java, so text input is limited to key
+/// events there._
 #[derive(Debug, Clone)]
 pub struct TextInputState {
     pub text: String,
@@ -907,9 +1313,37 @@ pub struct TextInputState {
     pub compose_region: Option<TextSpan>,
 }
 
+impl TextInputState {
+    /// Returns `true` if the input method is composing text that hasn't been
+    /// committed yet
+    ///
+    /// This distinguishes intermediate updates to the composing text from
+    /// final commits.
+    pub fn is_composing(&self) -> bool {
+        self.compose_region
+            .map_or(false, |region| region.start != region.end)
+    }
+}
+
+/// Describes the currently selected input method (soft keyboard) subtype
+///
+/// See [`AndroidApp::input_method_subtype()`](crate::AndroidApp::input_method_subtype)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct InputMethodSubtype {
+    /// The BCP-47 language tag for the subtype (such as `"en-US"`), if known
+    pub language_tag: Option<String>,
+
+    /// The subtype mode, such as `"keyboard"` or `"voice"`
+    ///
+    /// This may be empty if the input method didn't specify a mode.
+    pub mode: String,
+}
+
 /// An exclusive, lending iterator for input events
 pub struct InputIterator<'a> {
     pub(crate) inner: crate::activity_impl::InputIteratorInner<'a>,
+    pub(crate) stats: std::sync::Arc<crate::stats::LoopStatsCounters>,
 }
 
 impl<'a> InputIterator<'a> {
@@ -922,7 +1356,11 @@ impl<'a> InputIterator<'a> {
     where
         F: FnOnce(&crate::activity_impl::input::InputEvent) -> InputStatus,
     {
-        self.inner.next(callback)
+        let stats = &self.stats;
+        self.inner.next(|event| {
+            stats.input_event_handled();
+            callback(event)
+        })
     }
 }
 
@@ -958,6 +1396,34 @@ impl<'a> Pointer<'a> {
         self.axis_value(Axis::Pressure)
     }
 
+    /// The tilt of a stylus away from the perpendicular, in radians
+    ///
+    /// This is `0.0` when the stylus is perpendicular to the surface, up to
+    /// `PI / 2.0` when it's flat, and is always `0.0` for other tools.
+    #[inline]
+    pub fn tilt(&self) -> f32 {
+        self.axis_value(Axis::Tilt)
+    }
+
+    /// The relative X movement of a mouse since the last event, from
+    /// [`Axis::RelativeX`]
+    ///
+    /// This is reported for mice (including when the pointer is captured) on
+    /// Android 7.0 (API level 24) and newer, and is `0.0` for other tools.
+    #[inline]
+    pub fn relative_x(&self) -> f32 {
+        self.axis_value(Axis::RelativeX)
+    }
+
+    /// The relative Y movement of a mouse since the last event, from
+    /// [`Axis::RelativeY`]
+    ///
+    /// See [`Pointer::relative_x()`]
+    #[inline]
+    pub fn relative_y(&self) -> f32 {
+        self.axis_value(Axis::RelativeY)
+    }
+
     #[inline]
     pub fn raw_x(&self) -> f32 {
         self.inner.raw_x()
@@ -1031,3 +1497,101 @@ impl<'a> ExactSizeIterator for PointersIter<'a> {
         self.inner.len()
     }
 }
+
+/// A single sample of one pointer's state, from [`MotionEvent::history_iter()`]
+///
+/// Coordinates are in the same coordinate space as [`Pointer::x()`] and [`Pointer::y()`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct MotionSample {
+    /// The index of the pointer within the event
+    pub pointer_index: usize,
+
+    /// The ID of the pointer, which stays the same across events while the
+    /// pointer is down
+    pub pointer_id: i32,
+
+    /// The index of this sample within the event's history, or `None` for
+    /// the event's current (most recent) sample
+    pub history_index: Option<usize>,
+
+    /// The time of this sample, in the `java.lang.System.nanoTime()` time base
+    pub event_time: i64,
+
+    pub x: f32,
+    pub y: f32,
+    pub pressure: f32,
+    pub size: f32,
+    pub touch_major: f32,
+    pub touch_minor: f32,
+    pub orientation: f32,
+}
+
+/// An iterator over all the samples of all the pointers in a [`MotionEvent`],
+/// including any historical samples that were batched into the event
+///
+/// Samples are yielded from oldest to newest, with the event's current sample
+/// last. The samples for each moment are yielded in pointer index order.
+///
+/// See [`MotionEvent::history_iter()`]
+#[derive(Debug)]
+pub struct MotionSamplesIter<'a> {
+    event: &'a MotionEvent<'a>,
+    /// The history index of the next sample, where `history_size` refers to
+    /// the current sample
+    next_history_index: usize,
+    next_pointer_index: usize,
+    history_size: usize,
+    pointer_count: usize,
+}
+
+impl<'a> MotionSamplesIter<'a> {
+    pub(crate) fn new(event: &'a MotionEvent<'a>) -> Self {
+        Self {
+            event,
+            next_history_index: 0,
+            next_pointer_index: 0,
+            history_size: event.history_size(),
+            pointer_count: event.pointer_count(),
+        }
+    }
+}
+
+impl<'a> Iterator for MotionSamplesIter<'a> {
+    type Item = MotionSample;
+
+    fn next(&mut self) -> Option<MotionSample> {
+        if self.pointer_count == 0 || self.next_history_index > self.history_size {
+            return None;
+        }
+
+        let history_index = if self.next_history_index < self.history_size {
+            Some(self.next_history_index)
+        } else {
+            None
+        };
+        let sample = self.event.sample(history_index, self.next_pointer_index);
+
+        self.next_pointer_index += 1;
+        if self.next_pointer_index == self.pointer_count {
+            self.next_pointer_index = 0;
+            self.next_history_index += 1;
+        }
+        Some(sample)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let size = self.len();
+        (size, Some(size))
+    }
+}
+
+impl<'a> ExactSizeIterator for MotionSamplesIter<'a> {
+    fn len(&self) -> usize {
+        if self.pointer_count == 0 || self.next_history_index > self.history_size {
+            return 0;
+        }
+        let remaining_moments = self.history_size + 1 - self.next_history_index;
+        remaining_moments * self.pointer_count - self.next_pointer_index
+    }
+}
@@ -349,3 +349,79 @@ impl KeyCharacterMap {
         Ok(keyboard_type.into())
     }
 }
+
+/// Tracks a pending "dead key" across key presses, to combine it with the next
+/// character typed
+///
+/// This wraps [`KeyCharacterMap::get_dead_char`] for the common case of
+/// typing an accent followed by a base character (such as <kbd>`</kbd> then
+/// <kbd>e</kbd> to type 'è').
+///
+/// # Example
+///
+/// ```rust
+/// let mut composer = DeadKeyComposer::default();
+/// // Snip
+///
+/// if let Ok(map) = app.device_key_character_map(device_id) {
+///     if let Ok(key_char) = map.get(key_event.key_code(), key_event.meta_state()) {
+///         if let Ok(Some(typed)) = composer.compose(&map, key_char) {
+///             info!("KeyEvent: Typed '{typed}'");
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DeadKeyComposer {
+    accent: Option<char>,
+}
+
+impl DeadKeyComposer {
+    /// Returns the accent of a dead key that's waiting to be combined with the
+    /// next character, if any
+    pub fn pending_accent(&self) -> Option<char> {
+        self.accent
+    }
+
+    /// Forgets any pending dead key, such as when the text field loses focus
+    pub fn reset(&mut self) {
+        self.accent = None;
+    }
+
+    /// Handles the next character from [`KeyCharacterMap::get`], returning the
+    /// character that was typed, if any
+    ///
+    /// A [`KeyMapChar::CombiningAccent`] is remembered (and `None` returned)
+    /// so it can be combined with the next [`KeyMapChar::Unicode`] character.
+    /// If the accent can't be combined with that character then the character
+    /// is returned as-is. Non-printable keys ([`KeyMapChar::None`]) clear any
+    /// pending accent.
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn compose(
+        &mut self,
+        key_map: &KeyCharacterMap,
+        key_char: KeyMapChar,
+    ) -> Result<Option<char>, AppError> {
+        match key_char {
+            KeyMapChar::None => {
+                self.accent = None;
+                Ok(None)
+            }
+            KeyMapChar::CombiningAccent(accent) => {
+                self.accent = Some(accent);
+                Ok(None)
+            }
+            KeyMapChar::Unicode(unicode) => match self.accent.take() {
+                Some(accent) => Ok(Some(
+                    key_map.get_dead_char(accent, unicode)?.unwrap_or(unicode),
+                )),
+                None => Ok(Some(unicode)),
+            },
+        }
+    }
+}
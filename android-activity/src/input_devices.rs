@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use crate::input::Axis;
+use crate::jni_utils::{self, CloneJavaVM};
+use crate::MainEvent;
+
+/// Whether each `(device ID, source)` reports [`Axis::Pressure`], cached for
+/// `MotionEvent::pressure()` and cleared whenever the devices change
+static PRESSURE_AXES: Mutex<BTreeMap<(i32, u32), bool>> = Mutex::new(BTreeMap::new());
+
+/// Checks whether the given device reports [`Axis::Pressure`] for the given
+/// source, caching the result
+///
+/// Returns `None` if the device couldn't be queried.
+pub(crate) fn has_pressure_axis(device_id: i32, source: u32) -> Option<bool> {
+    if let Some(&has_axis) = PRESSURE_AXES.lock().unwrap().get(&(device_id, source)) {
+        return Some(has_axis);
+    }
+
+    let vm = ndk_context::android_context().vm();
+    let jvm = unsafe { CloneJavaVM::from_raw(vm.cast()) }.ok()?;
+    match jni_utils::input_device_has_axis(&jvm, device_id, Axis::Pressure, source) {
+        Ok(has_axis) => {
+            PRESSURE_AXES
+                .lock()
+                .unwrap()
+                .insert((device_id, source), has_axis);
+            Some(has_axis)
+        }
+        Err(err) => {
+            log::warn!("Failed to query the pressure axis of input device {device_id}: {err:?}");
+            None
+        }
+    }
+}
+
+/// Tracks the attached input devices (by ID, along with their sources), so
+/// that we can synthesize `InputDeviceAdded`, `InputDeviceRemoved` and
+/// `InputDeviceChanged` events
+///
+/// The backends update this whenever our `InputManager.InputDeviceListener` is
+/// notified, as well as whenever the Activity resumes or gains focus, and
+/// changes are found by comparing with the devices that were seen last time.
+#[derive(Debug, Default)]
+pub(crate) struct InputDeviceTracker {
+    /// The sources of each known device, or `None` before the first update
+    devices: Mutex<Option<BTreeMap<i32, u32>>>,
+}
+
+impl InputDeviceTracker {
+    /// Updates the known devices from a list of `(id, sources)` pairs, and
+    /// returns an event for each device that has been added, removed or
+    /// changed since the last update
+    ///
+    /// No events are returned for the first update.
+    pub(crate) fn update(&self, devices: Vec<(i32, u32)>) -> Vec<MainEvent<'static>> {
+        let devices: BTreeMap<i32, u32> = devices.into_iter().collect();
+        let previous = self.devices.lock().unwrap().replace(devices.clone());
+        let Some(previous) = previous else {
+            return Vec::new();
+        };
+
+        let mut events = Vec::new();
+        for (&id, &sources) in &previous {
+            match devices.get(&id) {
+                None => events.push(MainEvent::InputDeviceRemoved { id }),
+                Some(&new_sources) if new_sources != sources => {
+                    events.push(MainEvent::InputDeviceChanged { id })
+                }
+                Some(_) => {}
+            }
+        }
+        for &id in devices.keys() {
+            if !previous.contains_key(&id) {
+                events.push(MainEvent::InputDeviceAdded { id });
+            }
+        }
+        if !events.is_empty() {
+            PRESSURE_AXES.lock().unwrap().clear();
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(events: &[MainEvent<'_>]) -> Vec<(&'static str, i32)> {
+        events
+            .iter()
+            .map(|event| match event {
+                MainEvent::InputDeviceAdded { id } => ("added", *id),
+                MainEvent::InputDeviceRemoved { id } => ("removed", *id),
+                MainEvent::InputDeviceChanged { id } => ("changed", *id),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn first_update_reports_nothing() {
+        let tracker = InputDeviceTracker::default();
+        assert!(tracker.update(vec![(1, 0x101), (5, 0x1002)]).is_empty());
+        assert!(tracker.update(vec![(1, 0x101), (5, 0x1002)]).is_empty());
+    }
+
+    #[test]
+    fn reports_added_removed_and_changed_devices() {
+        let tracker = InputDeviceTracker::default();
+        tracker.update(vec![(1, 0x101), (5, 0x1002), (7, 0x401)]);
+
+        // A gamepad was swapped out while paused, and a keyboard gained a
+        // touchpad
+        let events = tracker.update(vec![(1, 0x100109), (5, 0x1002), (9, 0x401)]);
+        assert_eq!(
+            ids(&events),
+            vec![("changed", 1), ("removed", 7), ("added", 9)]
+        );
+    }
+}
@@ -13,8 +13,15 @@ use jni::{
 };
 
 use crate::{
+    camera::CameraInfo,
     error::{InternalAppError, InternalResult},
-    input::{KeyCharacterMap, KeyCharacterMapBinding},
+    input::{
+        Axis, InputDeviceInfo, InputMethodSubtype, KeyCharacterMap, KeyCharacterMapBinding,
+        MotionRange,
+    },
+    storage::{StorageVolume, StorageVolumeState},
+    ui_thread, AndroidApp, DeviceInfo, DisplayCutout, DisplayMode, LaunchContext, LaunchIntent,
+    Rect, UserRestrictions, WindowInsets, WindowMetrics,
 };
 
 // TODO: JavaVM should implement Clone
@@ -35,6 +42,29 @@ impl CloneJavaVM {
             jvm: JavaVM::from_raw(jvm)?,
         })
     }
+
+    /// Attaches the newly spawned `android_main` thread to the JVM
+    ///
+    /// Attaching can (rarely) fail under resource pressure, so this retries a
+    /// few times before giving up. Returns `false` if the thread couldn't be
+    /// attached, in which case JNI is unavailable for the thread.
+    pub fn attach_main_thread(&self) -> bool {
+        const MAX_ATTEMPTS: u64 = 3;
+        for attempt in 1..=MAX_ATTEMPTS {
+            match self.attach_current_thread_permanently() {
+                Ok(_) => return true,
+                Err(err) => {
+                    log::error!(
+                        "Failed to attach android_main thread to the JVM (attempt {attempt}/{MAX_ATTEMPTS}): {err}"
+                    );
+                    if attempt < MAX_ATTEMPTS {
+                        std::thread::sleep(std::time::Duration::from_millis(10 * attempt));
+                    }
+                }
+            }
+        }
+        false
+    }
 }
 unsafe impl Send for CloneJavaVM {}
 unsafe impl Sync for CloneJavaVM {}
@@ -149,3 +179,1727 @@ pub(crate) fn device_key_character_map(
         character_map,
     ))
 }
+
+/// Queries `InputDevice.isVirtual()` for the given device ID
+///
+/// Returns `true` if there's no device with the given ID
+pub(crate) fn input_device_is_virtual(jvm: &CloneJavaVM, device_id: i32) -> InternalResult<bool> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let device = env
+            .call_static_method(
+                "android/view/InputDevice",
+                "getDevice",
+                "(I)Landroid/view/InputDevice;",
+                &[device_id.into()],
+            )?
+            .l()?;
+        if device.is_null() {
+            return Ok(true);
+        }
+        env.call_method(&device, "isVirtual", "()Z", &[])?.z()
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Queries `InputDevice.getDeviceIds()`
+pub(crate) fn input_device_ids(jvm: &CloneJavaVM) -> InternalResult<Vec<i32>> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let ids = env
+            .call_static_method("android/view/InputDevice", "getDeviceIds", "()[I", &[])?
+            .l()?;
+        let ids = jni::objects::JIntArray::from(ids);
+        let len = env.get_array_length(&ids)?;
+        let mut buf = vec![0; len as usize];
+        env.get_int_array_region(&ids, 0, &mut buf)?;
+        Ok(buf)
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Queries the `(id, sources)` of each device from `InputDevice.getDeviceIds()`
+pub(crate) fn input_device_sources(jvm: &CloneJavaVM) -> InternalResult<Vec<(i32, u32)>> {
+    let ids = input_device_ids(jvm)?;
+
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let mut devices = Vec::with_capacity(ids.len());
+    for id in ids {
+        // Use a local frame per device to avoid running out of local references
+        let sources = env
+            .with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+                let device = env
+                    .call_static_method(
+                        "android/view/InputDevice",
+                        "getDevice",
+                        "(I)Landroid/view/InputDevice;",
+                        &[id.into()],
+                    )?
+                    .l()?;
+                if device.is_null() {
+                    // The device was removed since we queried the IDs
+                    return Ok(None);
+                }
+                Ok(Some(
+                    env.call_method(&device, "getSources", "()I", &[])?.i()?,
+                ))
+            })
+            .map_err(|err| clear_and_map_exception_to_err(&mut env, err))?;
+        if let Some(sources) = sources {
+            devices.push((id, sources as u32));
+        }
+    }
+    Ok(devices)
+}
+
+/// Queries the name, sources and motion ranges of `InputDevice.getDevice()`
+/// for the given device ID
+///
+/// Returns `None` if there's no device with the given ID
+pub(crate) fn input_device_info(
+    jvm: &CloneJavaVM,
+    device_id: i32,
+) -> InternalResult<Option<InputDeviceInfo>> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let device = env
+            .call_static_method(
+                "android/view/InputDevice",
+                "getDevice",
+                "(I)Landroid/view/InputDevice;",
+                &[device_id.into()],
+            )?
+            .l()?;
+        if device.is_null() {
+            return Ok(None);
+        }
+
+        let name = env
+            .call_method(&device, "getName", "()Ljava/lang/String;", &[])?
+            .l()?;
+        let name = get_string(env, name)?.unwrap_or_default();
+        let sources = env.call_method(&device, "getSources", "()I", &[])?.i()?;
+        let vendor_id = env.call_method(&device, "getVendorId", "()I", &[])?.i()?;
+        let product_id = env.call_method(&device, "getProductId", "()I", &[])?.i()?;
+        let is_virtual = env.call_method(&device, "isVirtual", "()Z", &[])?.z()?;
+
+        let list = env
+            .call_method(&device, "getMotionRanges", "()Ljava/util/List;", &[])?
+            .l()?;
+        let count = env.call_method(&list, "size", "()I", &[])?.i()?;
+        let mut motion_ranges = Vec::with_capacity(count.max(0) as usize);
+        for i in 0..count {
+            let range = env
+                .call_method(&list, "get", "(I)Ljava/lang/Object;", &[i.into()])?
+                .l()?;
+            let mut float = |method: &str| -> jni::errors::Result<f32> {
+                env.call_method(&range, method, "()F", &[])?.f()
+            };
+            let min = float("getMin")?;
+            let max = float("getMax")?;
+            let flat = float("getFlat")?;
+            let fuzz = float("getFuzz")?;
+            let resolution = float("getResolution")?;
+            let axis = env.call_method(&range, "getAxis", "()I", &[])?.i()?;
+            let source = env.call_method(&range, "getSource", "()I", &[])?.i()?;
+            motion_ranges.push(MotionRange {
+                axis: Axis::from(axis as u32),
+                source: source as u32,
+                min,
+                max,
+                flat,
+                fuzz,
+                resolution,
+            });
+            env.delete_local_ref(range)?;
+        }
+
+        Ok(Some(InputDeviceInfo {
+            id: device_id,
+            name,
+            sources: sources as u32,
+            vendor_id,
+            product_id,
+            is_virtual,
+            motion_ranges,
+        }))
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Checks whether the input device with the given ID reports the given axis
+/// for the given source, via `InputDevice.getMotionRange(axis, source)`
+///
+/// Returns `false` if the device doesn't exist.
+pub(crate) fn input_device_has_axis(
+    jvm: &CloneJavaVM,
+    device_id: i32,
+    axis: Axis,
+    source: u32,
+) -> InternalResult<bool> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let device = env
+            .call_static_method(
+                "android/view/InputDevice",
+                "getDevice",
+                "(I)Landroid/view/InputDevice;",
+                &[device_id.into()],
+            )?
+            .l()?;
+        if device.is_null() {
+            return Ok(false);
+        }
+        let axis: u32 = axis.into();
+        let range = env
+            .call_method(
+                &device,
+                "getMotionRange",
+                "(II)Landroid/view/InputDevice$MotionRange;",
+                &[(axis as i32).into(), (source as i32).into()],
+            )?
+            .l()?;
+        Ok(!range.is_null())
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Reads `getResources().getConfiguration().fontScale` for the given `Activity`
+///
+/// The font scale isn't exposed via `AConfiguration` so it has to be queried
+/// via JNI.
+#[cfg(feature = "native-activity")]
+pub(crate) fn activity_font_scale(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+) -> InternalResult<f32> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let resources = env
+            .call_method(
+                &activity,
+                "getResources",
+                "()Landroid/content/res/Resources;",
+                &[],
+            )?
+            .l()?;
+        let config = env
+            .call_method(
+                &resources,
+                "getConfiguration",
+                "()Landroid/content/res/Configuration;",
+                &[],
+            )?
+            .l()?;
+        env.get_field(&config, "fontScale", "F")?.f()
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+fn get_string(env: &mut jni::JNIEnv<'_>, obj: JObject<'_>) -> jni::errors::Result<Option<String>> {
+    if obj.is_null() {
+        Ok(None)
+    } else {
+        let obj = JString::from(obj);
+        let s: String = env.get_string(&obj)?.into();
+        Ok(Some(s))
+    }
+}
+
+/// Queries the action, data and flags of `Activity.getIntent()`
+pub(crate) fn launch_intent(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+) -> InternalResult<LaunchIntent> {
+    const FLAG_ACTIVITY_LAUNCHED_FROM_HISTORY: i32 = 0x0010_0000;
+
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let intent = env
+            .call_method(&activity, "getIntent", "()Landroid/content/Intent;", &[])?
+            .l()?;
+        if intent.is_null() {
+            return Ok(LaunchIntent::default());
+        }
+        let action = env
+            .call_method(&intent, "getAction", "()Ljava/lang/String;", &[])?
+            .l()?;
+        let action = get_string(env, action)?;
+        let data = env
+            .call_method(&intent, "getDataString", "()Ljava/lang/String;", &[])?
+            .l()?;
+        let data = get_string(env, data)?;
+        let flags = env.call_method(&intent, "getFlags", "()I", &[])?.i()?;
+        Ok(LaunchIntent {
+            action,
+            data,
+            flags,
+            launched_from_history: flags & FLAG_ACTIVITY_LAUNCHED_FROM_HISTORY != 0,
+        })
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Checks whether a soft keyboard is likely to be shown for the given `Activity`
+///
+/// This checks `InputMethodManager.getEnabledInputMethodList()` and
+/// `Configuration.hardKeyboardHidden`
+pub(crate) fn ime_available(jvm: &CloneJavaVM, activity: jni_sys::jobject) -> InternalResult<bool> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let service_name = env.new_string("input_method")?;
+        let imm = env
+            .call_method(
+                &activity,
+                "getSystemService",
+                "(Ljava/lang/String;)Ljava/lang/Object;",
+                &[(&service_name).into()],
+            )?
+            .l()?;
+        if imm.is_null() {
+            return Ok(false);
+        }
+        let enabled = env
+            .call_method(&imm, "getEnabledInputMethodList", "()Ljava/util/List;", &[])?
+            .l()?;
+        if env.call_method(&enabled, "isEmpty", "()Z", &[])?.z()? {
+            return Ok(false);
+        }
+
+        // By default, Android won't show a soft keyboard while a hardware
+        // keyboard is attached (`HARDKEYBOARDHIDDEN_NO`)
+        let resources = env
+            .call_method(
+                &activity,
+                "getResources",
+                "()Landroid/content/res/Resources;",
+                &[],
+            )?
+            .l()?;
+        let config = env
+            .call_method(
+                &resources,
+                "getConfiguration",
+                "()Landroid/content/res/Configuration;",
+                &[],
+            )?
+            .l()?;
+        const HARDKEYBOARDHIDDEN_NO: i32 = 1;
+        let hard_keyboard_hidden = env.get_field(&config, "hardKeyboardHidden", "I")?.i()?;
+
+        Ok(hard_keyboard_hidden != HARDKEYBOARDHIDDEN_NO)
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Queries `InputMethodManager.getCurrentInputMethodSubtype()` for the given `Activity`
+pub(crate) fn input_method_subtype(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+) -> InternalResult<Option<InputMethodSubtype>> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let service_name = env.new_string("input_method")?;
+        let imm = env
+            .call_method(
+                &activity,
+                "getSystemService",
+                "(Ljava/lang/String;)Ljava/lang/Object;",
+                &[(&service_name).into()],
+            )?
+            .l()?;
+        if imm.is_null() {
+            return Ok(None);
+        }
+        let subtype = env
+            .call_method(
+                &imm,
+                "getCurrentInputMethodSubtype",
+                "()Landroid/view/inputmethod/InputMethodSubtype;",
+                &[],
+            )?
+            .l()?;
+        if subtype.is_null() {
+            return Ok(None);
+        }
+
+        // `getLanguageTag()` was only added in API level 24 and may also be
+        // empty for subtypes that only specify the (deprecated) locale string
+        let mut language_tag = None;
+        if AndroidApp::sdk_version() >= 24 {
+            let tag = env
+                .call_method(&subtype, "getLanguageTag", "()Ljava/lang/String;", &[])?
+                .l()?;
+            language_tag = get_string(env, tag)?.filter(|tag| !tag.is_empty());
+        }
+        if language_tag.is_none() {
+            let locale = env
+                .call_method(&subtype, "getLocale", "()Ljava/lang/String;", &[])?
+                .l()?;
+            language_tag = get_string(env, locale)?
+                .filter(|locale| !locale.is_empty())
+                .map(|locale| locale.replace('_', "-"));
+        }
+
+        let mode = env
+            .call_method(&subtype, "getMode", "()Ljava/lang/String;", &[])?
+            .l()?;
+        let mode = get_string(env, mode)?.unwrap_or_default();
+
+        Ok(Some(InputMethodSubtype { language_tag, mode }))
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Calls `Activity.setTaskDescription()` on the Java main thread
+pub(crate) fn set_task_description(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+    label: Option<String>,
+    color: Option<u32>,
+    icon: Option<Vec<u8>>,
+) -> InternalResult<()> {
+    ui_thread::run_with_activity_on_ui_thread(jvm, activity, move |env, activity| {
+        let label = match label {
+            Some(label) => JObject::from(env.new_string(label)?),
+            None => JObject::null(),
+        };
+        let icon = match icon {
+            Some(icon) => {
+                let bytes = env.byte_array_from_slice(&icon)?;
+                env.call_static_method(
+                    "android/graphics/BitmapFactory",
+                    "decodeByteArray",
+                    "([BII)Landroid/graphics/Bitmap;",
+                    &[(&bytes).into(), 0.into(), (icon.len() as i32).into()],
+                )?
+                .l()?
+            }
+            None => JObject::null(),
+        };
+
+        // NB: This constructor is deprecated since API level 28 but it's the
+        // only one that can take a `Bitmap` on older versions of Android
+        let task_description = env.new_object(
+            "android/app/ActivityManager$TaskDescription",
+            "(Ljava/lang/String;Landroid/graphics/Bitmap;I)V",
+            &[
+                (&label).into(),
+                (&icon).into(),
+                (color.unwrap_or(0) as i32).into(),
+            ],
+        )?;
+        env.call_method(
+            activity,
+            "setTaskDescription",
+            "(Landroid/app/ActivityManager$TaskDescription;)V",
+            &[(&task_description).into()],
+        )?;
+        Ok(())
+    })
+}
+
+fn get_rect(env: &mut jni::JNIEnv<'_>, rect: &JObject<'_>) -> jni::errors::Result<Rect> {
+    Ok(Rect {
+        left: env.get_field(rect, "left", "I")?.i()?,
+        top: env.get_field(rect, "top", "I")?.i()?,
+        right: env.get_field(rect, "right", "I")?.i()?,
+        bottom: env.get_field(rect, "bottom", "I")?.i()?,
+    })
+}
+
+/// Queries the current and maximum `WindowMetrics` bounds for the given `Activity`
+///
+/// Requires API level 30
+pub(crate) fn window_metrics(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+) -> InternalResult<WindowMetrics> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let window_manager = env
+            .call_method(
+                &activity,
+                "getWindowManager",
+                "()Landroid/view/WindowManager;",
+                &[],
+            )?
+            .l()?;
+
+        let mut bounds_for = |method: &str| -> jni::errors::Result<Rect> {
+            let metrics = env
+                .call_method(
+                    &window_manager,
+                    method,
+                    "()Landroid/view/WindowMetrics;",
+                    &[],
+                )?
+                .l()?;
+            let bounds = env
+                .call_method(&metrics, "getBounds", "()Landroid/graphics/Rect;", &[])?
+                .l()?;
+            get_rect(env, &bounds)
+        };
+        let bounds = bounds_for("getCurrentWindowMetrics")?;
+        let maximum_bounds = bounds_for("getMaximumWindowMetrics")?;
+
+        let in_multi_window_mode = env
+            .call_method(&activity, "isInMultiWindowMode", "()Z", &[])?
+            .z()?;
+
+        Ok(WindowMetrics {
+            bounds,
+            maximum_bounds,
+            in_multi_window_mode,
+        })
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+fn int_method(
+    env: &mut jni::JNIEnv<'_>,
+    obj: &JObject<'_>,
+    method: &str,
+) -> jni::errors::Result<i32> {
+    env.call_method(obj, method, "()I", &[])?.i()
+}
+
+/// Gets the root `WindowInsets` of the `Activity`'s decor view, which is null
+/// if the view isn't attached yet
+///
+/// Requires API level 23
+fn root_window_insets<'local>(
+    env: &mut jni::JNIEnv<'local>,
+    activity: &JObject<'_>,
+) -> jni::errors::Result<JObject<'local>> {
+    let window = env
+        .call_method(activity, "getWindow", "()Landroid/view/Window;", &[])?
+        .l()?;
+    let decor_view = env
+        .call_method(&window, "getDecorView", "()Landroid/view/View;", &[])?
+        .l()?;
+    env.call_method(
+        &decor_view,
+        "getRootWindowInsets",
+        "()Landroid/view/WindowInsets;",
+        &[],
+    )?
+    .l()
+}
+
+/// Queries the root `WindowInsets` of the given `Activity`'s decor view
+///
+/// Requires API level 23
+pub(crate) fn window_insets(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+    sdk_version: i32,
+) -> InternalResult<WindowInsets> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let insets = root_window_insets(env, &activity)?;
+        if insets.is_null() {
+            // The view isn't attached yet
+            return Ok(WindowInsets::default());
+        }
+
+        if sdk_version >= 30 {
+            let mut insets_of_type = |type_method: &str| -> jni::errors::Result<Rect> {
+                let type_mask = env
+                    .call_static_method("android/view/WindowInsets$Type", type_method, "()I", &[])?
+                    .i()?;
+                let type_insets = env
+                    .call_method(
+                        &insets,
+                        "getInsets",
+                        "(I)Landroid/graphics/Insets;",
+                        &[type_mask.into()],
+                    )?
+                    .l()?;
+                // `Insets` has public `left`, `top`, `right` and `bottom` fields, like `Rect`
+                get_rect(env, &type_insets)
+            };
+            return Ok(WindowInsets {
+                system_bars: insets_of_type("systemBars")?,
+                display_cutout: insets_of_type("displayCutout")?,
+                ime: insets_of_type("ime")?,
+            });
+        }
+
+        let system_bars = Rect {
+            left: int_method(env, &insets, "getSystemWindowInsetLeft")?,
+            top: int_method(env, &insets, "getSystemWindowInsetTop")?,
+            right: int_method(env, &insets, "getSystemWindowInsetRight")?,
+            bottom: int_method(env, &insets, "getSystemWindowInsetBottom")?,
+        };
+        let display_cutout = if sdk_version >= 28 {
+            let cutout = env
+                .call_method(
+                    &insets,
+                    "getDisplayCutout",
+                    "()Landroid/view/DisplayCutout;",
+                    &[],
+                )?
+                .l()?;
+            if cutout.is_null() {
+                Rect::empty()
+            } else {
+                Rect {
+                    left: int_method(env, &cutout, "getSafeInsetLeft")?,
+                    top: int_method(env, &cutout, "getSafeInsetTop")?,
+                    right: int_method(env, &cutout, "getSafeInsetRight")?,
+                    bottom: int_method(env, &cutout, "getSafeInsetBottom")?,
+                }
+            }
+        } else {
+            Rect::empty()
+        };
+        Ok(WindowInsets {
+            system_bars,
+            display_cutout,
+            ime: Rect::empty(),
+        })
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Queries the `DisplayCutout` of the given `Activity`'s window, if it has one
+///
+/// Requires API level 28
+pub(crate) fn display_cutout(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+) -> InternalResult<Option<DisplayCutout>> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let insets = root_window_insets(env, &activity)?;
+        if insets.is_null() {
+            return Ok(None);
+        }
+        let cutout = env
+            .call_method(
+                &insets,
+                "getDisplayCutout",
+                "()Landroid/view/DisplayCutout;",
+                &[],
+            )?
+            .l()?;
+        if cutout.is_null() {
+            return Ok(None);
+        }
+
+        let safe_insets = Rect {
+            left: int_method(env, &cutout, "getSafeInsetLeft")?,
+            top: int_method(env, &cutout, "getSafeInsetTop")?,
+            right: int_method(env, &cutout, "getSafeInsetRight")?,
+            bottom: int_method(env, &cutout, "getSafeInsetBottom")?,
+        };
+
+        let list = env
+            .call_method(&cutout, "getBoundingRects", "()Ljava/util/List;", &[])?
+            .l()?;
+        let count = env.call_method(&list, "size", "()I", &[])?.i()?;
+        let mut bounding_rects = Vec::with_capacity(count.max(0) as usize);
+        for i in 0..count {
+            let rect = env
+                .call_method(&list, "get", "(I)Ljava/lang/Object;", &[i.into()])?
+                .l()?;
+            bounding_rects.push(get_rect(env, &rect)?);
+            env.delete_local_ref(rect)?;
+        }
+
+        Ok(Some(DisplayCutout {
+            safe_insets,
+            bounding_rects,
+        }))
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Determines the [`LaunchContext`] from the `Activity`'s state and
+/// `ActivityManager.getMyMemoryState()`
+pub(crate) fn launch_context(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+) -> InternalResult<LaunchContext> {
+    // `ActivityManager.RunningAppProcessInfo.IMPORTANCE_VISIBLE`
+    const IMPORTANCE_VISIBLE: i32 = 200;
+
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let finishing = env.call_method(&activity, "isFinishing", "()Z", &[])?.z()?
+            || env.call_method(&activity, "isDestroyed", "()Z", &[])?.z()?;
+        if finishing {
+            return Ok(LaunchContext::FinishingActivity);
+        }
+
+        let process_info = env.new_object(
+            "android/app/ActivityManager$RunningAppProcessInfo",
+            "()V",
+            &[],
+        )?;
+        env.call_static_method(
+            "android/app/ActivityManager",
+            "getMyMemoryState",
+            "(Landroid/app/ActivityManager$RunningAppProcessInfo;)V",
+            &[(&process_info).into()],
+        )?;
+        let importance = env.get_field(&process_info, "importance", "I")?.i()?;
+        Ok(if importance <= IMPORTANCE_VISIBLE {
+            LaunchContext::ForegroundActivity
+        } else {
+            LaunchContext::BackgroundActivity
+        })
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Queries `ClipboardManager.getPrimaryClipDescription().getTimestamp()`
+///
+/// Returns `None` if there's no primary clip (or it isn't accessible).
+///
+/// Requires API level 26
+pub(crate) fn primary_clip_timestamp(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+) -> InternalResult<Option<i64>> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let service_name = env.new_string("clipboard")?;
+        let clipboard_manager = env
+            .call_method(
+                &activity,
+                "getSystemService",
+                "(Ljava/lang/String;)Ljava/lang/Object;",
+                &[(&service_name).into()],
+            )?
+            .l()?;
+        if clipboard_manager.is_null() {
+            return Ok(None);
+        }
+        let description = env
+            .call_method(
+                &clipboard_manager,
+                "getPrimaryClipDescription",
+                "()Landroid/content/ClipDescription;",
+                &[],
+            )?
+            .l()?;
+        if description.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(
+            env.call_method(&description, "getTimestamp", "()J", &[])?
+                .j()?,
+        ))
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Queries `PowerManager.isInteractive()`
+pub(crate) fn is_interactive(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+) -> InternalResult<bool> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let service_name = env.new_string("power")?;
+        let power_manager = env
+            .call_method(
+                &activity,
+                "getSystemService",
+                "(Ljava/lang/String;)Ljava/lang/Object;",
+                &[(&service_name).into()],
+            )?
+            .l()?;
+        if power_manager.is_null() {
+            return Ok(true);
+        }
+        env.call_method(&power_manager, "isInteractive", "()Z", &[])?
+            .z()
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Queries `getWindow().getDecorView().isHardwareAccelerated()` for the given `Activity`
+pub(crate) fn is_hardware_accelerated(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+) -> InternalResult<bool> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let window = env
+            .call_method(&activity, "getWindow", "()Landroid/view/Window;", &[])?
+            .l()?;
+        let decor_view = env
+            .call_method(&window, "getDecorView", "()Landroid/view/View;", &[])?
+            .l()?;
+        env.call_method(&decor_view, "isHardwareAccelerated", "()Z", &[])?
+            .z()
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Reads an integer setting via `getInt(ContentResolver, String)` for the
+/// given `Settings` class (such as `android/provider/Settings$System`)
+///
+/// Returns `None` if the setting isn't found
+pub(crate) fn settings_int(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+    settings_class: &str,
+    name: &str,
+) -> InternalResult<Option<i32>> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let resolver = env
+            .call_method(
+                &activity,
+                "getContentResolver",
+                "()Landroid/content/ContentResolver;",
+                &[],
+            )?
+            .l()?;
+        let name = env.new_string(name)?;
+
+        // Use the `getInt()` overload with a default value, since the other
+        // throws a `SettingNotFoundException`
+        const NOT_FOUND: i32 = i32::MIN;
+        let value = env
+            .call_static_method(
+                settings_class,
+                "getInt",
+                "(Landroid/content/ContentResolver;Ljava/lang/String;I)I",
+                &[(&resolver).into(), (&name).into(), NOT_FOUND.into()],
+            )?
+            .i()?;
+        Ok((value != NOT_FOUND).then_some(value))
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Gets the `AudioManager` system service for the given `Activity`
+fn audio_manager<'local>(
+    env: &mut jni::JNIEnv<'local>,
+    activity: &JObject<'_>,
+) -> jni::errors::Result<JObject<'local>> {
+    let service_name = env.new_string("audio")?;
+    let audio_manager = env
+        .call_method(
+            activity,
+            "getSystemService",
+            "(Ljava/lang/String;)Ljava/lang/Object;",
+            &[(&service_name).into()],
+        )?
+        .l()?;
+    if audio_manager.is_null() {
+        return Err(jni::errors::Error::NullPtr(
+            "getSystemService(AUDIO_SERVICE)",
+        ));
+    }
+    Ok(audio_manager)
+}
+
+/// Queries `AudioManager.getStreamVolume()` for each of the given streams
+pub(crate) fn stream_volumes(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+    streams: &[i32],
+) -> InternalResult<Vec<i32>> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let audio_manager = audio_manager(env, &activity)?;
+        streams
+            .iter()
+            .map(|&stream| {
+                env.call_method(&audio_manager, "getStreamVolume", "(I)I", &[stream.into()])?
+                    .i()
+            })
+            .collect()
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Queries `AudioManager.getStreamMaxVolume()` for the given stream
+pub(crate) fn stream_max_volume(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+    stream: i32,
+) -> InternalResult<i32> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let audio_manager = audio_manager(env, &activity)?;
+        env.call_method(
+            &audio_manager,
+            "getStreamMaxVolume",
+            "(I)I",
+            &[stream.into()],
+        )?
+        .i()
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Calls `AudioManager.setStreamVolume()` for the given stream
+pub(crate) fn set_stream_volume(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+    stream: i32,
+    volume: i32,
+    flags: i32,
+) -> InternalResult<()> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let audio_manager = audio_manager(env, &activity)?;
+        env.call_method(
+            &audio_manager,
+            "setStreamVolume",
+            "(III)V",
+            &[stream.into(), volume.into(), flags.into()],
+        )?;
+        Ok(())
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Reads a static `String` field from `android.os.Build`
+fn build_string(env: &mut jni::JNIEnv<'_>, field: &str) -> jni::errors::Result<Option<String>> {
+    let value = env
+        .get_static_field("android/os/Build", field, "Ljava/lang/String;")?
+        .l()?;
+    get_string(env, value)
+}
+
+/// Reads `Build.SUPPORTED_ABIS`, in order of preference
+fn build_supported_abis(env: &mut jni::JNIEnv<'_>) -> jni::errors::Result<Vec<String>> {
+    let abis = env
+        .get_static_field("android/os/Build", "SUPPORTED_ABIS", "[Ljava/lang/String;")?
+        .l()?;
+    if abis.is_null() {
+        return Ok(Vec::new());
+    }
+    let abis = jni::objects::JObjectArray::from(abis);
+    let count = env.get_array_length(&abis)?;
+    let mut supported_abis = Vec::with_capacity(count as usize);
+    for i in 0..count {
+        let abi = env.get_object_array_element(&abis, i)?;
+        if let Some(abi) = get_string(env, abi)? {
+            supported_abis.push(abi);
+        }
+    }
+    Ok(supported_abis)
+}
+
+/// Reads `getApplicationInfo().nativeLibraryDir` for the given `Context`
+fn native_library_dir(
+    env: &mut jni::JNIEnv<'_>,
+    context: &JObject<'_>,
+) -> jni::errors::Result<Option<std::path::PathBuf>> {
+    let app_info = env
+        .call_method(
+            context,
+            "getApplicationInfo",
+            "()Landroid/content/pm/ApplicationInfo;",
+            &[],
+        )?
+        .l()?;
+    let dir = env
+        .get_field(&app_info, "nativeLibraryDir", "Ljava/lang/String;")?
+        .l()?;
+    Ok(get_string(env, dir)?.map(std::path::PathBuf::from))
+}
+
+/// Queries `Build.SUPPORTED_ABIS`
+pub(crate) fn supported_abis(jvm: &CloneJavaVM) -> InternalResult<Vec<String>> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    env.with_local_frame::<_, _, jni::errors::Error>(10, build_supported_abis)
+        .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Queries `ApplicationInfo.nativeLibraryDir` for the given `Activity`
+pub(crate) fn activity_native_library_dir(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+) -> InternalResult<Option<std::path::PathBuf>> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| native_library_dir(env, &activity))
+        .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Queries the JNI-backed facts for a [`DeviceInfo`] in a single JNI session
+///
+/// Only the fields that need JNI are populated.
+pub(crate) fn device_info(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+) -> InternalResult<DeviceInfo> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let manufacturer = build_string(env, "MANUFACTURER")?;
+        let model = build_string(env, "MODEL")?;
+        let supported_abis = build_supported_abis(env)?;
+        let native_library_dir = native_library_dir(env, &activity)?;
+
+        let sdk_codename = env
+            .get_static_field("android/os/Build$VERSION", "CODENAME", "Ljava/lang/String;")?
+            .l()?;
+        let sdk_codename = get_string(env, sdk_codename)?;
+        let preview_sdk_int = if AndroidApp::sdk_version() >= 23 {
+            env.get_static_field("android/os/Build$VERSION", "PREVIEW_SDK_INT", "I")?
+                .i()?
+        } else {
+            0
+        };
+
+        let package_name_obj = env
+            .call_method(&activity, "getPackageName", "()Ljava/lang/String;", &[])?
+            .l()?;
+        let package_manager = env
+            .call_method(
+                &activity,
+                "getPackageManager",
+                "()Landroid/content/pm/PackageManager;",
+                &[],
+            )?
+            .l()?;
+        let package_info = env
+            .call_method(
+                &package_manager,
+                "getPackageInfo",
+                "(Ljava/lang/String;I)Landroid/content/pm/PackageInfo;",
+                &[(&package_name_obj).into(), 0.into()],
+            )?
+            .l()?;
+        let package_name = get_string(env, package_name_obj)?;
+
+        let version_name = env
+            .get_field(&package_info, "versionName", "Ljava/lang/String;")?
+            .l()?;
+        let version_name = get_string(env, version_name)?;
+        let version_code = if AndroidApp::sdk_version() >= 28 {
+            env.call_method(&package_info, "getLongVersionCode", "()J", &[])?
+                .j()?
+        } else {
+            env.get_field(&package_info, "versionCode", "I")?.i()? as i64
+        };
+
+        Ok(DeviceInfo {
+            sdk_codename,
+            preview_sdk_int,
+            manufacturer,
+            model,
+            package_name,
+            version_name,
+            version_code,
+            supported_abis,
+            native_library_dir,
+            ..Default::default()
+        })
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Controls whether the given `Activity`'s window fits its content within the
+/// system bars, on the Java main thread
+///
+/// This mirrors `WindowCompat.setDecorFitsSystemWindows()` from AndroidX, by
+/// calling `Window.setDecorFitsSystemWindows()` on API level 30+ and otherwise
+/// updating the decor view's (deprecated) layout `systemUiVisibility` flags.
+pub(crate) fn set_decor_fits_system_windows(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+    sdk_version: i32,
+    fits: bool,
+) -> InternalResult<()> {
+    ui_thread::run_with_activity_on_ui_thread(jvm, activity, move |env, activity| {
+        let window = env
+            .call_method(activity, "getWindow", "()Landroid/view/Window;", &[])?
+            .l()?;
+        if sdk_version >= 30 {
+            env.call_method(&window, "setDecorFitsSystemWindows", "(Z)V", &[fits.into()])?;
+        } else {
+            // View.SYSTEM_UI_FLAG_LAYOUT_STABLE | SYSTEM_UI_FLAG_LAYOUT_HIDE_NAVIGATION
+            // | SYSTEM_UI_FLAG_LAYOUT_FULLSCREEN
+            const LAYOUT_FLAGS: i32 = 0x100 | 0x200 | 0x400;
+            let decor_view = env
+                .call_method(&window, "getDecorView", "()Landroid/view/View;", &[])?
+                .l()?;
+            let visibility = env
+                .call_method(&decor_view, "getSystemUiVisibility", "()I", &[])?
+                .i()?;
+            let visibility = if fits {
+                visibility & !LAYOUT_FLAGS
+            } else {
+                visibility | LAYOUT_FLAGS
+            };
+            env.call_method(
+                &decor_view,
+                "setSystemUiVisibility",
+                "(I)V",
+                &[visibility.into()],
+            )?;
+        }
+        Ok(())
+    })
+}
+
+/// Calls `View.performHapticFeedback()` on the decor view of the given
+/// `Activity`, on the Java main thread
+///
+/// Returns `None` if the call didn't complete in time
+pub(crate) fn perform_haptic_feedback(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+    feedback_constant: i32,
+) -> InternalResult<Option<bool>> {
+    ui_thread::call_with_activity_on_ui_thread(
+        jvm,
+        activity,
+        std::time::Duration::from_millis(500),
+        move |env, activity| {
+            let window = env
+                .call_method(activity, "getWindow", "()Landroid/view/Window;", &[])?
+                .l()?;
+            let decor_view = env
+                .call_method(&window, "getDecorView", "()Landroid/view/View;", &[])?
+                .l()?;
+            env.call_method(
+                &decor_view,
+                "performHapticFeedback",
+                "(I)Z",
+                &[feedback_constant.into()],
+            )?
+            .z()
+        },
+    )
+}
+
+/// Calls `InputMethodManager.showSoftInput()` or
+/// `InputMethodManager.hideSoftInputFromWindow()` for the given `Activity`'s
+/// focused view (or decor view), on the Java main thread
+///
+/// `flags` are `InputMethodManager.SHOW_*` flags for showing and `HIDE_*` flags
+/// for hiding.
+///
+/// Returns the result of the `InputMethodManager` call (`false` if there's no
+/// `InputMethodManager`), or `None` if the call didn't complete in time
+#[cfg(feature = "native-activity")]
+pub(crate) fn set_soft_input_visible(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+    show: bool,
+    flags: i32,
+) -> InternalResult<Option<bool>> {
+    ui_thread::call_with_activity_on_ui_thread(
+        jvm,
+        activity,
+        std::time::Duration::from_millis(500),
+        move |env, activity| {
+            let service_name = env.new_string("input_method")?;
+            let imm = env
+                .call_method(
+                    activity,
+                    "getSystemService",
+                    "(Ljava/lang/String;)Ljava/lang/Object;",
+                    &[(&service_name).into()],
+                )?
+                .l()?;
+            if imm.is_null() {
+                return Ok(false);
+            }
+            let mut view = env
+                .call_method(activity, "getCurrentFocus", "()Landroid/view/View;", &[])?
+                .l()?;
+            if view.is_null() {
+                let window = env
+                    .call_method(activity, "getWindow", "()Landroid/view/Window;", &[])?
+                    .l()?;
+                view = env
+                    .call_method(&window, "getDecorView", "()Landroid/view/View;", &[])?
+                    .l()?;
+            }
+            if show {
+                env.call_method(
+                    &imm,
+                    "showSoftInput",
+                    "(Landroid/view/View;I)Z",
+                    &[(&view).into(), flags.into()],
+                )?
+                .z()
+            } else {
+                let token = env
+                    .call_method(&view, "getWindowToken", "()Landroid/os/IBinder;", &[])?
+                    .l()?;
+                env.call_method(
+                    &imm,
+                    "hideSoftInputFromWindow",
+                    "(Landroid/os/IBinder;I)Z",
+                    &[(&token).into(), flags.into()],
+                )?
+                .z()
+            }
+        },
+    )
+}
+
+/// Calls `View.requestPointerCapture()` or `View.releasePointerCapture()` on
+/// the decor view of the given `Activity`, on the Java main thread
+pub(crate) fn set_pointer_capture(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+    capture: bool,
+) -> InternalResult<()> {
+    ui_thread::run_with_activity_on_ui_thread(jvm, activity, move |env, activity| {
+        let window = env
+            .call_method(activity, "getWindow", "()Landroid/view/Window;", &[])?
+            .l()?;
+        let decor_view = env
+            .call_method(&window, "getDecorView", "()Landroid/view/View;", &[])?
+            .l()?;
+        let method = if capture {
+            "requestPointerCapture"
+        } else {
+            "releasePointerCapture"
+        };
+        env.call_method(&decor_view, method, "()V", &[])?;
+        Ok(())
+    })
+}
+
+/// Calls `View.hasPointerCapture()` on the decor view of the given `Activity`,
+/// on the Java main thread
+///
+/// Returns `None` if the call didn't complete in time
+pub(crate) fn has_pointer_capture(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+) -> InternalResult<Option<bool>> {
+    ui_thread::call_with_activity_on_ui_thread(
+        jvm,
+        activity,
+        std::time::Duration::from_millis(500),
+        move |env, activity| {
+            let window = env
+                .call_method(activity, "getWindow", "()Landroid/view/Window;", &[])?
+                .l()?;
+            let decor_view = env
+                .call_method(&window, "getDecorView", "()Landroid/view/View;", &[])?
+                .l()?;
+            env.call_method(&decor_view, "hasPointerCapture", "()Z", &[])?
+                .z()
+        },
+    )
+}
+
+/// Queries `UserManager` and `DevicePolicyManager` for the restrictions that
+/// apply to the current user
+pub(crate) fn user_restrictions(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+) -> InternalResult<UserRestrictions> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let mut restrictions = UserRestrictions::default();
+
+        let service_name = env.new_string("user")?;
+        let user_manager = env
+            .call_method(
+                &activity,
+                "getSystemService",
+                "(Ljava/lang/String;)Ljava/lang/Object;",
+                &[(&service_name).into()],
+            )?
+            .l()?;
+        if !user_manager.is_null() {
+            if AndroidApp::sdk_version() >= 30 {
+                restrictions.is_managed_profile = Some(
+                    env.call_method(&user_manager, "isManagedProfile", "()Z", &[])?
+                        .z()?,
+                );
+            }
+
+            let mut has_restriction = |key: &str| -> jni::errors::Result<bool> {
+                let key = env.new_string(key)?;
+                env.call_method(
+                    &user_manager,
+                    "hasUserRestriction",
+                    "(Ljava/lang/String;)Z",
+                    &[(&key).into()],
+                )?
+                .z()
+            };
+            restrictions.disallow_cross_profile_copy_paste =
+                has_restriction("no_cross_profile_copy_paste")?;
+            restrictions.disallow_usb_file_transfer = has_restriction("no_usb_file_transfer")?;
+            restrictions.disallow_mount_physical_media = has_restriction("no_physical_media")?;
+            restrictions.disallow_share_location = has_restriction("no_share_location")?;
+        }
+
+        let service_name = env.new_string("device_policy")?;
+        let policy_manager = env
+            .call_method(
+                &activity,
+                "getSystemService",
+                "(Ljava/lang/String;)Ljava/lang/Object;",
+                &[(&service_name).into()],
+            )?
+            .l()?;
+        if !policy_manager.is_null() {
+            restrictions.screen_capture_disabled = env
+                .call_method(
+                    &policy_manager,
+                    "getScreenCaptureDisabled",
+                    "(Landroid/content/ComponentName;)Z",
+                    &[(&JObject::null()).into()],
+                )?
+                .z()?;
+        }
+
+        Ok(restrictions)
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Queries `CameraCharacteristics.get()` for an `Integer` valued key, by name
+fn camera_characteristic_int(
+    env: &mut jni::JNIEnv<'_>,
+    characteristics: &JObject<'_>,
+    key_name: &str,
+) -> jni::errors::Result<Option<i32>> {
+    let key = env
+        .get_static_field(
+            "android/hardware/camera2/CameraCharacteristics",
+            key_name,
+            "Landroid/hardware/camera2/CameraCharacteristics$Key;",
+        )?
+        .l()?;
+    let value = env
+        .call_method(
+            characteristics,
+            "get",
+            "(Landroid/hardware/camera2/CameraCharacteristics$Key;)Ljava/lang/Object;",
+            &[(&key).into()],
+        )?
+        .l()?;
+    if value.is_null() {
+        Ok(None)
+    } else {
+        Ok(Some(env.call_method(&value, "intValue", "()I", &[])?.i()?))
+    }
+}
+
+/// Enumerates the cameras reported by `CameraManager`, along with their lens
+/// facing and sensor orientation
+///
+/// We use the Java `CameraManager` API instead of the `ACameraManager` NDK API
+/// to avoid needing to link every application with `libcamera2ndk`.
+pub(crate) fn cameras(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+) -> InternalResult<Vec<CameraInfo>> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let service_name = env.new_string("camera")?;
+        let manager = env
+            .call_method(
+                &activity,
+                "getSystemService",
+                "(Ljava/lang/String;)Ljava/lang/Object;",
+                &[(&service_name).into()],
+            )?
+            .l()?;
+        if manager.is_null() {
+            return Ok(Vec::new());
+        }
+
+        let ids = env
+            .call_method(&manager, "getCameraIdList", "()[Ljava/lang/String;", &[])?
+            .l()?;
+        let ids = jni::objects::JObjectArray::from(ids);
+        let count = env.get_array_length(&ids)?;
+
+        let mut cameras = Vec::with_capacity(count as usize);
+        for i in 0..count {
+            // Use a local frame per camera to avoid running out of local references
+            let camera = env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+                let id = env.get_object_array_element(&ids, i)?;
+                let characteristics = env
+                    .call_method(
+                        &manager,
+                        "getCameraCharacteristics",
+                        "(Ljava/lang/String;)Landroid/hardware/camera2/CameraCharacteristics;",
+                        &[(&id).into()],
+                    )?
+                    .l()?;
+                let id = get_string(env, id)?.unwrap_or_default();
+                let lens_facing = camera_characteristic_int(env, &characteristics, "LENS_FACING")?
+                    .map(|facing| (facing as u32).into());
+                let sensor_orientation =
+                    camera_characteristic_int(env, &characteristics, "SENSOR_ORIENTATION")?;
+                Ok(CameraInfo {
+                    id,
+                    lens_facing,
+                    sensor_orientation,
+                })
+            })?;
+            cameras.push(camera);
+        }
+
+        Ok(cameras)
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Enumerates `StorageManager.getStorageVolumes()` for the given `Activity`
+///
+/// Requires API level 24
+pub(crate) fn storage_volumes(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+) -> InternalResult<Vec<StorageVolume>> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let service_name = env.new_string("storage")?;
+        let manager = env
+            .call_method(
+                &activity,
+                "getSystemService",
+                "(Ljava/lang/String;)Ljava/lang/Object;",
+                &[(&service_name).into()],
+            )?
+            .l()?;
+        if manager.is_null() {
+            return Ok(Vec::new());
+        }
+
+        let list = env
+            .call_method(&manager, "getStorageVolumes", "()Ljava/util/List;", &[])?
+            .l()?;
+        let count = env.call_method(&list, "size", "()I", &[])?.i()?;
+
+        let mut volumes = Vec::with_capacity(count as usize);
+        for i in 0..count {
+            // Use a local frame per volume to avoid running out of local references
+            let volume = env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+                let volume = env
+                    .call_method(&list, "get", "(I)Ljava/lang/Object;", &[i.into()])?
+                    .l()?;
+
+                let path = if AndroidApp::sdk_version() >= 30 {
+                    let dir = env
+                        .call_method(&volume, "getDirectory", "()Ljava/io/File;", &[])?
+                        .l()?;
+                    if dir.is_null() {
+                        None
+                    } else {
+                        let path = env
+                            .call_method(&dir, "getAbsolutePath", "()Ljava/lang/String;", &[])?
+                            .l()?;
+                        get_string(env, path)?.map(Into::into)
+                    }
+                } else {
+                    None
+                };
+
+                let uuid = env
+                    .call_method(&volume, "getUuid", "()Ljava/lang/String;", &[])?
+                    .l()?;
+                let uuid = get_string(env, uuid)?;
+                let description = env
+                    .call_method(
+                        &volume,
+                        "getDescription",
+                        "(Landroid/content/Context;)Ljava/lang/String;",
+                        &[(&activity).into()],
+                    )?
+                    .l()?;
+                let description = get_string(env, description)?;
+                let is_primary = env.call_method(&volume, "isPrimary", "()Z", &[])?.z()?;
+                let is_removable = env.call_method(&volume, "isRemovable", "()Z", &[])?.z()?;
+                let is_emulated = env.call_method(&volume, "isEmulated", "()Z", &[])?.z()?;
+                let state = env
+                    .call_method(&volume, "getState", "()Ljava/lang/String;", &[])?
+                    .l()?;
+                let state = get_string(env, state)?
+                    .map(|state| StorageVolumeState::from_java(&state))
+                    .unwrap_or(StorageVolumeState::Unknown);
+
+                Ok(StorageVolume {
+                    path,
+                    uuid,
+                    description,
+                    is_primary,
+                    is_removable,
+                    is_emulated,
+                    state,
+                })
+            })?;
+            volumes.push(volume);
+        }
+
+        Ok(volumes)
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Queries `Display.getHdrSdrRatio()` for the display of the given `Activity`
+///
+/// Requires API level 34
+pub(crate) fn hdr_sdr_ratio(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+) -> InternalResult<Option<f32>> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let display = activity_display(env, &activity)?;
+        if !env
+            .call_method(&display, "isHdrSdrRatioAvailable", "()Z", &[])?
+            .z()?
+        {
+            return Ok(None);
+        }
+        Ok(Some(
+            env.call_method(&display, "getHdrSdrRatio", "()F", &[])?
+                .f()?,
+        ))
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Calls `Window.setDesiredHdrHeadroom()` on the Java main thread
+///
+/// Requires API level 35
+pub(crate) fn set_desired_hdr_headroom(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+    headroom: f32,
+) -> InternalResult<()> {
+    ui_thread::run_with_activity_on_ui_thread(jvm, activity, move |env, activity| {
+        let window = env
+            .call_method(activity, "getWindow", "()Landroid/view/Window;", &[])?
+            .l()?;
+        env.call_method(&window, "setDesiredHdrHeadroom", "(F)V", &[headroom.into()])?;
+        Ok(())
+    })
+}
+
+/// Gets the `Display` that the given `Activity` is shown on
+///
+/// This uses `Activity.getDisplay()` on Android 11 (API level 30) and newer,
+/// since `WindowManager.getDefaultDisplay()` is deprecated there (and always
+/// returns the display the `WindowManager` was created for).
+fn activity_display<'local>(
+    env: &mut jni::JNIEnv<'local>,
+    activity: &JObject<'_>,
+) -> jni::errors::Result<JObject<'local>> {
+    if AndroidApp::sdk_version() >= 30 {
+        return env
+            .call_method(activity, "getDisplay", "()Landroid/view/Display;", &[])?
+            .l();
+    }
+    let window_manager = env
+        .call_method(
+            activity,
+            "getWindowManager",
+            "()Landroid/view/WindowManager;",
+            &[],
+        )?
+        .l()?;
+    env.call_method(
+        &window_manager,
+        "getDefaultDisplay",
+        "()Landroid/view/Display;",
+        &[],
+    )?
+    .l()
+}
+
+fn get_display_mode(
+    env: &mut jni::JNIEnv<'_>,
+    mode: &JObject<'_>,
+) -> jni::errors::Result<DisplayMode> {
+    Ok(DisplayMode {
+        id: int_method(env, mode, "getModeId")?,
+        width: int_method(env, mode, "getPhysicalWidth")?,
+        height: int_method(env, mode, "getPhysicalHeight")?,
+        refresh_rate: env.call_method(mode, "getRefreshRate", "()F", &[])?.f()?,
+    })
+}
+
+/// Queries `Display.getRefreshRate()` for the display of the given `Activity`
+pub(crate) fn display_refresh_rate(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+) -> InternalResult<f32> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let display = activity_display(env, &activity)?;
+        env.call_method(&display, "getRefreshRate", "()F", &[])?.f()
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Queries `Display.getMode()` for the display of the given `Activity`
+///
+/// Requires API level 23
+pub(crate) fn display_mode(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+) -> InternalResult<DisplayMode> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let display = activity_display(env, &activity)?;
+        let mode = env
+            .call_method(&display, "getMode", "()Landroid/view/Display$Mode;", &[])?
+            .l()?;
+        get_display_mode(env, &mode)
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Queries `Display.getSupportedModes()` for the display of the given `Activity`
+///
+/// Requires API level 23
+pub(crate) fn supported_display_modes(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+) -> InternalResult<Vec<DisplayMode>> {
+    // Attach 'permanently' to avoid any chance of detaching the thread from the VM
+    let mut env = jvm.attach_current_thread_permanently()?;
+
+    let activity = unsafe { JObject::from_raw(activity) };
+    env.with_local_frame::<_, _, jni::errors::Error>(10, |env| {
+        let display = activity_display(env, &activity)?;
+        let modes: jni::objects::JObjectArray = env
+            .call_method(
+                &display,
+                "getSupportedModes",
+                "()[Landroid/view/Display$Mode;",
+                &[],
+            )?
+            .l()?
+            .into();
+        let len = env.get_array_length(&modes)?;
+        let mut display_modes = Vec::with_capacity(len as usize);
+        for i in 0..len {
+            let mode = env.get_object_array_element(&modes, i)?;
+            display_modes.push(get_display_mode(env, &mode)?);
+            env.delete_local_ref(mode)?;
+        }
+        Ok(display_modes)
+    })
+    .map_err(|err| clear_and_map_exception_to_err(&mut env, err))
+}
+
+/// Sets `WindowManager.LayoutParams.preferredDisplayModeId` for the window of
+/// the given `Activity`, on the Java main thread
+///
+/// Requires API level 23
+pub(crate) fn set_preferred_display_mode(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+    mode_id: i32,
+) -> InternalResult<()> {
+    ui_thread::run_with_activity_on_ui_thread(jvm, activity, move |env, activity| {
+        let window = env
+            .call_method(activity, "getWindow", "()Landroid/view/Window;", &[])?
+            .l()?;
+        let params = env
+            .call_method(
+                &window,
+                "getAttributes",
+                "()Landroid/view/WindowManager$LayoutParams;",
+                &[],
+            )?
+            .l()?;
+        env.set_field(&params, "preferredDisplayModeId", "I", mode_id.into())?;
+        env.call_method(
+            &window,
+            "setAttributes",
+            "(Landroid/view/WindowManager$LayoutParams;)V",
+            &[(&params).into()],
+        )?;
+        Ok(())
+    })
+}
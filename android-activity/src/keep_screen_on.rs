@@ -0,0 +1,72 @@
+use std::sync::Mutex;
+
+use crate::WindowManagerFlags;
+
+#[derive(Debug, Default)]
+struct State {
+    requested: bool,
+    resumed: bool,
+    focused: bool,
+    applied: bool,
+}
+
+/// Tracks whether `FLAG_KEEP_SCREEN_ON` should currently be set for the
+/// window, based on what the application requested and whether the Activity
+/// is resumed and focused
+///
+/// Each setter returns the `(add_flags, remove_flags)` that need to be passed
+/// to `set_window_flags()`, if the window flags need to change.
+///
+/// See [`AndroidApp::set_keep_screen_on()`](crate::AndroidApp::set_keep_screen_on)
+#[derive(Debug, Default)]
+pub(crate) struct KeepScreenOn {
+    state: Mutex<State>,
+}
+
+impl KeepScreenOn {
+    pub(crate) fn set_requested(
+        &self,
+        requested: bool,
+    ) -> Option<(WindowManagerFlags, WindowManagerFlags)> {
+        self.update(|state| state.requested = requested)
+    }
+
+    pub(crate) fn set_resumed(
+        &self,
+        resumed: bool,
+    ) -> Option<(WindowManagerFlags, WindowManagerFlags)> {
+        self.update(|state| state.resumed = resumed)
+    }
+
+    pub(crate) fn set_focused(
+        &self,
+        focused: bool,
+    ) -> Option<(WindowManagerFlags, WindowManagerFlags)> {
+        self.update(|state| state.focused = focused)
+    }
+
+    fn update(
+        &self,
+        f: impl FnOnce(&mut State),
+    ) -> Option<(WindowManagerFlags, WindowManagerFlags)> {
+        let mut state = self.state.lock().unwrap();
+        f(&mut state);
+
+        let keep_on = state.requested && state.resumed && state.focused;
+        if keep_on == state.applied {
+            return None;
+        }
+        state.applied = keep_on;
+        if keep_on {
+            Some((
+                WindowManagerFlags::KEEP_SCREEN_ON,
+                WindowManagerFlags::empty(),
+            ))
+        } else {
+            Some((
+                WindowManagerFlags::empty(),
+                WindowManagerFlags::KEEP_SCREEN_ON,
+            ))
+        }
+    }
+}
@@ -54,6 +54,8 @@
 //!
 //! 1. An I/O thread is spawned that will handle redirecting standard input
 //!    and output to the Android log, visible via `logcat`.
+//!    See [`init_logging()`] for also setting up a [`log`] backend and a panic
+//!    hook that write to `logcat`.
 //! 2. A `JavaVM` and `Activity` instance will be associated with the [`ndk_context`] crate
 //!    so that other, independent, Rust crates are able to find a JavaVM
 //!    for making JNI calls.
@@ -106,6 +108,20 @@
 //! }
 //! ```
 //!
+//! # Synthesized Events
+//!
+//! Android reports some kinds of state changes via Java listeners, callbacks
+//! or `BroadcastReceiver`s. This crate doesn't require any Java code to be
+//! built into applications, so where possible it registers these at runtime via
+//! a minimal `NativeListener` class that's loaded from an embedded dex file.
+//! When a listener is notified it wakes up [`AndroidApp::poll_events()`], and
+//! the corresponding [`MainEvent`] is synthesized by checking the new state.
+//!
+//! Other events are synthesized by checking the state at particular points,
+//! such as when the Activity resumes or its window gains focus. Each of these
+//! events documents when it's checked, and changes that happen in between are
+//! reported late.
+//!
 //! [`Activity`]: https://developer.android.com/reference/android/app/Activity
 //! [`NativeActivity`]: https://developer.android.com/reference/android/app/NativeActivity
 //! [ndk_concepts]: https://developer.android.com/ndk/guides/concepts#naa
@@ -114,12 +130,13 @@
 
 #![deny(clippy::manual_let_else)]
 
+use std::any::Any;
 use std::hash::Hash;
 use std::sync::Arc;
 use std::sync::RwLock;
 use std::time::Duration;
 
-use input::KeyCharacterMap;
+use input::{InputMethodSubtype, InputOrigin, KeyCharacterMap};
 use libc::c_void;
 use ndk::asset::AssetManager;
 use ndk::native_window::NativeWindow;
@@ -163,13 +180,49 @@ use error::Result;
 
 pub mod input;
 
+pub mod camera;
+
+pub mod storage;
+
+pub mod logging;
+pub use logging::init_logging;
+
 mod config;
-pub use config::ConfigurationRef;
+pub use config::{ConfigChange, ConfigurationRef, ScreenSizeClass};
+
+mod resource_cache;
+pub use resource_cache::ResourceCache;
+
+mod keep_screen_on;
+
+mod non_config;
+
+#[cfg(feature = "serde")]
+mod saved_state;
+#[cfg(feature = "serde")]
+pub use saved_state::{SaveStateError, MAX_SAVED_STATE_LEN};
+
+mod visibility;
+
+mod window_size;
+
+mod input_devices;
+
+mod frame_callback;
+
+mod notify;
+
+mod listener;
+
+mod stats;
+pub use stats::LoopStats;
 
 mod util;
 
 mod jni_utils;
 
+mod ui_thread;
+
 /// A rectangle with integer edge coordinates. Used to represent window insets, for example.
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct Rect {
@@ -213,6 +266,325 @@ impl From<ndk_sys::ARect> for Rect {
     }
 }
 
+/// The current bounds of an application's window, and the maximum bounds it could occupy
+///
+/// See [`AndroidApp::window_metrics()`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct WindowMetrics {
+    /// The bounds of the area occupied by the application's window, in pixels
+    pub bounds: Rect,
+
+    /// The largest bounds that the application's window could occupy, in pixels
+    ///
+    /// When in split-screen mode, comparing this with [`Self::bounds`] can
+    /// show which side of the screen the application is on.
+    pub maximum_bounds: Rect,
+
+    /// Whether the Activity is currently in multi-window (e.g. split-screen) mode
+    pub in_multi_window_mode: bool,
+}
+
+/// The insets of the application's window, in pixels, for keeping content
+/// clear of system UI and display cutouts
+///
+/// Each [`Rect`] holds the thickness of the inset along each edge of the
+/// window, rather than a position.
+///
+/// See [`AndroidApp::window_insets()`]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct WindowInsets {
+    /// The insets of the status bar, navigation bar and caption bar
+    pub system_bars: Rect,
+
+    /// The safe insets that avoid any display cutout (such as a notch or
+    /// camera hole)
+    ///
+    /// This is empty on Android versions older than 9 (API level 28).
+    pub display_cutout: Rect,
+
+    /// The insets of the on-screen keyboard (IME), if it's shown
+    ///
+    /// This is empty on Android versions older than 11 (API level 30), where
+    /// the IME insets are instead included in [`Self::system_bars`] (if the
+    /// window is resized for the IME).
+    pub ime: Rect,
+}
+
+/// The area of the display that's not functional for displaying content, such
+/// as a notch or punch-hole camera
+///
+/// See [`AndroidApp::display_cutout()`]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct DisplayCutout {
+    /// The insets, in pixels, that avoid the cutout
+    ///
+    /// Like [`WindowInsets`], this holds the thickness of the inset along each
+    /// edge of the window.
+    pub safe_insets: Rect,
+
+    /// The bounding rectangles of each cutout, in window coordinates (pixels)
+    pub bounding_rects: Vec<Rect>,
+}
+
+/// A mode that a display can run in, with a specific resolution and refresh rate
+///
+/// See [`AndroidApp::supported_display_modes()`]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct DisplayMode {
+    /// The ID of the mode, for [`AndroidApp::set_preferred_display_mode()`]
+    pub id: i32,
+
+    /// The physical width of the display, in pixels, in this mode
+    pub width: i32,
+
+    /// The physical height of the display, in pixels, in this mode
+    pub height: i32,
+
+    /// The refresh rate of this mode, in Hz
+    pub refresh_rate: f32,
+}
+
+/// A snapshot of commonly-needed, immutable facts about the device and application
+///
+/// See [`AndroidApp::device_info()`]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct DeviceInfo {
+    /// The user-visible SDK version of the framework, as returned by [`AndroidApp::sdk_version()`]
+    pub sdk_version: i32,
+
+    /// The development codename of the framework, from
+    /// [`Build.VERSION.CODENAME`](https://developer.android.com/reference/android/os/Build.VERSION#CODENAME)
+    ///
+    /// This is `"REL"` for release builds.
+    pub sdk_codename: Option<String>,
+
+    /// The developer preview revision of a pre-release SDK, from
+    /// [`Build.VERSION.PREVIEW_SDK_INT`](https://developer.android.com/reference/android/os/Build.VERSION#PREVIEW_SDK_INT)
+    ///
+    /// This is `0` for release builds, and on Android versions older than 6.0
+    /// (API level 23).
+    pub preview_sdk_int: i32,
+
+    /// The manufacturer of the device, from [`Build.MANUFACTURER`](https://developer.android.com/reference/android/os/Build#MANUFACTURER)
+    pub manufacturer: Option<String>,
+
+    /// The end-user-visible name of the device, from [`Build.MODEL`](https://developer.android.com/reference/android/os/Build#MODEL)
+    pub model: Option<String>,
+
+    /// The ABIs supported by the device, in order of preference, from
+    /// [`Build.SUPPORTED_ABIS`](https://developer.android.com/reference/android/os/Build#SUPPORTED_ABIS),
+    /// as returned by [`AndroidApp::supported_abis()`]
+    pub supported_abis: Vec<String>,
+
+    /// The application's package name
+    pub package_name: Option<String>,
+
+    /// The application's user-visible version name, from `PackageInfo.versionName`
+    pub version_name: Option<String>,
+
+    /// The application's internal version number, from `PackageInfo.getLongVersionCode()`
+    pub version_code: i64,
+
+    /// Path to the directory containing the application's installed native
+    /// libraries, as returned by [`AndroidApp::native_library_dir()`]
+    pub native_library_dir: Option<std::path::PathBuf>,
+
+    /// Path to this application's internal data directory, as returned by [`AndroidApp::internal_data_path()`]
+    pub internal_data_path: Option<std::path::PathBuf>,
+
+    /// Path to this application's external data directory, as returned by [`AndroidApp::external_data_path()`]
+    pub external_data_path: Option<std::path::PathBuf>,
+
+    /// Path to the directory containing the application's OBB files, as returned by [`AndroidApp::obb_path()`]
+    pub obb_path: Option<std::path::PathBuf>,
+}
+
+/// Restrictions that apply to the current user, such as in a managed (work) profile
+///
+/// See [`AndroidApp::user_restrictions()`]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct UserRestrictions {
+    /// Whether the application is running in a managed (work) profile
+    ///
+    /// This is `None` on Android versions older than 11 (API level 30).
+    pub is_managed_profile: Option<bool>,
+
+    /// Whether clipboard data can't be shared across profiles
+    /// ([`DISALLOW_CROSS_PROFILE_COPY_PASTE`](https://developer.android.com/reference/android/os/UserManager#DISALLOW_CROSS_PROFILE_COPY_PASTE))
+    pub disallow_cross_profile_copy_paste: bool,
+
+    /// Whether transferring files over USB is disallowed
+    /// ([`DISALLOW_USB_FILE_TRANSFER`](https://developer.android.com/reference/android/os/UserManager#DISALLOW_USB_FILE_TRANSFER))
+    pub disallow_usb_file_transfer: bool,
+
+    /// Whether mounting physical external media is disallowed
+    /// ([`DISALLOW_MOUNT_PHYSICAL_MEDIA`](https://developer.android.com/reference/android/os/UserManager#DISALLOW_MOUNT_PHYSICAL_MEDIA))
+    pub disallow_mount_physical_media: bool,
+
+    /// Whether the user's location can't be shared
+    /// ([`DISALLOW_SHARE_LOCATION`](https://developer.android.com/reference/android/os/UserManager#DISALLOW_SHARE_LOCATION))
+    pub disallow_share_location: bool,
+
+    /// Whether screen capture has been disabled by a device policy, from
+    /// [`DevicePolicyManager.getScreenCaptureDisabled()`](https://developer.android.com/reference/android/app/admin/DevicePolicyManager#getScreenCaptureDisabled(android.content.ComponentName))
+    pub screen_capture_disabled: bool,
+}
+
+/// The level of a [`MainEvent::TrimMemory`] request, indicating how much the
+/// application should reduce its memory use
+///
+/// See [the ComponentCallbacks2 docs](https://developer.android.com/reference/android/content/ComponentCallbacks2#TRIM_MEMORY_UI_HIDDEN)
+///
+/// # Android Extensible Enum
+///
+/// This is a runtime [extensible enum](`crate#android-extensible-enums`) and
+/// should be handled similar to a `#[non_exhaustive]` enum to maintain
+/// forwards compatibility.
+///
+/// This implements `Into<i32>` and `From<i32>` for converting to/from Android
+/// SDK integer values.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, num_enum::FromPrimitive, num_enum::IntoPrimitive)]
+#[non_exhaustive]
+#[repr(i32)]
+pub enum TrimMemoryLevel {
+    /// The application is running, but the device is beginning to run low on
+    /// memory
+    ///
+    /// Release caches that are cheap to rebuild.
+    RunningModerate = 5,
+
+    /// The application is running, but the device is running much lower on
+    /// memory
+    ///
+    /// Release unused resources to improve system performance.
+    RunningLow = 10,
+
+    /// The application is running, but the device is extremely low on memory
+    /// and is about to kill background processes
+    ///
+    /// Release all non-critical resources.
+    RunningCritical = 15,
+
+    /// The application's UI is no longer visible
+    ///
+    /// This is a good time to free GPU-side UI resources, such as textures and
+    /// font atlases, that are only needed while the UI is shown.
+    UiHidden = 20,
+
+    /// The application is in the background and on the system's list of
+    /// processes to kill, near its least recently used end
+    ///
+    /// Release resources that are easy to recover when the user returns.
+    Background = 40,
+
+    /// The application is in the background, around the middle of the list
+    /// of processes to kill
+    ///
+    /// Release more resources, since the process is becoming likely to be killed.
+    Moderate = 60,
+
+    /// The application is in the background and will be one of the first
+    /// processes killed if the system doesn't recover memory soon
+    ///
+    /// Release everything that's not needed to restore the application's state.
+    Complete = 80,
+
+    #[doc(hidden)]
+    #[num_enum(catch_all)]
+    __Unknown(i32),
+}
+
+/// An audio stream type, whose volume can be queried or set
+///
+/// See [`AndroidApp::stream_volume()`] and the
+/// [AudioManager docs](https://developer.android.com/reference/android/media/AudioManager#STREAM_MUSIC)
+///
+/// # Android Extensible Enum
+///
+/// This is a runtime [extensible enum](`crate#android-extensible-enums`) and
+/// should be handled similar to a `#[non_exhaustive]` enum to maintain
+/// forwards compatibility.
+///
+/// This implements `Into<i32>` and `From<i32>` for converting to/from Android
+/// SDK integer values.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, num_enum::FromPrimitive, num_enum::IntoPrimitive)]
+#[non_exhaustive]
+#[repr(i32)]
+pub enum StreamType {
+    /// Phone calls
+    VoiceCall = 0,
+    /// System sounds
+    System = 1,
+    /// The phone ringer
+    Ring = 2,
+    /// Music and other media playback, including games
+    Music = 3,
+    /// Alarms
+    Alarm = 4,
+    /// Notifications
+    Notification = 5,
+    /// DTMF tones
+    Dtmf = 8,
+    /// Accessibility prompts
+    Accessibility = 10,
+
+    #[doc(hidden)]
+    #[num_enum(catch_all)]
+    __Unknown(i32),
+}
+
+/// A pixel format for the buffers of a [`NativeWindow`]
+///
+/// See [`AndroidApp::set_window_buffers_geometry()`]
+///
+/// This implements `Into<i32>` and `From<i32>` for converting to/from
+/// `AHardwareBuffer_Format` values.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, num_enum::FromPrimitive, num_enum::IntoPrimitive)]
+#[non_exhaustive]
+#[repr(i32)]
+pub enum HardwareBufferFormat {
+    /// 8 bits per channel, with alpha (`AHARDWAREBUFFER_FORMAT_R8G8B8A8_UNORM`)
+    Rgba8888 = ndk_sys::AHardwareBuffer_Format::AHARDWAREBUFFER_FORMAT_R8G8B8A8_UNORM.0 as i32,
+    /// 8 bits per channel, with the alpha channel ignored
+    /// (`AHARDWAREBUFFER_FORMAT_R8G8B8X8_UNORM`)
+    Rgbx8888 = ndk_sys::AHardwareBuffer_Format::AHARDWAREBUFFER_FORMAT_R8G8B8X8_UNORM.0 as i32,
+    /// 16 bits per pixel, without alpha (`AHARDWAREBUFFER_FORMAT_R5G6B5_UNORM`)
+    Rgb565 = ndk_sys::AHardwareBuffer_Format::AHARDWAREBUFFER_FORMAT_R5G6B5_UNORM.0 as i32,
+    /// 10 bits per color channel, with 2 bits of alpha
+    /// (`AHARDWAREBUFFER_FORMAT_R10G10B10A2_UNORM`, API level 26)
+    Rgba1010102 =
+        ndk_sys::AHardwareBuffer_Format::AHARDWAREBUFFER_FORMAT_R10G10B10A2_UNORM.0 as i32,
+    /// 16-bit floating point per channel, such as for HDR rendering
+    /// (`AHARDWAREBUFFER_FORMAT_R16G16B16A16_FLOAT`, API level 26)
+    RgbaFp16 = ndk_sys::AHardwareBuffer_Format::AHARDWAREBUFFER_FORMAT_R16G16B16A16_FLOAT.0 as i32,
+
+    #[doc(hidden)]
+    #[num_enum(catch_all)]
+    __Unknown(i32),
+}
+
+impl StreamType {
+    /// The streams that are checked for [`MainEvent::VolumeChanged`] events
+    pub(crate) const TRACKED: [StreamType; 6] = [
+        StreamType::VoiceCall,
+        StreamType::System,
+        StreamType::Ring,
+        StreamType::Music,
+        StreamType::Alarm,
+        StreamType::Notification,
+    ];
+}
+
+#[cfg(feature = "native-activity")]
+pub use activity_impl::NativeActivityCallbacks;
 pub use activity_impl::StateLoader;
 pub use activity_impl::StateSaver;
 
@@ -242,11 +614,140 @@ pub enum MainEvent<'a> {
     #[non_exhaustive]
     TerminateWindow {},
 
-    // TODO: include the prev and new size in the event
+    /// Synthesized after a [`MainEvent::InitWindow`] event when the new
+    /// [`NativeWindow`] replaces a window that was previously terminated via
+    /// [`MainEvent::TerminateWindow`]
+    ///
+    /// This isn't sent when a window is created for the first time, or when
+    /// the last window is terminated, and gives renderers a single hook for
+    /// rebuilding their surface instead of tracking `TerminateWindow` +
+    /// `InitWindow` pairs.
+    ///
+    /// Sizes are `(width, height)` in pixels.
+    #[non_exhaustive]
+    SurfaceRecreated {
+        old_size: (i32, i32),
+        new_size: (i32, i32),
+    },
+
+    /// Synthesized when the device's screen is turned off or on, as reported by
+    /// [`AndroidApp::is_interactive()`]
+    ///
+    /// This is delivered before the [`MainEvent::LostFocus`] or [`MainEvent::Pause`]
+    /// event that follows the screen turning off (and so before the Activity is
+    /// stopped), and before the [`MainEvent::GainedFocus`] or [`MainEvent::Resume`]
+    /// event after the screen is turned back on. Applications can use it to
+    /// distinguish the screen turning off from the user navigating away, such
+    /// as to stop sensor work early.
+    ///
+    /// _Note: this is [synthesized](crate#synthesized-events) from
+    /// `ACTION_SCREEN_OFF` / `ACTION_SCREEN_ON` broadcasts, which wake up
+    /// [`AndroidApp::poll_events()`]. The interactive state is also checked
+    /// whenever the Activity is started, resumed, paused or stopped, or its
+    /// window gains or loses focus._
+    #[non_exhaustive]
+    ScreenStateChanged { interactive: bool },
+
+    /// The content of the primary clipboard has changed
+    ///
+    /// Since Android 10 (API level 29), applications are only notified of
+    /// clipboard changes (and can only access the clipboard) while they have
+    /// input focus, and so changes made while the window isn't focused are
+    /// reported after the next [`MainEvent::GainedFocus`] event. This is the
+    /// point at which the new content is readable, such as for offering to
+    /// paste it.
+    ///
+    /// Checking for changes when the window gains focus is only supported on
+    /// Android 8.0 (API level 26) and newer.
+    ///
+    /// _Note: this is [synthesized](crate#synthesized-events) from a
+    /// `ClipboardManager.OnPrimaryClipChangedListener`, which wakes up
+    /// [`AndroidApp::poll_events()`]._
+    ClipboardChanged,
+
+    /// The volume of an audio stream has changed, which can be queried with
+    /// [`AndroidApp::stream_volume()`]
+    ///
+    /// _Note: this is [synthesized](crate#synthesized-events) from
+    /// `VOLUME_CHANGED_ACTION` broadcasts, which wake up
+    /// [`AndroidApp::poll_events()`]. Stream volumes are also checked when the
+    /// Activity resumes or its window gains focus._
+    ///
+    /// This is only reported for the [`StreamType::VoiceCall`],
+    /// [`StreamType::System`], [`StreamType::Ring`], [`StreamType::Music`],
+    /// [`StreamType::Alarm`] and [`StreamType::Notification`] streams.
+    #[non_exhaustive]
+    VolumeChanged { stream: StreamType },
+
+    /// An input device (such as a gamepad) has been connected, which can be
+    /// queried with [`AndroidApp::input_device_info()`]
+    ///
+    /// _Note: this is [synthesized](crate#synthesized-events) from an
+    /// `InputManager.InputDeviceListener`, which wakes up
+    /// [`AndroidApp::poll_events()`]. The attached input devices are also
+    /// checked when the Activity resumes or its window gains focus._
+    #[non_exhaustive]
+    InputDeviceAdded { id: i32 },
+
+    /// Command from main thread: pointer capture has been released by the
+    /// system, because the window lost focus
+    ///
+    /// This is delivered after the [`MainEvent::LostFocus`] event if pointer
+    /// capture had been requested with
+    /// [`AndroidApp::request_pointer_capture()`] (and not released with
+    /// [`AndroidApp::release_pointer_capture()`]).
+    ///
+    /// _Note: this is [synthesized](crate#synthesized-events), since
+    /// `View.onPointerCaptureChange()` can't be implemented, and so it's
+    /// currently only delivered with `captured: false` when the window loses
+    /// focus. Applications can use
+    /// [`AndroidApp::has_pointer_capture()`] to check whether a request was
+    /// granted._
+    #[non_exhaustive]
+    PointerCaptureChanged { captured: bool },
+
+    /// An input method (IME) has committed or composed text
+    ///
+    /// `text` is the full (UTF-8) text being edited by the input method and
+    /// `composing` is the byte range of `text` that's still being composed
+    /// (such as while entering CJK characters, or before an autocomplete
+    /// suggestion is accepted). Once the text is committed `composing` is
+    /// `None`, so composing updates can be distinguished from final commits.
+    ///
+    /// This is delivered before the corresponding
+    /// [`InputEvent::TextEvent`](input::InputEvent::TextEvent), which reports
+    /// the full [`input::TextInputState`], including the selection.
+    ///
+    /// _Note: this is only delivered with GameActivity, since NativeActivity
+    /// doesn't support input methods (see [`input::TextInputState`])._
+    #[non_exhaustive]
+    TextInput {
+        text: String,
+        composing: Option<std::ops::Range<usize>>,
+    },
+
+    /// An input device has been disconnected
+    ///
+    /// See the note for [`MainEvent::InputDeviceAdded`] about when this is
+    /// delivered.
+    #[non_exhaustive]
+    InputDeviceRemoved { id: i32 },
+
+    /// The sources of an input device have changed
+    ///
+    /// See the note for [`MainEvent::InputDeviceAdded`] about when this is
+    /// delivered.
+    #[non_exhaustive]
+    InputDeviceChanged { id: i32 },
+
+    // TODO: include the previous size in the event
     /// Command from main thread: the current [`NativeWindow`] has been resized.
     /// Please redraw with its new size.
+    ///
+    /// `width` and `height` are the new size of the window, in pixels, as
+    /// would be returned by [`AndroidApp::window_size()`].
     #[non_exhaustive]
-    WindowResized {},
+    WindowResized { width: i32, height: i32 },
 
     /// Command from main thread: the current [`NativeWindow`] needs to be redrawn.
     /// You should redraw the window before the [`AndroidApp::poll_events()`]
@@ -254,6 +755,36 @@ pub enum MainEvent<'a> {
     #[non_exhaustive]
     RedrawNeeded {},
 
+    /// A display vsync has occurred, following a call to
+    /// [`AndroidApp::request_frame_callback()`]
+    ///
+    /// `frame_time_nanos` is the time at which the frame started being
+    /// rendered, as reported by
+    /// [`AChoreographer`](https://developer.android.com/ndk/reference/group/choreographer),
+    /// in the [`AndroidApp::uptime()`] (`CLOCK_MONOTONIC`) time base. This is
+    /// more accurate than reading the time when the event is handled, and can
+    /// be used to predict the deadline for presenting the next frame.
+    ///
+    /// Only one `Frame` event is delivered per request, so applications that
+    /// want to render continuously should request another frame callback
+    /// while handling this event.
+    #[non_exhaustive]
+    Frame { frame_time_nanos: i64 },
+
+    /// The mode of the Activity's display has changed, such as after
+    /// [`AndroidApp::set_preferred_display_mode()`] switched to a higher
+    /// refresh rate
+    ///
+    /// _Note: this is [synthesized](crate#synthesized-events) instead of using
+    /// a `DisplayManager.DisplayListener`. Changes are detected when the
+    /// Activity is resumed, gains focus, is resized or its configuration
+    /// changes, and on Android 11 (API level 30) and newer also when
+    /// `AChoreographer` reports a refresh rate change (such as an adaptive
+    /// switch between 60 and 120 Hz). This is only supported on Android 6.0
+    /// (API level 23) and newer._
+    #[non_exhaustive]
+    DisplayModeChanged { mode: DisplayMode },
+
     /// Command from main thread: the content area of the window has changed,
     /// such as from the soft input window being shown or hidden.  You can
     /// get the new content rect by calling [`AndroidApp::content_rect()`]
@@ -271,13 +802,65 @@ pub enum MainEvent<'a> {
     /// Command from main thread: the current device configuration has changed.
     /// You can get a copy of the latest [`ndk::configuration::Configuration`] by calling
     /// [`AndroidApp::config()`]
+    ///
+    /// If the window size changes along with the configuration (such as when
+    /// folding or unfolding a device) and the window has already been resized,
+    /// a single [`MainEvent::WindowResized`] event is delivered immediately
+    /// after this event.
+    ///
+    /// For changes that aren't reported via the Activity's
+    /// `onConfigurationChanged()` (such as application-level changes, depending
+    /// on the manifest's `android:configChanges`), this is also
+    /// [synthesized](crate#synthesized-events) from `ComponentCallbacks2` that
+    /// are registered with the application context, and when the configuration
+    /// is re-checked whenever the Activity resumes or gains focus.
     #[non_exhaustive]
-    ConfigChanged {},
+    ConfigChanged {
+        /// The configuration qualifiers that changed, such as
+        /// [`ConfigChange::ORIENTATION`] or [`ConfigChange::DENSITY`]
+        changes: ConfigChange,
+        /// The new `screenWidthDp`, or `None` if it's undefined
+        screen_width_dp: Option<i32>,
+        /// The new `screenHeightDp`, or `None` if it's undefined
+        screen_height_dp: Option<i32>,
+    },
 
     /// Command from main thread: the system is running low on memory.
     /// Try to reduce your memory use.
+    ///
+    /// This corresponds to `onLowMemory()` and is only sent by the
+    /// `native-activity` backend.
+    ///
+    /// _Note: this is a legacy event that's kept for backwards compatibility.
+    /// New applications should handle [`MainEvent::TrimMemory`] instead, which
+    /// the `native-activity` backend delivers immediately after this event, with
+    /// a level of [`TrimMemoryLevel::Complete`]._
     LowMemory,
 
+    /// Command from main thread: the system would like the application to
+    /// reduce its memory use, to the degree indicated by `level`
+    ///
+    /// This corresponds to `onTrimMemory()` with the `game-activity` backend.
+    ///
+    /// `NativeActivity` doesn't forward `onTrimMemory()` to native code, so the
+    /// `native-activity` backend only sends this with a level of
+    /// [`TrimMemoryLevel::Complete`], after each [`MainEvent::LowMemory`] event.
+    ///
+    /// Graphics applications should check for [`TrimMemoryLevel::UiHidden`],
+    /// which is sent when the UI is no longer visible, to free GPU-side UI
+    /// caches. See [`TrimMemoryLevel`] for recommended responses to other
+    /// levels.
+    #[non_exhaustive]
+    TrimMemory { level: TrimMemoryLevel },
+
+    /// Command from main thread: the app's activity is being restarted, after
+    /// having previously been stopped.
+    ///
+    /// This is always followed by a [`MainEvent::Start`] event, and can be used
+    /// to distinguish restarting a stopped activity from starting it for the
+    /// first time.
+    Restart,
+
     /// Command from main thread: the app's activity has been started.
     Start,
 
@@ -304,8 +887,32 @@ pub enum MainEvent<'a> {
     Destroy,
 
     /// Command from main thread: the app's insets have changed.
+    ///
+    /// Applications can query the new insets with [`AndroidApp::window_insets()`].
+    ///
+    /// With NativeActivity this is synthesized after a
+    /// [`MainEvent::ContentRectChanged`] event if the insets have changed since
+    /// they were last checked.
     #[non_exhaustive]
     InsetsChanged {},
+
+    /// The on-screen keyboard (IME) has been shown or hidden, or its insets
+    /// have changed
+    ///
+    /// This is synthesized after a [`MainEvent::InsetsChanged`] event if the
+    /// IME insets have changed (see [`AndroidApp::ime_insets()`]). The insets
+    /// are the IME's final, resting insets rather than intermediate frames of
+    /// the keyboard's show/hide animation.
+    ///
+    /// _Note: with NativeActivity this is only supported on Android 11 (API
+    /// level 30) and newer._
+    #[non_exhaustive]
+    ImeVisibilityChanged {
+        /// Whether the IME is visible
+        visible: bool,
+        /// The insets of the IME, in pixels, which are empty when it's hidden
+        insets: Rect,
+    },
 }
 
 /// An event delivered during [`AndroidApp::poll_events`]
@@ -320,15 +927,251 @@ pub enum PollEvent<'a> {
 /// Indicates whether an application has handled or ignored an event
 ///
 /// If an event is not handled by an application then some default handling may happen.
+///
+/// With the `native-activity` backend this status is passed on to
+/// `AInputQueue_finishEvent()`, so that unhandled events fall through to the
+/// system. For example, returning [`InputStatus::Unhandled`] for
+/// [`Keycode::VolumeUp`](crate::input::Keycode::VolumeUp) lets the system
+/// change the volume, and returning it for
+/// [`Keycode::Back`](crate::input::Keycode::Back) lets the system finish the
+/// `Activity` or navigate back.
+///
+/// With the `game-activity` backend, events are delivered to the application
+/// after the Java `GameActivity` has already reported them as handled or not,
+/// so this status currently has no effect.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum InputStatus {
+    /// The event was consumed by the application, and should not be handled
+    /// by the system
     Handled,
+
+    /// The event was ignored by the application, and may lead to some default
+    /// handling by the system
     Unhandled,
 }
 
+/// Identifies which `Activity` backend this crate was built to support
+///
+/// See [`AndroidApp::backend()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Backend {
+    /// The `native-activity` backend, based on the
+    /// [`NativeActivity`](https://developer.android.com/reference/android/app/NativeActivity) class
+    NativeActivity,
+
+    /// The `game-activity` backend, based on the
+    /// [`GameActivity`](https://developer.android.com/games/agdk/game-activity) class
+    GameActivity,
+}
+
+/// How visible the application's Activity is, derived from its lifecycle and focus state
+///
+/// Renderers can use this to choose between not rendering at all, rendering at a
+/// reduced rate or rendering at full rate.
+///
+/// See [`AndroidApp::visibility()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Visibility {
+    /// The Activity is not visible (it hasn't started yet, or has been stopped)
+    Hidden,
+
+    /// The Activity is visible but doesn't have input focus, such as when a dialog,
+    /// the notification shade or another app in multi-window mode has focus
+    Visible,
+
+    /// The Activity is visible and has input focus
+    Focused,
+}
+
+/// The lifecycle state of the application's Activity, as of the last lifecycle
+/// event delivered to `android_main`
+///
+/// See [`AndroidApp::lifecycle_state()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LifecycleState {
+    /// No lifecycle event has been delivered yet
+    Init,
+    /// After [`MainEvent::Start`]
+    Start,
+    /// After [`MainEvent::Resume`]
+    Resume,
+    /// After [`MainEvent::Pause`]
+    Pause,
+    /// After [`MainEvent::Stop`]
+    Stop,
+}
+
+/// The context that `android_main` is running in, for libraries that need to
+/// avoid assuming that a UI exists
+///
+/// See [`AndroidApp::launch_context()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LaunchContext {
+    /// The Activity is valid and the process is running in the foreground,
+    /// with a UI that's visible to the user
+    ForegroundActivity,
+
+    /// The Activity is valid, but the process isn't in the foreground, such as
+    /// when the Activity was started behind the lock screen or the process is
+    /// primarily running a service
+    BackgroundActivity,
+
+    /// The Activity is finishing or has been destroyed, so no UI should be
+    /// assumed
+    FinishingActivity,
+}
+
+/// Details of the `Intent` that started the Activity
+///
+/// See [`AndroidApp::launch_intent()`]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct LaunchIntent {
+    /// The intent's action, such as `"android.intent.action.MAIN"`
+    pub action: Option<String>,
+
+    /// The intent's data URI, as a string
+    pub data: Option<String>,
+
+    /// The intent's raw `FLAG_*` flags, from
+    /// [`Intent.getFlags()`](https://developer.android.com/reference/android/content/Intent#getFlags())
+    pub flags: i32,
+
+    /// Whether the Activity was relaunched from the recent apps overview,
+    /// rather than freshly launched, based on
+    /// [`FLAG_ACTIVITY_LAUNCHED_FROM_HISTORY`](https://developer.android.com/reference/android/content/Intent#FLAG_ACTIVITY_LAUNCHED_FROM_HISTORY)
+    ///
+    /// Applications can use this to skip a splash screen, for example.
+    pub launched_from_history: bool,
+}
+
+/// Standard haptic feedback types, for [`AndroidApp::perform_haptic_feedback()`]
+///
+/// See [the HapticFeedbackConstants docs](https://developer.android.com/reference/android/view/HapticFeedbackConstants)
+///
+/// # Android Extensible Enum
+///
+/// This is a runtime [extensible enum](`crate#android-extensible-enums`) and
+/// should be handled similar to a `#[non_exhaustive]` enum to maintain
+/// forwards compatibility.
+///
+/// This implements `Into<u32>` and `From<u32>` for converting to/from Android
+/// SDK integer values.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, num_enum::FromPrimitive, num_enum::IntoPrimitive)]
+#[non_exhaustive]
+#[repr(u32)]
+pub enum HapticFeedbackConstant {
+    /// The user has performed a long press on an object that is resulting in an action being performed
+    LongPress = 0,
+    /// The user has pressed on a virtual on-screen key
+    VirtualKey = 1,
+    /// The user has pressed a soft keyboard key
+    KeyboardTap = 3,
+    /// The user has pressed either an hour or minute tick of a clock
+    ClockTick = 4,
+    /// The user has performed a context click on an object
+    ContextClick = 6,
+    /// The user has released a soft keyboard key (API level 27)
+    KeyboardRelease = 7,
+    /// The user has released a virtual key (API level 27)
+    VirtualKeyRelease = 8,
+    /// The user has performed a selection/insertion handle move on text field (API level 27)
+    TextHandleMove = 9,
+    /// The user has started a gesture (API level 30)
+    GestureStart = 12,
+    /// The user has finished a gesture (API level 30)
+    GestureEnd = 13,
+    /// A haptic effect to signal the confirmation or successful completion of a user interaction (API level 30)
+    Confirm = 16,
+    /// A haptic effect to signal the rejection or failure of a user interaction (API level 30)
+    Reject = 17,
+    /// The user has toggled a switch or button into the on position (API level 34)
+    ToggleOn = 21,
+    /// The user has toggled a switch or button into the off position (API level 34)
+    ToggleOff = 22,
+    /// The user is executing a swipe/drag-style gesture and has passed the activation threshold (API level 34)
+    GestureThresholdActivate = 23,
+    /// The user is executing a swipe/drag-style gesture and has cancelled past the activation threshold (API level 34)
+    GestureThresholdDeactivate = 24,
+    /// The user has started a drag-and-drop gesture (API level 34)
+    DragStart = 25,
+    /// The user is switching between a series of potential choices (API level 34)
+    SegmentTick = 26,
+    /// The user is switching between a series of many potential choices (API level 34)
+    SegmentFrequentTick = 27,
+
+    #[doc(hidden)]
+    #[num_enum(catch_all)]
+    __Unknown(u32),
+}
+
+/// Identifies a Java main thread callback that is blocked, waiting for the
+/// application's `android_main` thread to respond
+///
+/// See [`AndroidApp::set_anr_risk_callback()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BlockedOperation {
+    /// Waiting for a [`MainEvent::Start`] to be handled
+    Start,
+    /// Waiting for a [`MainEvent::Resume`] to be handled
+    Resume,
+    /// Waiting for a [`MainEvent::Pause`] to be handled
+    Pause,
+    /// Waiting for a [`MainEvent::Stop`] to be handled
+    Stop,
+    /// Waiting for a [`MainEvent::SaveState`] to be handled
+    SaveState,
+    /// Waiting for a [`MainEvent::InitWindow`] or [`MainEvent::TerminateWindow`] to be handled
+    WindowChanged,
+    /// Waiting for the application to acknowledge a new (or removed) input queue
+    InputQueueChanged,
+    /// Waiting for the `android_main` thread to exit after a [`MainEvent::Destroy`]
+    Destroy,
+}
+
+pub(crate) type AnrRiskCallbackFn = Arc<dyn Fn(BlockedOperation) + Send + Sync>;
+
+pub(crate) type CustomCmdHandlerFn = Arc<dyn Fn(u8) + Send + Sync>;
+
+/// A handler registered via [`AndroidApp::set_custom_cmd_handler()`]
+#[derive(Clone)]
+pub(crate) struct CustomCmdHandler(pub(crate) CustomCmdHandlerFn);
+impl std::fmt::Debug for CustomCmdHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("<handler>")
+    }
+}
+
 use activity_impl::AndroidAppInner;
 pub use activity_impl::AndroidAppWaker;
 
+bitflags! {
+    /// The power sources that keep the device awake while plugged in
+    ///
+    /// See [`AndroidApp::stay_on_while_plugged_in()`] and
+    /// [`Settings.Global.STAY_ON_WHILE_PLUGGED_IN`](https://developer.android.com/reference/android/provider/Settings.Global#STAY_ON_WHILE_PLUGGED_IN)
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+    pub struct StayOnWhilePluggedIn: u32 {
+        /// Stay awake while connected to an AC charger
+        const AC = 0x1;
+
+        /// Stay awake while connected to a USB charger
+        const USB = 0x2;
+
+        /// Stay awake while charging wirelessly
+        const WIRELESS = 0x4;
+
+        /// Stay awake while docked
+        const DOCK = 0x8;
+    }
+}
+
 bitflags! {
     /// Flags for [`AndroidApp::set_window_flags`]
     /// as per the [android.view.WindowManager.LayoutParams Java API](https://developer.android.com/reference/android/view/WindowManager.LayoutParams)
@@ -534,30 +1377,109 @@ impl Hash for AndroidApp {
 }
 
 impl AndroidApp {
+    /// The first command byte that's available for extending the command
+    /// protocol between the Java main thread and the `android_main` thread
+    ///
+    /// Commands below this value are reserved for this crate. See
+    /// [`AndroidApp::set_custom_cmd_handler()`].
+    pub const CUSTOM_CMD_MIN: u8 = 64;
+
     /// Queries the current [`NativeWindow`] for the application.
     ///
     /// This will only return `Some(window)` between
     /// [`MainEvent::InitWindow`] and [`MainEvent::TerminateWindow`]
     /// events.
+    ///
+    /// This is safe to call from any thread: the window is cloned while
+    /// holding the same lock that's used to hand windows over from the Java
+    /// main thread, so it can't race with the window being terminated. The
+    /// returned handle holds its own reference (via `ANativeWindow_acquire()`)
+    /// that keeps the underlying `ANativeWindow` alive until it's dropped, even
+    /// after a [`MainEvent::TerminateWindow`] event.
+    ///
+    /// However, once [`MainEvent::TerminateWindow`] has been handled, the
+    /// window's surface may be destroyed by the system, so renderers should
+    /// stop drawing to it and drop any handles that were queried before then.
     pub fn native_window(&self) -> Option<NativeWindow> {
         self.inner.read().unwrap().native_window()
     }
 
-    /// Returns a pointer to the Java Virtual Machine, for making JNI calls
+    /// Queries the `(width, height)` of the current [`NativeWindow`], in pixels
     ///
-    /// This returns a pointer to the Java Virtual Machine which can be used
-    /// with the [`jni`] crate (or similar crates) to make JNI calls that bridge
-    /// between native Rust code and Java/Kotlin code running within the JVM.
+    /// This is a convenience for querying the size of
+    /// [`AndroidApp::native_window()`], and returns `None` when there is no
+    /// window (or if `ANativeWindow_getWidth()`/`getHeight()` report an error,
+    /// as a negative value). The new size is also delivered with each
+    /// [`MainEvent::WindowResized`] event.
+    pub fn window_size(&self) -> Option<(u32, u32)> {
+        let window = self.native_window()?;
+        let width = u32::try_from(window.width()).ok()?;
+        let height = u32::try_from(window.height()).ok()?;
+        Some((width, height))
+    }
+
+    /// Sets the size and format of the buffers of the current [`NativeWindow`]
     ///
-    /// If you use the [`jni`] crate you can wrap this as a [`JavaVM`] via:
-    /// ```ignore
-    /// # use jni::JavaVM;
-    /// # let app: AndroidApp = todo!();
-    /// let vm = unsafe { JavaVM::from_raw(app.vm_as_ptr()) };
-    /// ```
+    /// This is a wrapper for
+    /// [`ANativeWindow_setBuffersGeometry()`](https://developer.android.com/ndk/reference/group/a-native-window#anativewindow_setbuffersgeometry).
+    /// The `width` and `height` control the number of pixels in the buffers,
+    /// not the size of the window on screen, and the compositor scales the
+    /// buffers to fit the window. This can be used to render at a lower
+    /// internal resolution, such as for pixel-art games.
     ///
-    /// [`jni`]: https://crates.io/crates/jni
-    /// [`JavaVM`]: https://docs.rs/jni/latest/jni/struct.JavaVM.html
+    /// The `width` and `height` must either both be zero or both be non-zero,
+    /// and passing `0` (or `None` for the `format`) resets to the window's
+    /// default.
+    ///
+    /// This only applies to the current window, so it should be called again
+    /// after each [`MainEvent::InitWindow`] event.
+    ///
+    /// Returns `false` if there is no window or its geometry couldn't be set.
+    pub fn set_window_buffers_geometry(
+        &self,
+        width: i32,
+        height: i32,
+        format: Option<HardwareBufferFormat>,
+    ) -> bool {
+        let Some(window) = self.native_window() else {
+            return false;
+        };
+        let format = format.map_or(0, i32::from);
+        let status = unsafe {
+            ndk_sys::ANativeWindow_setBuffersGeometry(window.ptr().as_ptr(), width, height, format)
+        };
+        if status != 0 {
+            log::error!("Failed to set window buffers geometry: {status}");
+            return false;
+        }
+        true
+    }
+
+    /// Returns the screen density in dpi, from the current configuration
+    ///
+    /// This is the same as [`ConfigurationRef::density()`], except that an
+    /// unspecified density is reported as the baseline density of 160 dpi
+    /// (`DisplayMetrics.DENSITY_DEFAULT`). Divide by 160 to get the scale
+    /// factor for converting density-independent pixels (dp) into pixels.
+    pub fn density_dpi(&self) -> u32 {
+        self.config().density().unwrap_or(160)
+    }
+
+    /// Returns a pointer to the Java Virtual Machine, for making JNI calls
+    ///
+    /// This returns a pointer to the Java Virtual Machine which can be used
+    /// with the [`jni`] crate (or similar crates) to make JNI calls that bridge
+    /// between native Rust code and Java/Kotlin code running within the JVM.
+    ///
+    /// If you use the [`jni`] crate you can wrap this as a [`JavaVM`] via:
+    /// ```ignore
+    /// # use jni::JavaVM;
+    /// # let app: AndroidApp = todo!();
+    /// let vm = unsafe { JavaVM::from_raw(app.vm_as_ptr()) };
+    /// ```
+    ///
+    /// [`jni`]: https://crates.io/crates/jni
+    /// [`JavaVM`]: https://docs.rs/jni/latest/jni/struct.JavaVM.html
     pub fn vm_as_ptr(&self) -> *mut c_void {
         self.inner.read().unwrap().vm_as_ptr()
     }
@@ -611,23 +1533,134 @@ impl AndroidApp {
         self.inner.read().unwrap().poll_events(timeout, callback);
     }
 
+    /// Restores the normal routing of input events to [`AndroidApp::poll_events()`]
+    ///
+    /// This re-attaches the current input queue to the `android_main` thread's
+    /// `ALooper` and is intended for advanced integrations that temporarily take
+    /// control of the looper (such as via [`ndk::looper::ThreadLooper`]) and
+    /// may have detached the input queue.
+    ///
+    /// After calling this, new input will once again result in a
+    /// [`MainEvent::InputAvailable`] event.
+    ///
+    /// _Note: this is a no-op with the `game-activity` backend, which doesn't
+    /// deliver input via the looper._
+    pub fn reattach_input(&self) {
+        self.inner.read().unwrap().reattach_input()
+    }
+
     /// Creates a means to wake up the main loop while it is blocked waiting for
     /// events within [`AndroidApp::poll_events()`].
+    ///
+    /// The returned [`AndroidAppWaker`] is `Send`, `Sync` and cheap to clone, so
+    /// it can be handed to other threads, such as a render or network thread
+    /// that wants the main loop to process some new work.
+    ///
+    /// [`AndroidAppWaker::wake()`] is based on
+    /// [`ALooper_wake()`](https://developer.android.com/ndk/reference/group/looper#alooper_wake),
+    /// which is independent of the commands that are sent from the Java main
+    /// thread, and never blocks or takes any of this crate's internal locks.
+    /// Multiple wake ups that happen before the main loop runs may be
+    /// coalesced into a single [`PollEvent::Wake`] event, so applications
+    /// should track their own pending work (such as via a channel) and drain
+    /// it all after being woken. This crate's own notifications (such as for
+    /// [`MainEvent::Frame`] events) don't use `ALooper_wake()`, so they can't
+    /// be mistaken for, or swallow, an application's wake up.
     pub fn create_waker(&self) -> AndroidAppWaker {
         self.inner.read().unwrap().create_waker()
     }
 
+    /// Ensures that the next call to [`AndroidApp::poll_events()`] will return
+    /// immediately, with a [`PollEvent::Wake`] event, instead of blocking.
+    ///
+    /// This is a cheap alternative to [`AndroidApp::create_waker()`] for code
+    /// running on the main thread that wants to re-run its event loop body
+    /// (e.g. after queuing some internal work) without waiting for the next
+    /// external event or timeout.
+    pub fn wake(&self) {
+        self.inner.read().unwrap().wake()
+    }
+
+    /// Requests a [`MainEvent::Frame`] event on the next display vsync
+    ///
+    /// This is based on
+    /// [`AChoreographer_postFrameCallback64()`](https://developer.android.com/ndk/reference/group/choreographer#achoreographer_postframecallback64),
+    /// which lets applications pace their rendering and simulation to the
+    /// display's refresh rate without busy-looping.
+    ///
+    /// Each request results in a single `Frame` event, so this should be
+    /// called again while handling that event to keep receiving them. Multiple
+    /// requests that are made before the next vsync are coalesced into a
+    /// single event.
+    ///
+    /// This can be called from any thread, but the callback is registered
+    /// from within [`AndroidApp::poll_events()`] on the main thread.
+    ///
+    /// Returns `false` if `AChoreographer` isn't supported on this device
+    /// (before Android 7.0, API level 24). Before Android 10 (API level 29),
+    /// frame times may be truncated on 32-bit devices.
+    pub fn request_frame_callback(&self) -> bool {
+        self.inner.read().unwrap().request_frame_callback()
+    }
+
     /// Returns a (cheaply clonable) reference to this application's [`ndk::configuration::Configuration`]
     pub fn config(&self) -> ConfigurationRef {
         self.inner.read().unwrap().config()
     }
 
+    /// Classifies the screen as [`ScreenSizeClass::Compact`], [`ScreenSizeClass::Medium`]
+    /// or [`ScreenSizeClass::Expanded`], for choosing between phone and tablet layouts
+    ///
+    /// This is derived from the configuration's `smallestScreenWidthDp` and so
+    /// it may change after a [`MainEvent::ConfigChanged`] event (such as when
+    /// entering split-screen mode).
+    ///
+    /// Returns `None` if the smallest screen width isn't known.
+    pub fn screen_size_class(&self) -> Option<ScreenSizeClass> {
+        self.config().screen_size_class()
+    }
+
+    /// Returns the user's preferred scaling factor for fonts
+    ///
+    /// This is relative to the base density scaling, so `1.0` represents the
+    /// default font size and a larger value indicates that the user has
+    /// requested larger text (e.g. via accessibility settings).
+    ///
+    /// Applications that render their own text should scale font sizes by this
+    /// factor and re-query it after a [`MainEvent::ConfigChanged`] event.
+    ///
+    /// Note: an Activity will only receive a [`MainEvent::ConfigChanged`]
+    /// event for font scale changes if it declares that it handles
+    /// `fontScale` changes via `android:configChanges` in its
+    /// `AndroidManifest.xml`, otherwise the system will restart the Activity.
+    pub fn font_scale(&self) -> f32 {
+        self.inner.read().unwrap().font_scale()
+    }
+
     /// Queries the current content rectangle of the window; this is the area where the
     /// window's content should be placed to be seen by the user.
     pub fn content_rect(&self) -> Rect {
         self.inner.read().unwrap().content_rect()
     }
 
+    /// Queries the insets of the on-screen keyboard (IME), in pixels
+    ///
+    /// Like [`WindowInsets`], this holds the thickness of the inset along each
+    /// edge of the window, so the height of the keyboard is normally
+    /// [`Rect::bottom`]. This is an empty [`Rect`] while the keyboard is
+    /// hidden.
+    ///
+    /// Text-editing applications can use this to scroll the focused text field
+    /// into view, and can re-query it after
+    /// [`MainEvent::ImeVisibilityChanged`] events.
+    ///
+    /// _Note: with NativeActivity this is based on
+    /// [`AndroidApp::window_insets()`], and so is always empty on Android
+    /// versions older than 11 (API level 30)._
+    pub fn ime_insets(&self) -> Rect {
+        self.inner.read().unwrap().ime_insets()
+    }
+
     /// Queries the Asset Manager instance for the application.
     ///
     /// Use this to access binary assets bundled inside your application's .apk file.
@@ -635,11 +1668,43 @@ impl AndroidApp {
         self.inner.read().unwrap().asset_manager()
     }
 
+    /// Returns the application's `AAssetManager` as a raw pointer
+    ///
+    /// This is for passing to C libraries that read assets directly via the
+    /// [`AAssetManager`](https://developer.android.com/ndk/reference/group/asset)
+    /// API. Rust code should prefer [`AndroidApp::asset_manager()`].
+    ///
+    /// # Safety
+    ///
+    /// Although this function is safe, the pointer is owned by the `Activity` and
+    /// is only valid until the Activity is destroyed (after a [`MainEvent::Destroy`]
+    /// event), so it must not be used after `android_main` returns. The pointer
+    /// must not be freed.
+    pub fn asset_manager_ptr(&self) -> *mut ndk_sys::AAssetManager {
+        self.asset_manager().ptr().as_ptr()
+    }
+
     /// Change the window flags of the given activity.
     ///
     /// Note that some flags must be set before the window decoration is created,
     /// see
     /// `<https://developer.android.com/reference/android/view/Window#setFlags(int,%20int)>`.
+    ///
+    /// This is a wrapper for `ANativeActivity_setWindowFlags()` (or
+    /// `GameActivity_setWindowFlags()`), which applies the change
+    /// asynchronously on the Java main thread. For example,
+    /// [`WindowManagerFlags::SECURE`] prevents the window's content from
+    /// appearing in screenshots or on non-secure displays, and
+    /// [`WindowManagerFlags::SHOW_WHEN_LOCKED`] and
+    /// [`WindowManagerFlags::TURN_SCREEN_ON`] let an alarm or call screen be
+    /// shown over the lock screen.
+    ///
+    /// To keep the screen on during video playback, prefer
+    /// [`AndroidApp::set_keep_screen_on()`], which only sets
+    /// [`WindowManagerFlags::KEEP_SCREEN_ON`] while the Activity is in the
+    /// foreground. [`WindowManagerFlags::FULLSCREEN`] only hides the status bar;
+    /// for an immersive, edge-to-edge layout see
+    /// [`AndroidApp::set_decor_fits_system_windows()`].
     pub fn set_window_flags(
         &self,
         add_flags: WindowManagerFlags,
@@ -651,6 +1716,65 @@ impl AndroidApp {
             .set_window_flags(add_flags, remove_flags);
     }
 
+    /// Queries how visible the Activity currently is
+    ///
+    /// This combines the [`MainEvent::Start`] / [`MainEvent::Stop`] lifecycle
+    /// events with the [`MainEvent::GainedFocus`] / [`MainEvent::LostFocus`]
+    /// events into a single [`Visibility`] state.
+    ///
+    /// This is updated before the corresponding event is passed to the
+    /// application's [`AndroidApp::poll_events()`] callback.
+    pub fn visibility(&self) -> Visibility {
+        self.inner.read().unwrap().visibility()
+    }
+
+    /// Returns `true` if the Activity's window currently has input focus
+    ///
+    /// This is `false` until the first [`MainEvent::GainedFocus`] event and is
+    /// then updated by [`MainEvent::GainedFocus`] / [`MainEvent::LostFocus`]
+    /// events, before they are passed to the application's
+    /// [`AndroidApp::poll_events()`] callback.
+    ///
+    /// Games can use this to pause audio or ignore input while another window
+    /// (such as a dialog or the notification shade) has focus.
+    pub fn is_window_focused(&self) -> bool {
+        self.inner.read().unwrap().is_window_focused()
+    }
+
+    /// Queries the current lifecycle state of the Activity
+    ///
+    /// This lets code that doesn't see the full stream of [`MainEvent`]s (such
+    /// as a plugin that's loaded after the Activity has resumed) query the
+    /// state instead of having to replay events.
+    ///
+    /// This is updated before the corresponding event is passed to the
+    /// application's [`AndroidApp::poll_events()`] callback.
+    pub fn lifecycle_state(&self) -> LifecycleState {
+        self.inner.read().unwrap().lifecycle_state()
+    }
+
+    /// Returns `true` if the Activity is resumed
+    ///
+    /// This is a shorthand for checking if [`AndroidApp::lifecycle_state()`]
+    /// is [`LifecycleState::Resume`].
+    pub fn is_resumed(&self) -> bool {
+        self.lifecycle_state() == LifecycleState::Resume
+    }
+
+    /// Keeps the screen on while the Activity is resumed and has focus
+    ///
+    /// This manages [`WindowManagerFlags::KEEP_SCREEN_ON`] for you, so the flag is
+    /// set while the Activity is both resumed and focused and is automatically
+    /// cleared when the Activity is paused or loses focus (and restored when it
+    /// resumes and regains focus), so the screen isn't accidentally kept on
+    /// after the application has been sent to the background.
+    ///
+    /// Avoid also setting [`WindowManagerFlags::KEEP_SCREEN_ON`] directly via
+    /// [`AndroidApp::set_window_flags()`] since the two would conflict.
+    pub fn set_keep_screen_on(&self, keep_on: bool) {
+        self.inner.read().unwrap().set_keep_screen_on(keep_on);
+    }
+
     /// Enable additional input axis
     ///
     /// To reduce overhead, by default only [`input::Axis::X`] and [`input::Axis::Y`] are enabled
@@ -672,10 +1796,43 @@ impl AndroidApp {
     ///
     /// Call this if the user interacts with your view in such a way that they
     /// have expressed they would like to start performing input into it.
+    ///
+    /// This may silently do nothing if there's no soft keyboard available, such
+    /// as when a hardware keyboard is attached. See [`AndroidApp::ime_available()`].
+    ///
+    /// With NativeActivity this calls
+    /// [`InputMethodManager.showSoftInput()`](https://developer.android.com/reference/android/view/inputmethod/InputMethodManager#showSoftInput(android.view.View,%20int))
+    /// on the Java main thread, waiting briefly for its result, and falls back
+    /// to `ANativeActivity_showSoftInput()` if that fails. It shouldn't be
+    /// called from the Java main thread itself.
     pub fn show_soft_input(&self, show_implicit: bool) {
         self.inner.read().unwrap().show_soft_input(show_implicit);
     }
 
+    /// Queries whether a soft keyboard is expected to be shown by
+    /// [`AndroidApp::show_soft_input()`]
+    ///
+    /// This returns `false` if there are no enabled input methods, or if a
+    /// hardware keyboard is attached (in which case Android won't normally show
+    /// a soft keyboard), so that applications can decide to show their own
+    /// on-screen keyboard instead.
+    ///
+    /// This is a heuristic: the user may have configured their device to show
+    /// the soft keyboard even while a hardware keyboard is attached.
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn ime_available(&self) -> Result<bool> {
+        let inner = self.inner.read().unwrap();
+        Ok(jni_utils::ime_available(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+        )?)
+    }
+
     /// Request to hide the soft input window from the context of the window
     /// that is currently accepting input.
     ///
@@ -689,6 +1846,9 @@ impl AndroidApp {
     }
 
     /// Fetch the current input text state, as updated by any active IME.
+    ///
+    /// _Note: this is only supported with GameActivity, and is always empty
+    /// with NativeActivity (see [`input::TextInputState`])._
     pub fn text_input_state(&self) -> input::TextInputState {
         self.inner.read().unwrap().text_input_state()
     }
@@ -754,20 +1914,25 @@ impl AndroidApp {
     /// This must only be called from your `android_main()` thread and it may panic if called
     /// from another thread.
     pub fn input_events_iter(&self) -> Result<input::InputIterator> {
-        let receiver = {
+        let (receiver, stats) = {
             let guard = self.inner.read().unwrap();
-            guard.input_events_receiver()?
+            (guard.input_events_receiver()?, guard.stats.clone())
         };
 
         Ok(input::InputIterator {
             inner: receiver.into(),
+            stats,
         })
     }
 
     /// Lookup the [`KeyCharacterMap`] for the given input `device_id`
     ///
     /// Use [`KeyCharacterMap::get`] to map key codes + meta state into unicode characters
-    /// or dead keys that compose with the next key.
+    /// or dead keys that compose with the next key. The map is cached per device, so
+    /// this is cheap to call for each key event.
+    ///
+    /// [`input::DeadKeyComposer`] can be used to combine dead keys with the next
+    /// character, or this can be handled manually as below.
     ///
     /// # Example
     ///
@@ -833,17 +1998,1175 @@ impl AndroidApp {
             .device_key_character_map(device_id)?)
     }
 
-    /// The user-visible SDK version of the framework
+    /// Determines whether an input event came from a genuine touchscreen, as
+    /// opposed to a virtual device
     ///
-    /// Also referred to as [`Build.VERSION_CODES`](https://developer.android.com/reference/android/os/Build.VERSION_CODES)
-    pub fn sdk_version() -> i32 {
-        let mut prop = android_properties::getprop("ro.build.version.sdk");
-        if let Some(val) = prop.value() {
-            val.parse::<i32>()
-                .expect("Failed to parse ro.build.version.sdk property")
-        } else {
-            panic!("Couldn't read ro.build.version.sdk system property");
+    /// Test frameworks and remote control tools typically inject events that
+    /// are attributed to a virtual (or unknown) device, so security-sensitive
+    /// flows may want to reject input that isn't
+    /// [`InputOrigin::is_genuine_touch`]. Pass the
+    /// [`device_id()`](input::MotionEvent::device_id) and
+    /// [`source()`](input::MotionEvent::source) of a [`input::MotionEvent`] or
+    /// [`input::KeyEvent`].
+    ///
+    /// This is based on
+    /// [`InputDevice.isVirtual()`](https://developer.android.com/reference/android/view/InputDevice#isVirtual())
+    /// and isn't a guarantee, since a privileged tool could also emulate a
+    /// physical device. See also [`input::MotionEvent::is_obscured()`] for
+    /// detecting tapjacking.
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn input_origin(&self, device_id: i32, source: input::Source) -> Result<InputOrigin> {
+        let source: u32 = source.into();
+        let touchscreen: u32 = input::Source::Touchscreen.into();
+        let inner = self.inner.read().unwrap();
+        let is_virtual_device = jni_utils::input_device_is_virtual(&inner.jvm, device_id)?;
+        Ok(InputOrigin {
+            source,
+            is_virtual_device,
+            is_genuine_touch: source & touchscreen == touchscreen && !is_virtual_device,
+        })
+    }
+
+    /// Lists the IDs of all attached input devices
+    ///
+    /// This is a wrapper for
+    /// [`InputDevice.getDeviceIds()`](https://developer.android.com/reference/android/view/InputDevice#getDeviceIds()).
+    /// Use [`AndroidApp::input_device_info()`] to query each device's
+    /// capabilities.
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn input_devices(&self) -> Result<Vec<i32>> {
+        let inner = self.inner.read().unwrap();
+        Ok(jni_utils::input_device_ids(&inner.jvm)?)
+    }
+
+    /// Queries the name, sources and axis ranges of the input device with the
+    /// given ID
+    ///
+    /// The ID can come from [`AndroidApp::input_devices()`] or from the
+    /// `device_id()` of an [`input::MotionEvent`] or [`input::KeyEvent`]. For
+    /// example, [`input::InputDeviceInfo::has_dual_analog_sticks()`] can be
+    /// used to decide which on-screen controller hints to show.
+    ///
+    /// This is based on
+    /// [`InputDevice.getDevice()`](https://developer.android.com/reference/android/view/InputDevice#getDevice(int))
+    /// and returns `None` if there's no device with the given ID (such as
+    /// after it has been disconnected).
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn input_device_info(&self, device_id: i32) -> Result<Option<input::InputDeviceInfo>> {
+        let inner = self.inner.read().unwrap();
+        Ok(jni_utils::input_device_info(&inner.jvm, device_id)?)
+    }
+
+    /// Registers a callback that will be called if the Java main thread has been
+    /// blocked, waiting for the `android_main` thread, for longer than `threshold`
+    ///
+    /// Android will show an "Application Not Responding" (ANR) dialog if the
+    /// Java main thread is blocked for too long (typically around five seconds)
+    /// and this callback gives applications an opportunity to log diagnostics
+    /// or dump state before that happens.
+    ///
+    /// The callback is called (at most once per blocked operation) from the Java
+    /// main thread, not the `android_main` thread, so it must not block waiting
+    /// on the `android_main` thread itself.
+    ///
+    /// By default no callback is registered.
+    ///
+    /// _Note: this is currently only supported by the `native-activity` backend
+    /// and is a no-op with the `game-activity` backend._
+    pub fn set_anr_risk_callback<F>(&self, threshold: Duration, callback: F)
+    where
+        F: Fn(BlockedOperation) + Send + Sync + 'static,
+    {
+        self.inner
+            .read()
+            .unwrap()
+            .set_anr_risk_callback(Some((threshold, Arc::new(callback))));
+    }
+
+    /// Removes any callback previously registered via [`AndroidApp::set_anr_risk_callback()`]
+    pub fn clear_anr_risk_callback(&self) {
+        self.inner.read().unwrap().set_anr_risk_callback(None);
+    }
+
+    /// Registers a handler for commands that are read from the main command
+    /// pipe that aren't understood by this crate
+    ///
+    /// This supports layered architectures where a higher-level crate writes
+    /// its own commands to the same pipe that's used to deliver lifecycle
+    /// events from the Java main thread. The handler is called from within
+    /// [`AndroidApp::poll_events()`] on the `android_main` thread, with the
+    /// command byte.
+    ///
+    /// Only command bytes from [`AndroidApp::CUSTOM_CMD_MIN`] up to `127` are
+    /// passed to the handler. Unknown commands below that range are reserved
+    /// for this crate and are logged and dropped, as are any commands read
+    /// while no handler is registered.
+    pub fn set_custom_cmd_handler<F>(&self, handler: F)
+    where
+        F: Fn(u8) + Send + Sync + 'static,
+    {
+        self.inner
+            .read()
+            .unwrap()
+            .set_custom_cmd_handler(Some(Arc::new(handler)));
+    }
+
+    /// Removes any handler previously registered via [`AndroidApp::set_custom_cmd_handler()`]
+    pub fn clear_custom_cmd_handler(&self) {
+        self.inner.read().unwrap().set_custom_cmd_handler(None);
+    }
+
+    /// Temporarily stops forwarding stdout and stderr to logcat
+    ///
+    /// By default (with the `native-stdout-stderr-logging` feature), this crate
+    /// redirects the stdout and stderr file descriptors to a pipe that's
+    /// forwarded to logcat. This restores the original file
+    /// descriptors, such as for writing raw bytes to the real stdout/stderr, or
+    /// while a crash handler writes to stderr.
+    ///
+    /// This affects the whole process and any pending output is flushed first.
+    /// Call [`AndroidApp::resume_stdio_capture()`] to resume forwarding.
+    ///
+    /// Returns `false` if stdout and stderr weren't being forwarded (e.g. if
+    /// forwarding is already paused, or the `native-stdout-stderr-logging`
+    /// feature is disabled).
+    pub fn pause_stdio_capture(&self) -> bool {
+        util::pause_stdio_capture()
+    }
+
+    /// Sets the logcat tag for stdout and stderr output that's forwarded to logcat
+    ///
+    /// The default tag is `RustStdoutStderr`, unless overridden by the
+    /// `ANDROID_ACTIVITY_LOG_TAG` environment variable when the Activity is
+    /// created. This affects all subsequently forwarded output.
+    ///
+    /// Returns `false`, leaving the tag unchanged, if `tag` is empty or contains
+    /// a NUL byte.
+    pub fn set_stdio_log_tag(&self, tag: &str) -> bool {
+        util::set_stdio_log_tag(tag)
+    }
+
+    /// Resumes forwarding stdout and stderr to logcat after
+    /// [`AndroidApp::pause_stdio_capture()`]
+    ///
+    /// Returns `false` if forwarding wasn't paused.
+    pub fn resume_stdio_capture(&self) -> bool {
+        util::resume_stdio_capture()
+    }
+
+    /// Stores an object that should outlive the current Activity, for handing
+    /// over to the next `android_main` after a configuration change
+    ///
+    /// This is a native equivalent of
+    /// [`Activity.onRetainNonConfigurationInstance()`](https://developer.android.com/reference/android/app/Activity#onRetainNonConfigurationInstance()),
+    /// for keeping objects that are expensive to recreate (such as GPU
+    /// resources or caches) across an Activity being recreated, such as after a
+    /// rotation, without serializing them. Unlike the state saved via
+    /// [`MainEvent::SaveState`], this is only held in memory and _does not_
+    /// survive the process being killed.
+    ///
+    /// The instance is stored in a single, process-global slot that's not tied
+    /// to any particular Activity, and any previously stored instance is
+    /// returned. The object will stay alive until it is taken via
+    /// [`AndroidApp::take_last_non_config_instance()`] or replaced.
+    ///
+    /// Note: a new `android_main` runs on a new thread, so the object must be
+    /// [`Send`].
+    pub fn set_non_config_instance<T: Any + Send>(
+        &self,
+        instance: T,
+    ) -> Option<Box<dyn Any + Send>> {
+        non_config::set(Box::new(instance))
+    }
+
+    /// Takes the object stored via [`AndroidApp::set_non_config_instance()`],
+    /// if any, leaving the slot empty
+    ///
+    /// This is a native equivalent of
+    /// [`Activity.getLastNonConfigurationInstance()`](https://developer.android.com/reference/android/app/Activity#getLastNonConfigurationInstance())
+    /// and would typically be called near the start of `android_main`. The
+    /// returned object can be recovered with [`Box::downcast()`].
+    ///
+    /// Since the slot isn't cleared when an Activity is finished, applications
+    /// shouldn't assume that `Some` implies a configuration change.
+    pub fn take_last_non_config_instance(&self) -> Option<Box<dyn Any + Send>> {
+        non_config::take()
+    }
+
+    /// Returns a snapshot of statistics about the application's event loop,
+    /// for profiling
+    ///
+    /// These counters are maintained cheaply by [`AndroidApp::poll_events()`]
+    /// and [`input::InputIterator`] and are cumulative until reset via
+    /// [`AndroidApp::reset_stats()`].
+    pub fn stats(&self) -> LoopStats {
+        self.inner.read().unwrap().stats.snapshot()
+    }
+
+    /// Resets all the counters reported by [`AndroidApp::stats()`] to zero
+    pub fn reset_stats(&self) {
+        self.inner.read().unwrap().stats.reset()
+    }
+
+    /// Returns which `Activity` backend is in use
+    ///
+    /// This can be used by libraries to decide at runtime whether backend
+    /// specific features are available (for example, only the `GameActivity`
+    /// backend currently supports [`AndroidApp::text_input_state()`]).
+    pub fn backend(&self) -> Backend {
+        self.inner.read().unwrap().backend()
+    }
+
+    /// Queries the subtype (such as the language) of the currently selected input method
+    ///
+    /// This can be used to show the active keyboard language, for example.
+    ///
+    /// Returns `None` if there is no current input method subtype.
+    ///
+    /// There is no event to notify applications when the user switches input method
+    /// subtype, so applications should re-query this when they need an up-to-date
+    /// value (such as whenever the soft input is shown, or the app regains focus).
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn input_method_subtype(&self) -> Result<Option<InputMethodSubtype>> {
+        let inner = self.inner.read().unwrap();
+        Ok(jni_utils::input_method_subtype(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+        )?)
+    }
+
+    /// Customizes how this application's task is presented in the system's
+    /// recent apps overview
+    ///
+    /// This is a wrapper for
+    /// [`Activity.setTaskDescription`](https://developer.android.com/reference/android/app/Activity#setTaskDescription(android.app.ActivityManager.TaskDescription)).
+    ///
+    /// - `label` overrides the application's label (`None` for the default)
+    /// - `color` is an opaque, ARGB primary color for the task (`None` for the default)
+    /// - `icon` is an encoded image (such as a PNG) for the task's icon (`None` for the default)
+    ///
+    /// The task description is updated asynchronously on the Java main thread
+    /// and any error from doing so (such as a non-opaque `color` or an icon
+    /// that can't be decoded) will be logged.
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn set_task_description(
+        &self,
+        label: Option<&str>,
+        color: Option<u32>,
+        icon: Option<&[u8]>,
+    ) -> Result<()> {
+        let inner = self.inner.read().unwrap();
+        Ok(jni_utils::set_task_description(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+            label.map(String::from),
+            color,
+            icon.map(Vec::from),
+        )?)
+    }
+
+    /// Queries the current bounds of the application's window, and the maximum bounds
+    /// it could occupy, in addition to whether the Activity is in multi-window mode
+    ///
+    /// This is based on
+    /// [`WindowManager.getCurrentWindowMetrics()`](https://developer.android.com/reference/android/view/WindowManager#getCurrentWindowMetrics())
+    /// and [`Activity.isInMultiWindowMode()`](https://developer.android.com/reference/android/app/Activity#isInMultiWindowMode())
+    /// and these bounds are more precise than the size of the [`NativeWindow`]
+    /// while, for example, the user drags the split-screen divider.
+    ///
+    /// There is no event to notify of changes to the metrics, so applications
+    /// should re-query them after [`MainEvent::WindowResized`] or
+    /// [`MainEvent::ConfigChanged`] events.
+    ///
+    /// Android doesn't report whether the window is the primary or secondary
+    /// side of a split-screen, so this doesn't include a split role, but the
+    /// `bounds` can be compared with the `maximum_bounds` to find where the
+    /// window is.
+    ///
+    /// Returns `None` on Android versions older than 11 (API level 30).
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn window_metrics(&self) -> Result<Option<WindowMetrics>> {
+        if AndroidApp::sdk_version() < 30 {
+            return Ok(None);
+        }
+        let inner = self.inner.read().unwrap();
+        Ok(Some(jni_utils::window_metrics(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+        )?))
+    }
+
+    /// Queries the insets of the application's window, for the system bars,
+    /// display cutout and on-screen keyboard (IME)
+    ///
+    /// This is based on
+    /// [`View.getRootWindowInsets()`](https://developer.android.com/reference/android/view/View#getRootWindowInsets())
+    /// for the window's decor view, which is more detailed than
+    /// [`AndroidApp::content_rect()`]. Content that's drawn edge-to-edge (see
+    /// [`AndroidApp::set_decor_fits_system_windows()`]) should be kept clear of
+    /// these insets.
+    ///
+    /// Applications should re-query the insets after [`MainEvent::InsetsChanged`]
+    /// events.
+    ///
+    /// All insets are empty on Android versions older than 6.0 (API level 23),
+    /// or before the window has been attached.
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn window_insets(&self) -> Result<WindowInsets> {
+        if AndroidApp::sdk_version() < 23 {
+            return Ok(WindowInsets::default());
+        }
+        let inner = self.inner.read().unwrap();
+        Ok(jni_utils::window_insets(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+            AndroidApp::sdk_version(),
+        )?)
+    }
+
+    /// Queries the display cutout (such as a notch or punch-hole camera) that
+    /// overlaps the application's window, if any
+    ///
+    /// This is based on
+    /// [`WindowInsets.getDisplayCutout()`](https://developer.android.com/reference/android/view/WindowInsets#getDisplayCutout())
+    /// and, unlike [`WindowInsets::display_cutout`], includes the bounding
+    /// rectangles of each cutout so applications can avoid drawing important
+    /// content behind them while still using the rest of that edge.
+    ///
+    /// The cutout moves when the device is rotated, so applications should
+    /// re-query it after [`MainEvent::ConfigChanged`] and
+    /// [`MainEvent::InsetsChanged`] events.
+    ///
+    /// Returns `None` if the window doesn't overlap a cutout (which depends on
+    /// the window's `layoutInDisplayCutoutMode`), before the window has been
+    /// attached, or on Android versions older than 9 (API level 28).
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn display_cutout(&self) -> Result<Option<DisplayCutout>> {
+        if AndroidApp::sdk_version() < 28 {
+            return Ok(None);
         }
+        let inner = self.inner.read().unwrap();
+        Ok(jni_utils::display_cutout(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+        )?)
+    }
+
+    /// Queries the refresh rate of the Activity's display, in Hz
+    ///
+    /// This is a wrapper for
+    /// [`Display.getRefreshRate()`](https://developer.android.com/reference/android/view/Display#getRefreshRate()).
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn display_refresh_rate(&self) -> Result<f32> {
+        let inner = self.inner.read().unwrap();
+        Ok(jni_utils::display_refresh_rate(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+        )?)
+    }
+
+    /// Queries the current mode of the Activity's display
+    ///
+    /// This is a wrapper for
+    /// [`Display.getMode()`](https://developer.android.com/reference/android/view/Display#getMode())
+    /// and changes are reported with [`MainEvent::DisplayModeChanged`].
+    ///
+    /// Returns `None` on Android versions older than 6.0 (API level 23).
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn display_mode(&self) -> Result<Option<DisplayMode>> {
+        if AndroidApp::sdk_version() < 23 {
+            return Ok(None);
+        }
+        let inner = self.inner.read().unwrap();
+        Ok(Some(jni_utils::display_mode(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+        )?))
+    }
+
+    /// Queries the modes supported by the Activity's display
+    ///
+    /// This is a wrapper for
+    /// [`Display.getSupportedModes()`](https://developer.android.com/reference/android/view/Display#getSupportedModes())
+    /// and can be used to find a high refresh rate mode (such as 90 or 120 Hz)
+    /// for [`AndroidApp::set_preferred_display_mode()`].
+    ///
+    /// Returns an empty list on Android versions older than 6.0 (API level 23).
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn supported_display_modes(&self) -> Result<Vec<DisplayMode>> {
+        if AndroidApp::sdk_version() < 23 {
+            return Ok(Vec::new());
+        }
+        let inner = self.inner.read().unwrap();
+        Ok(jni_utils::supported_display_modes(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+        )?)
+    }
+
+    /// Requests that the display switches to the given mode while the
+    /// Activity's window is visible
+    ///
+    /// `mode_id` should be the [`DisplayMode::id`] of one of the
+    /// [`AndroidApp::supported_display_modes()`], or `0` to clear the
+    /// preference. This sets
+    /// [`WindowManager.LayoutParams.preferredDisplayModeId`](https://developer.android.com/reference/android/view/WindowManager.LayoutParams#preferredDisplayModeId)
+    /// for the window, which is applied asynchronously on the Java main
+    /// thread, and the system may decide not to switch modes (such as in
+    /// battery saver mode). A successful switch is reported with a
+    /// [`MainEvent::DisplayModeChanged`] event.
+    ///
+    /// Returns `false` (and does nothing) on Android versions older than 6.0
+    /// (API level 23), where this isn't supported.
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn set_preferred_display_mode(&self, mode_id: i32) -> Result<bool> {
+        if AndroidApp::sdk_version() < 23 {
+            return Ok(false);
+        }
+        let inner = self.inner.read().unwrap();
+        jni_utils::set_preferred_display_mode(&inner.jvm, inner.activity_as_ptr() as _, mode_id)?;
+        Ok(true)
+    }
+
+    /// Queries whether the Activity's window is hardware accelerated
+    ///
+    /// This is a wrapper for
+    /// [`View.isHardwareAccelerated()`](https://developer.android.com/reference/android/view/View#isHardwareAccelerated())
+    /// on the window's decor view.
+    ///
+    /// On rare configurations (such as some emulators or remote displays) this
+    /// may be `false`, which can be a hint to fall back to software rendering.
+    ///
+    /// Note: this describes how Android renders the Activity's view hierarchy
+    /// and is not a guarantee that EGL or Vulkan are (or aren't) usable with
+    /// the [`NativeWindow`]. This will also return `false` until the window has
+    /// been attached, so it's best to query this after a
+    /// [`MainEvent::InitWindow`] event.
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn is_hardware_accelerated(&self) -> Result<bool> {
+        let inner = self.inner.read().unwrap();
+        Ok(jni_utils::is_hardware_accelerated(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+        )?)
+    }
+
+    /// Enumerates the device's cameras, along with the characteristics needed
+    /// to correctly rotate a camera preview (the lens facing and sensor orientation)
+    ///
+    /// This is based on the
+    /// [`CameraManager`](https://developer.android.com/reference/android/hardware/camera2/CameraManager)
+    /// API and doesn't require the `CAMERA` permission.
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn cameras(&self) -> Result<Vec<camera::CameraInfo>> {
+        let inner = self.inner.read().unwrap();
+        Ok(jni_utils::cameras(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+        )?)
+    }
+
+    /// Enumerates the shared storage volumes available to the application,
+    /// such as the primary internal storage, SD cards and USB drives
+    ///
+    /// This is a wrapper for
+    /// [`StorageManager.getStorageVolumes()`](https://developer.android.com/reference/android/os/storage/StorageManager#getStorageVolumes())
+    /// and includes volumes that aren't currently mounted, so check
+    /// [`StorageVolume::state`](storage::StorageVolume::state) before accessing a volume.
+    ///
+    /// Returns an empty list on Android versions older than 7.0 (API level 24).
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn storage_volumes(&self) -> Result<Vec<storage::StorageVolume>> {
+        if AndroidApp::sdk_version() < 24 {
+            return Ok(Vec::new());
+        }
+        let inner = self.inner.read().unwrap();
+        Ok(jni_utils::storage_volumes(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+        )?)
+    }
+
+    /// Queries the current HDR/SDR ratio of the Activity's display
+    ///
+    /// This is the ratio between the current brightness of HDR white and SDR
+    /// white, which HDR renderers can use to tone map their output to the
+    /// available headroom.
+    ///
+    /// This is a wrapper for
+    /// [`Display.getHdrSdrRatio()`](https://developer.android.com/reference/android/view/Display#getHdrSdrRatio())
+    /// and there is no event to notify of changes, so applications should
+    /// re-query this periodically (such as once per frame) while rendering HDR content.
+    ///
+    /// Returns `None` if the ratio isn't available for the display, or on Android
+    /// versions older than 14 (API level 34).
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn hdr_sdr_ratio(&self) -> Result<Option<f32>> {
+        if AndroidApp::sdk_version() < 34 {
+            return Ok(None);
+        }
+        let inner = self.inner.read().unwrap();
+        Ok(jni_utils::hdr_sdr_ratio(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+        )?)
+    }
+
+    /// Requests the amount of HDR headroom (the HDR/SDR ratio) the application
+    /// would like for rendering HDR content
+    ///
+    /// This is a wrapper for
+    /// [`Window.setDesiredHdrHeadroom()`](https://developer.android.com/reference/android/view/Window#setDesiredHdrHeadroom(float))
+    /// and a value of `0.0` resets to the default. The request is applied
+    /// asynchronously on the Java main thread.
+    ///
+    /// Returns `false` (and does nothing) on Android versions older than 15
+    /// (API level 35), where this isn't supported.
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn set_desired_hdr_headroom(&self, headroom: f32) -> Result<bool> {
+        if AndroidApp::sdk_version() < 35 {
+            return Ok(false);
+        }
+        let inner = self.inner.read().unwrap();
+        jni_utils::set_desired_hdr_headroom(&inner.jvm, inner.activity_as_ptr() as _, headroom)?;
+        Ok(true)
+    }
+
+    /// Controls whether the window's content is laid out to fit within the
+    /// system bars, or drawn edge-to-edge behind them
+    ///
+    /// This is equivalent to AndroidX's
+    /// [`WindowCompat.setDecorFitsSystemWindows()`](https://developer.android.com/reference/androidx/core/view/WindowCompat#setDecorFitsSystemWindows(android.view.Window,boolean)),
+    /// calling
+    /// [`Window.setDecorFitsSystemWindows()`](https://developer.android.com/reference/android/view/Window#setDecorFitsSystemWindows(boolean))
+    /// on Android 11 (API level 30) and newer and falling back to the layout
+    /// flags of `View.setSystemUiVisibility()` on older versions. This is the
+    /// modern replacement for making the window fullscreen via
+    /// [`WindowManagerFlags::FULLSCREEN`] and the change is applied
+    /// asynchronously on the Java main thread.
+    ///
+    /// After passing `false`, the window extends behind the status and
+    /// navigation bars (and any display cutout the window's layout mode
+    /// allows) so the application becomes responsible for keeping important
+    /// content and input targets clear of them. Once the new layout takes
+    /// effect the application will see [`MainEvent::ContentRectChanged`] and
+    /// [`MainEvent::InsetsChanged`] events, after which
+    /// [`AndroidApp::content_rect()`] or [`AndroidApp::window_insets()`] can be
+    /// used to find the area that's not obscured by system UI.
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn set_decor_fits_system_windows(&self, fits: bool) -> Result<()> {
+        let inner = self.inner.read().unwrap();
+        Ok(jni_utils::set_decor_fits_system_windows(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+            AndroidApp::sdk_version(),
+            fits,
+        )?)
+    }
+
+    /// Performs standard, platform-consistent haptic feedback, such as for a
+    /// keyboard tap or long press
+    ///
+    /// This is a wrapper for
+    /// [`View.performHapticFeedback()`](https://developer.android.com/reference/android/view/View#performHapticFeedback(int))
+    /// on the Activity's decor view, which runs on the Java main thread. This
+    /// respects the user's haptic feedback settings and doesn't require the
+    /// `VIBRATE` permission.
+    ///
+    /// Returns whether the haptic feedback was performed. This waits briefly for
+    /// the Java main thread and returns `false` if it doesn't respond in time.
+    ///
+    /// This must not be called from the Java main thread.
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn perform_haptic_feedback(&self, feedback: HapticFeedbackConstant) -> Result<bool> {
+        // Don't hold the lock while waiting for the Java main thread
+        let (jvm, activity) = {
+            let inner = self.inner.read().unwrap();
+            (inner.jvm.clone(), inner.activity_as_ptr())
+        };
+        let feedback: u32 = feedback.into();
+        let performed = jni_utils::perform_haptic_feedback(&jvm, activity as _, feedback as i32)?;
+        Ok(performed.unwrap_or(false))
+    }
+
+    /// Requests pointer capture, so that mouse (and touchpad) input is delivered
+    /// to the application as relative motion, without a visible cursor
+    ///
+    /// This is a wrapper for
+    /// [`View.requestPointerCapture()`](https://developer.android.com/reference/android/view/View#requestPointerCapture())
+    /// on the Activity's decor view, which runs asynchronously on the Java main
+    /// thread. This is typically used for mouse-look in first-person games.
+    ///
+    /// While the pointer is captured, mouse events have a
+    /// [`Source::MouseRelative`](input::Source::MouseRelative) source and
+    /// report movement deltas instead of absolute positions (see
+    /// [`MotionEvent::is_captured()`](input::MotionEvent::is_captured) and
+    /// [`MotionEvent::relative_axis_value()`](input::MotionEvent::relative_axis_value)).
+    ///
+    /// The window must have focus for the request to be granted, and the
+    /// system releases the capture whenever the window loses focus (which is
+    /// reported with a [`MainEvent::PointerCaptureChanged`] event), so
+    /// applications should request it again after
+    /// [`MainEvent::GainedFocus`]. Pointer capture is only supported on
+    /// Android 8.0 (API level 26) and newer and this does nothing on older
+    /// versions.
+    ///
+    /// _Note: with GameActivity, captured events are only delivered if the
+    /// Java `GameActivity` forwards `View.onCapturedPointerEvent()` events._
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn request_pointer_capture(&self) -> Result<()> {
+        self.set_pointer_capture(true)
+    }
+
+    /// Releases pointer capture, previously requested with
+    /// [`AndroidApp::request_pointer_capture()`]
+    ///
+    /// This is a wrapper for
+    /// [`View.releasePointerCapture()`](https://developer.android.com/reference/android/view/View#releasePointerCapture())
+    /// on the Activity's decor view, which runs asynchronously on the Java main
+    /// thread.
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn release_pointer_capture(&self) -> Result<()> {
+        self.set_pointer_capture(false)
+    }
+
+    fn set_pointer_capture(&self, capture: bool) -> Result<()> {
+        if AndroidApp::sdk_version() < 26 {
+            return Ok(());
+        }
+        let inner = self.inner.read().unwrap();
+        jni_utils::set_pointer_capture(&inner.jvm, inner.activity_as_ptr() as _, capture)?;
+        inner
+            .pointer_capture_requested
+            .store(capture, std::sync::atomic::Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Queries whether the Activity currently has pointer capture
+    ///
+    /// This is a wrapper for
+    /// [`View.hasPointerCapture()`](https://developer.android.com/reference/android/view/View#hasPointerCapture())
+    /// on the Activity's decor view. This waits briefly for the Java main thread
+    /// and returns `false` if it doesn't respond in time, or on Android versions
+    /// older than 8.0 (API level 26).
+    ///
+    /// This must not be called from the Java main thread.
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn has_pointer_capture(&self) -> Result<bool> {
+        if AndroidApp::sdk_version() < 26 {
+            return Ok(false);
+        }
+        // Don't hold the lock while waiting for the Java main thread
+        let (jvm, activity) = {
+            let inner = self.inner.read().unwrap();
+            (inner.jvm.clone(), inner.activity_as_ptr())
+        };
+        let captured = jni_utils::has_pointer_capture(&jvm, activity as _)?;
+        Ok(captured.unwrap_or(false))
+    }
+
+    /// Queries whether the device is in an interactive state, which normally
+    /// means the screen is on
+    ///
+    /// This is a wrapper for
+    /// [`PowerManager.isInteractive()`](https://developer.android.com/reference/android/os/PowerManager#isInteractive()).
+    /// See [`MainEvent::ScreenStateChanged`] for being notified of changes.
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn is_interactive(&self) -> Result<bool> {
+        let inner = self.inner.read().unwrap();
+        Ok(jni_utils::is_interactive(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+        )?)
+    }
+
+    /// Queries the [`LaunchContext`] that `android_main` is running in
+    ///
+    /// `android_main` is always run for an Activity, but that doesn't
+    /// necessarily mean that a UI is shown to the user: the process may be
+    /// running in the background, such as for a service that happens to share
+    /// the process, or the Activity may already be finishing. Libraries that
+    /// may be loaded in different contexts can use this to avoid assuming that
+    /// a UI exists.
+    ///
+    /// This is based on
+    /// [`Activity.isFinishing()`](https://developer.android.com/reference/android/app/Activity#isFinishing()),
+    /// [`Activity.isDestroyed()`](https://developer.android.com/reference/android/app/Activity#isDestroyed())
+    /// and the process importance from
+    /// [`ActivityManager.getMyMemoryState()`](https://developer.android.com/reference/android/app/ActivityManager#getMyMemoryState(android.app.ActivityManager.RunningAppProcessInfo)),
+    /// and can change over time.
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn launch_context(&self) -> Result<LaunchContext> {
+        let inner = self.inner.read().unwrap();
+        Ok(jni_utils::launch_context(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+        )?)
+    }
+
+    /// Queries details of the `Intent` that started the Activity, such as
+    /// whether it was relaunched from the recent apps overview
+    ///
+    /// This is based on
+    /// [`Activity.getIntent()`](https://developer.android.com/reference/android/app/Activity#getIntent())
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn launch_intent(&self) -> Result<LaunchIntent> {
+        let inner = self.inner.read().unwrap();
+        Ok(jni_utils::launch_intent(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+        )?)
+    }
+
+    /// Queries a [`DeviceInfo`] snapshot of commonly-needed, immutable facts
+    /// about the device and application
+    ///
+    /// This gathers everything that requires JNI within a single JNI session,
+    /// which is cheaper than querying each fact separately, such as during
+    /// application startup. The result never changes, so it can be cached.
+    ///
+    /// Note: configuration-dependent state, such as the screen density, isn't
+    /// included and should be queried via [`AndroidApp::config()`].
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn device_info(&self) -> Result<DeviceInfo> {
+        let inner = self.inner.read().unwrap();
+        let mut info = jni_utils::device_info(&inner.jvm, inner.activity_as_ptr() as _)?;
+        info.sdk_version = AndroidApp::sdk_version();
+        info.internal_data_path = inner.internal_data_path();
+        info.external_data_path = inner.external_data_path();
+        info.obb_path = inner.obb_path();
+        Ok(info)
+    }
+
+    /// Queries the ABIs supported by the device, in order of preference
+    ///
+    /// This is based on
+    /// [`Build.SUPPORTED_ABIS`](https://developer.android.com/reference/android/os/Build#SUPPORTED_ABIS),
+    /// such as `["arm64-v8a", "armeabi-v7a", "armeabi"]`, and can be used to
+    /// pick the right build of a native plugin to load. The result never
+    /// changes and is also included in [`AndroidApp::device_info()`].
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn supported_abis(&self) -> Result<Vec<String>> {
+        let inner = self.inner.read().unwrap();
+        Ok(jni_utils::supported_abis(&inner.jvm)?)
+    }
+
+    /// Queries the path to the directory containing the application's
+    /// installed native libraries
+    ///
+    /// This is based on
+    /// [`ApplicationInfo.nativeLibraryDir`](https://developer.android.com/reference/android/content/pm/ApplicationInfo#nativeLibraryDir)
+    /// and is where native plugins that are packaged with the application can
+    /// be loaded from, such as via `dlopen()`. The result never changes and is
+    /// also included in [`AndroidApp::device_info()`].
+    ///
+    /// Note: if the application's libraries are loaded directly from the APK
+    /// (with `extractNativeLibs="false"`) then this directory may be empty, and
+    /// libraries should be loaded by their file name alone.
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn native_library_dir(&self) -> Result<Option<std::path::PathBuf>> {
+        let inner = self.inner.read().unwrap();
+        Ok(jni_utils::activity_native_library_dir(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+        )?)
+    }
+
+    /// Queries the user's screen timeout setting
+    ///
+    /// This reads [`Settings.System.SCREEN_OFF_TIMEOUT`](https://developer.android.com/reference/android/provider/Settings.System#SCREEN_OFF_TIMEOUT),
+    /// which is how long the device can be idle before the screen turns off.
+    ///
+    /// Returns `None` if the setting isn't available.
+    ///
+    /// Changing this setting requires special permissions, so applications that
+    /// need to keep the screen on should use [`AndroidApp::set_keep_screen_on()`]
+    /// instead.
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn screen_off_timeout(&self) -> Result<Option<Duration>> {
+        let inner = self.inner.read().unwrap();
+        let timeout = jni_utils::settings_int(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+            "android/provider/Settings$System",
+            "screen_off_timeout",
+        )?;
+        Ok(timeout
+            .filter(|millis| *millis >= 0)
+            .map(|millis| Duration::from_millis(millis as u64)))
+    }
+
+    /// Queries which power sources keep the device awake while plugged in
+    ///
+    /// This reads the developer option [`Settings.Global.STAY_ON_WHILE_PLUGGED_IN`](https://developer.android.com/reference/android/provider/Settings.Global#STAY_ON_WHILE_PLUGGED_IN),
+    /// and returns empty flags if the setting isn't enabled or isn't available.
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn stay_on_while_plugged_in(&self) -> Result<StayOnWhilePluggedIn> {
+        let inner = self.inner.read().unwrap();
+        let flags = jni_utils::settings_int(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+            "android/provider/Settings$Global",
+            "stay_on_while_plugged_in",
+        )?;
+        Ok(StayOnWhilePluggedIn::from_bits_truncate(
+            flags.unwrap_or(0) as u32
+        ))
+    }
+
+    /// Queries whether the user has enabled auto-rotate, or has locked the
+    /// screen's rotation
+    ///
+    /// This reads [`Settings.System.ACCELEROMETER_ROTATION`](https://developer.android.com/reference/android/provider/Settings.System#ACCELEROMETER_ROTATION).
+    /// Applications that rotate their content based on sensors, or that
+    /// request orientation changes via
+    /// [`Activity.setRequestedOrientation()`](https://developer.android.com/reference/android/app/Activity#setRequestedOrientation(int)),
+    /// should defer to the user's preference when this returns `false`.
+    ///
+    /// Returns `true` if the setting isn't available.
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn auto_rotate_enabled(&self) -> Result<bool> {
+        let inner = self.inner.read().unwrap();
+        let enabled = jni_utils::settings_int(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+            "android/provider/Settings$System",
+            "accelerometer_rotation",
+        )?;
+        Ok(enabled != Some(0))
+    }
+
+    /// Queries the current volume index of the given audio stream
+    ///
+    /// The volume ranges from `0` to [`AndroidApp::max_volume()`]. See
+    /// [`AudioManager.getStreamVolume()`](https://developer.android.com/reference/android/media/AudioManager#getStreamVolume(int))
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn stream_volume(&self, stream: StreamType) -> Result<i32> {
+        let inner = self.inner.read().unwrap();
+        let volumes =
+            jni_utils::stream_volumes(&inner.jvm, inner.activity_as_ptr() as _, &[stream.into()])?;
+        Ok(volumes[0])
+    }
+
+    /// Queries the maximum volume index of the given audio stream
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn max_volume(&self, stream: StreamType) -> Result<i32> {
+        let inner = self.inner.read().unwrap();
+        Ok(jni_utils::stream_max_volume(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+            stream.into(),
+        )?)
+    }
+
+    /// Sets the volume index of the given audio stream
+    ///
+    /// If `show_ui` is `true` then the system's volume panel is shown.
+    ///
+    /// See [`AudioManager.setStreamVolume()`](https://developer.android.com/reference/android/media/AudioManager#setStreamVolume(int,%20int,%20int))
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught. This includes a `SecurityException` if changing the volume
+    /// would toggle Do Not Disturb without the required permission.
+    pub fn set_stream_volume(&self, stream: StreamType, volume: i32, show_ui: bool) -> Result<()> {
+        const FLAG_SHOW_UI: i32 = 1;
+        let inner = self.inner.read().unwrap();
+        jni_utils::set_stream_volume(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+            stream.into(),
+            volume,
+            if show_ui { FLAG_SHOW_UI } else { 0 },
+        )?;
+        Ok(())
+    }
+
+    /// Queries the [`UserRestrictions`] that apply to the current user, such as
+    /// whether the application is running in a managed (work) profile
+    ///
+    /// This is based on the
+    /// [`UserManager`](https://developer.android.com/reference/android/os/UserManager)
+    /// and [`DevicePolicyManager`](https://developer.android.com/reference/android/app/admin/DevicePolicyManager)
+    /// APIs, and can be used to adjust features (such as disabling data export)
+    /// in managed environments.
+    ///
+    /// # Errors
+    ///
+    /// Since this API needs to use JNI internally to call into the Android JVM it may return
+    /// a [`error::AppError::JavaError`] in case there is a spurious JNI error or an exception
+    /// is caught.
+    pub fn user_restrictions(&self) -> Result<UserRestrictions> {
+        let inner = self.inner.read().unwrap();
+        Ok(jni_utils::user_restrictions(
+            &inner.jvm,
+            inner.activity_as_ptr() as _,
+        )?)
+    }
+
+    /// The SDK version of the framework, as passed to the native Activity
+    ///
+    /// With the `native-activity` backend this reads the `sdkVersion` field of
+    /// the `ANativeActivity`.
+    ///
+    /// `GameActivity` doesn't populate its equivalent field, so with the
+    /// `game-activity` backend this is the same as [`AndroidApp::sdk_version()`].
+    ///
+    /// Both report the same value, and both are cheap to call.
+    pub fn native_activity_sdk_version(&self) -> i32 {
+        self.inner.read().unwrap().native_activity_sdk_version()
+    }
+
+    /// The user-visible SDK version of the framework
+    ///
+    /// Also referred to as [`Build.VERSION_CODES`](https://developer.android.com/reference/android/os/Build.VERSION_CODES)
+    ///
+    /// The `ro.build.version.sdk` system property is only read the first time
+    /// this is called, so it's cheap enough to call for each input event.
+    pub fn sdk_version() -> i32 {
+        use std::sync::atomic::{AtomicI32, Ordering};
+
+        // `0` until the property has been read (`OnceLock` would need Rust 1.70)
+        static SDK_VERSION: AtomicI32 = AtomicI32::new(0);
+
+        let cached = SDK_VERSION.load(Ordering::Relaxed);
+        if cached != 0 {
+            return cached;
+        }
+        let mut prop = android_properties::getprop("ro.build.version.sdk");
+        let sdk_version = if let Some(val) = prop.value() {
+            val.parse::<i32>()
+                .expect("Failed to parse ro.build.version.sdk property")
+        } else {
+            panic!("Couldn't read ro.build.version.sdk system property");
+        };
+        SDK_VERSION.store(sdk_version, Ordering::Relaxed);
+        sdk_version
+    }
+
+    /// Time since boot, not counting time spent in deep sleep, from `CLOCK_MONOTONIC`
+    ///
+    /// This is the time base of
+    /// [`SystemClock.uptimeMillis()`](https://developer.android.com/reference/android/os/SystemClock#uptimeMillis())
+    /// and `java.lang.System.nanoTime()` (but with nanosecond precision), and
+    /// of input event timestamps, such as [`input::MotionEvent::event_time()`]
+    /// and [`input::MotionEvent::event_uptime()`].
+    pub fn uptime() -> Duration {
+        util::clock_gettime(libc::CLOCK_MONOTONIC)
+    }
+
+    /// Time since boot, including time spent in deep sleep
+    ///
+    /// This is equivalent to
+    /// [`SystemClock.elapsedRealtime()`](https://developer.android.com/reference/android/os/SystemClock#elapsedRealtime())
+    /// (but with nanosecond precision).
+    pub fn elapsed_realtime() -> Duration {
+        util::clock_gettime(libc::CLOCK_BOOTTIME)
+    }
+
+    /// Calculates how long ago an input event occurred, given its `event_time`
+    ///
+    /// `event_time` is a timestamp, in nanoseconds, in the [`AndroidApp::uptime()`] time base,
+    /// such as from [`input::MotionEvent::event_time()`] or [`input::KeyEvent::event_time()`].
+    ///
+    /// This can be used to measure input latency.
+    pub fn input_event_age(event_time: i64) -> Duration {
+        Self::uptime().saturating_sub(util::event_time_to_duration(event_time))
+    }
+
+    /// Converts an input event's `event_time` into an [`Instant`](std::time::Instant)
+    ///
+    /// `event_time` is a timestamp, in nanoseconds, in the [`AndroidApp::uptime()`] time base,
+    /// such as from [`input::MotionEvent::event_time()`] or [`input::KeyEvent::event_time()`].
+    ///
+    /// The returned `Instant` can be compared with `Instant::now()`, such as
+    /// when measuring the latency between an input event and presenting a
+    /// frame that responds to it.
+    pub fn event_time_to_instant(event_time: i64) -> std::time::Instant {
+        // `Instant` doesn't expose its `CLOCK_MONOTONIC` value, so calculate
+        // relative to "now"
+        let now = std::time::Instant::now();
+        let age = Self::input_event_age(event_time);
+        now.checked_sub(age).unwrap_or(now)
+    }
+
+    /// Converts an input event's `event_time` into the time since this
+    /// `AndroidApp` was created (i.e. since `android_main` was started)
+    ///
+    /// `event_time` is a timestamp, in nanoseconds, in the [`AndroidApp::uptime()`] time base,
+    /// such as from [`input::MotionEvent::event_time()`] or [`input::KeyEvent::event_time()`].
+    ///
+    /// Events that occurred before the `AndroidApp` was created map to [`Duration::ZERO`].
+    pub fn event_time_since_start(&self, event_time: i64) -> Duration {
+        util::event_time_to_duration(event_time)
+            .saturating_sub(self.inner.read().unwrap().start_uptime)
     }
 
     /// Path to this application's internal data directory
@@ -0,0 +1,634 @@
+//! Android reports many kinds of state changes via Java listeners and
+//! `BroadcastReceiver`s, but this crate doesn't build any Java code into
+//! applications.
+//!
+//! Instead, we load a minimal `NativeListener` class at runtime from an
+//! embedded dex file (see `java/NativeListener.java`), whose native methods
+//! notify the `android_main` thread's looper, via [`notify::notify_current()`].
+//! The notified backend then checks the corresponding state and synthesizes any
+//! events.
+//!
+//! Listeners are registered with the application context (so they can't leak
+//! the Activity) on the Java main thread, and unregistered when the Activity is
+//! destroyed.
+
+use std::sync::{Arc, Mutex};
+
+use jni::{
+    objects::{GlobalRef, JClass, JObject, JObjectArray, JString, JValue},
+    sys::jobject,
+    JNIEnv, NativeMethod,
+};
+
+use crate::{
+    jni_utils::{self, CloneJavaVM},
+    notify::{self, Notifications},
+    ui_thread,
+    util::abort_on_panic,
+    AndroidApp,
+};
+
+const NATIVE_LISTENER_DEX: &[u8] = include_bytes!("../java/NativeListener.dex");
+const NATIVE_LISTENER_CLASS: &str = "com.github.rust_mobile.android_activity.NativeListener";
+
+/// `Context.RECEIVER_NOT_EXPORTED`, which still lets the receiver get
+/// broadcasts from the system
+const RECEIVER_NOT_EXPORTED: i32 = 0x4;
+
+/// The `NativeListener` class, which is loaded once per process
+static NATIVE_LISTENER: Mutex<Option<GlobalRef>> = Mutex::new(None);
+
+type RegisterFn = for<'local> fn(
+    &mut JNIEnv<'local>,
+    &JObject<'_>,
+    &JClass<'_>,
+) -> jni::errors::Result<JObject<'local>>;
+type UnregisterFn = fn(&mut JNIEnv<'_>, &JObject<'_>, &JObject<'_>) -> jni::errors::Result<()>;
+
+/// A kind of Java listener that we register for each Activity
+struct ListenerKind {
+    name: &'static str,
+    /// Registers a new listener with the given application context, using
+    /// the given `NativeListener` class, and returns it
+    register: RegisterFn,
+    /// Unregisters a listener that was returned by `register`
+    unregister: UnregisterFn,
+}
+
+const LISTENER_KINDS: &[ListenerKind] = &[
+    ListenerKind {
+        name: "screen state receiver",
+        register: register_screen_state_receiver,
+        unregister: unregister_receiver,
+    },
+    ListenerKind {
+        name: "volume receiver",
+        register: register_volume_receiver,
+        unregister: unregister_receiver,
+    },
+    ListenerKind {
+        name: "primary clip listener",
+        register: register_clip_listener,
+        unregister: unregister_clip_listener,
+    },
+    ListenerKind {
+        name: "input device listener",
+        register: register_input_device_listener,
+        unregister: unregister_input_device_listener,
+    },
+    ListenerKind {
+        name: "component callbacks",
+        register: register_component_callbacks,
+        unregister: unregister_component_callbacks,
+    },
+];
+
+#[derive(Debug)]
+struct Registration {
+    listener: GlobalRef,
+    unregister: UnregisterFn,
+}
+
+/// The Java listeners that have been registered for an Activity
+#[derive(Debug, Default)]
+pub(crate) struct JavaListeners {
+    registrations: Arc<Mutex<Vec<Registration>>>,
+}
+
+impl JavaListeners {
+    /// Registers all of our listeners for the given `Activity`, asynchronously
+    /// on the Java main thread
+    ///
+    /// Any failure is logged, and the state that the listener would report on
+    /// is then only checked at particular points, such as when the Activity
+    /// resumes.
+    pub(crate) fn register(jvm: &CloneJavaVM, activity: jni_sys::jobject) -> Self {
+        let listeners = Self::default();
+        let registrations = listeners.registrations.clone();
+        let result =
+            ui_thread::run_with_activity_on_ui_thread(jvm, activity, move |env, activity| {
+                let class = native_listener_class(env, activity)?;
+                let class: &JClass = class.as_obj().into();
+                let context = env
+                    .call_method(
+                        activity,
+                        "getApplicationContext",
+                        "()Landroid/content/Context;",
+                        &[],
+                    )?
+                    .l()?;
+                for kind in LISTENER_KINDS {
+                    let listener = match (kind.register)(env, &context, class) {
+                        Ok(listener) => listener,
+                        Err(err) => {
+                            let err = jni_utils::clear_and_map_exception_to_err(env, err);
+                            log::error!("Failed to register {}: {err:?}", kind.name);
+                            continue;
+                        }
+                    };
+                    registrations.lock().unwrap().push(Registration {
+                        listener: env.new_global_ref(listener)?,
+                        unregister: kind.unregister,
+                    });
+                }
+                Ok(())
+            });
+        if let Err(err) = result {
+            log::error!("Failed to register Java listeners: {err:?}");
+        }
+        listeners
+    }
+
+    /// Unregisters all of the listeners, asynchronously on the Java main thread
+    pub(crate) fn unregister(&self, jvm: &CloneJavaVM, activity: jni_sys::jobject) {
+        let registrations = self.registrations.clone();
+        let result =
+            ui_thread::run_with_activity_on_ui_thread(jvm, activity, move |env, activity| {
+                let context = env
+                    .call_method(
+                        activity,
+                        "getApplicationContext",
+                        "()Landroid/content/Context;",
+                        &[],
+                    )?
+                    .l()?;
+                let registrations = std::mem::take(&mut *registrations.lock().unwrap());
+                for registration in registrations {
+                    if let Err(err) =
+                        (registration.unregister)(env, &context, registration.listener.as_obj())
+                    {
+                        let err = jni_utils::clear_and_map_exception_to_err(env, err);
+                        log::error!("Failed to unregister Java listener: {err:?}");
+                    }
+                }
+                Ok(())
+            });
+        if let Err(err) = result {
+            log::error!("Failed to unregister Java listeners: {err:?}");
+        }
+    }
+}
+
+/// Loads the `NativeListener` class from our embedded dex file (the first time
+/// it's needed) and registers its native methods
+///
+/// Requires API level 21
+fn native_listener_class(
+    env: &mut JNIEnv<'_>,
+    activity: &JObject<'_>,
+) -> jni::errors::Result<GlobalRef> {
+    let mut guard = NATIVE_LISTENER.lock().unwrap();
+    if let Some(class) = guard.as_ref() {
+        return Ok(class.clone());
+    }
+
+    let parent = env
+        .call_method(activity, "getClassLoader", "()Ljava/lang/ClassLoader;", &[])?
+        .l()?;
+    let loader = if AndroidApp::sdk_version() >= 26 {
+        let bytes = env.byte_array_from_slice(NATIVE_LISTENER_DEX)?;
+        let buffer = env
+            .call_static_method(
+                "java/nio/ByteBuffer",
+                "wrap",
+                "([B)Ljava/nio/ByteBuffer;",
+                &[(&bytes).into()],
+            )?
+            .l()?;
+        env.new_object(
+            "dalvik/system/InMemoryDexClassLoader",
+            "(Ljava/nio/ByteBuffer;Ljava/lang/ClassLoader;)V",
+            &[(&buffer).into(), (&parent).into()],
+        )?
+    } else {
+        // Before `InMemoryDexClassLoader` the dex has to be loaded from a file
+        let code_cache_dir = env
+            .call_method(activity, "getCodeCacheDir", "()Ljava/io/File;", &[])?
+            .l()?;
+        let code_cache_dir: JString = env
+            .call_method(
+                &code_cache_dir,
+                "getAbsolutePath",
+                "()Ljava/lang/String;",
+                &[],
+            )?
+            .l()?
+            .into();
+        let code_cache_dir = String::from(env.get_string(&code_cache_dir)?);
+        let dex_path = format!("{code_cache_dir}/android-activity-listener.dex");
+        let dex_path = env.new_string(dex_path)?;
+        let bytes = env.byte_array_from_slice(NATIVE_LISTENER_DEX)?;
+        let output = env.new_object(
+            "java/io/FileOutputStream",
+            "(Ljava/lang/String;)V",
+            &[(&dex_path).into()],
+        )?;
+        env.call_method(&output, "write", "([B)V", &[(&bytes).into()])?;
+        env.call_method(&output, "close", "()V", &[])?;
+        let code_cache_dir = env.new_string(code_cache_dir)?;
+        env.new_object(
+            "dalvik/system/DexClassLoader",
+            "(Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;Ljava/lang/ClassLoader;)V",
+            &[
+                (&dex_path).into(),
+                (&code_cache_dir).into(),
+                (&JObject::null()).into(),
+                (&parent).into(),
+            ],
+        )?
+    };
+
+    let class_name = env.new_string(NATIVE_LISTENER_CLASS)?;
+    let class = env
+        .call_method(
+            &loader,
+            "loadClass",
+            "(Ljava/lang/String;)Ljava/lang/Class;",
+            &[(&class_name).into()],
+        )?
+        .l()?;
+    let class: JClass = class.into();
+    env.register_native_methods(
+        &class,
+        &[
+            NativeMethod {
+                name: "onReceive".into(),
+                sig: "(Landroid/content/Context;Landroid/content/Intent;)V".into(),
+                fn_ptr: on_receive as *mut std::ffi::c_void,
+            },
+            NativeMethod {
+                name: "invoke".into(),
+                sig: "(Ljava/lang/Object;Ljava/lang/reflect/Method;[Ljava/lang/Object;)Ljava/lang/Object;".into(),
+                fn_ptr: invoke as *mut std::ffi::c_void,
+            },
+        ],
+    )?;
+
+    let class = env.new_global_ref(class)?;
+    *guard = Some(class.clone());
+    Ok(class)
+}
+
+/// Creates a new `IntentFilter` for the given actions
+fn intent_filter<'local>(
+    env: &mut JNIEnv<'local>,
+    actions: &[&str],
+) -> jni::errors::Result<JObject<'local>> {
+    let filter = env.new_object("android/content/IntentFilter", "()V", &[])?;
+    for action in actions {
+        let action = env.new_string(action)?;
+        env.call_method(
+            &filter,
+            "addAction",
+            "(Ljava/lang/String;)V",
+            &[(&action).into()],
+        )?;
+    }
+    Ok(filter)
+}
+
+/// Registers a new `NativeListener` as a `BroadcastReceiver` for the given
+/// actions
+fn register_receiver<'local>(
+    env: &mut JNIEnv<'local>,
+    context: &JObject<'_>,
+    class: &JClass<'_>,
+    actions: &[&str],
+) -> jni::errors::Result<JObject<'local>> {
+    let receiver = env.new_object(class, "()V", &[])?;
+    let filter = intent_filter(env, actions)?;
+    if AndroidApp::sdk_version() >= 33 {
+        env.call_method(
+            context,
+            "registerReceiver",
+            "(Landroid/content/BroadcastReceiver;Landroid/content/IntentFilter;I)Landroid/content/Intent;",
+            &[(&receiver).into(), (&filter).into(), RECEIVER_NOT_EXPORTED.into()],
+        )?;
+    } else {
+        env.call_method(
+            context,
+            "registerReceiver",
+            "(Landroid/content/BroadcastReceiver;Landroid/content/IntentFilter;)Landroid/content/Intent;",
+            &[(&receiver).into(), (&filter).into()],
+        )?;
+    }
+    Ok(receiver)
+}
+
+fn unregister_receiver(
+    env: &mut JNIEnv<'_>,
+    context: &JObject<'_>,
+    receiver: &JObject<'_>,
+) -> jni::errors::Result<()> {
+    env.call_method(
+        context,
+        "unregisterReceiver",
+        "(Landroid/content/BroadcastReceiver;)V",
+        &[receiver.into()],
+    )?;
+    Ok(())
+}
+
+const ACTION_SCREEN_ON: &str = "android.intent.action.SCREEN_ON";
+const ACTION_SCREEN_OFF: &str = "android.intent.action.SCREEN_OFF";
+
+fn register_screen_state_receiver<'local>(
+    env: &mut JNIEnv<'local>,
+    context: &JObject<'_>,
+    class: &JClass<'_>,
+) -> jni::errors::Result<JObject<'local>> {
+    register_receiver(env, context, class, &[ACTION_SCREEN_ON, ACTION_SCREEN_OFF])
+}
+
+/// Creates a `java.lang.reflect.Proxy` that implements the given listener
+/// interface, with a new `NativeListener` as its `InvocationHandler`
+fn new_proxy<'local>(
+    env: &mut JNIEnv<'local>,
+    class: &JClass<'_>,
+    interface: &str,
+) -> jni::errors::Result<JObject<'local>> {
+    let handler = env.new_object(class, "()V", &[])?;
+    let loader = env
+        .call_method(class, "getClassLoader", "()Ljava/lang/ClassLoader;", &[])?
+        .l()?;
+    let interface = env.find_class(interface)?;
+    let interfaces = env.new_object_array(1, "java/lang/Class", &interface)?;
+    env.call_static_method(
+        "java/lang/reflect/Proxy",
+        "newProxyInstance",
+        "(Ljava/lang/ClassLoader;[Ljava/lang/Class;Ljava/lang/reflect/InvocationHandler;)Ljava/lang/Object;",
+        &[(&loader).into(), (&interfaces).into(), (&handler).into()],
+    )?
+    .l()
+}
+
+/// Gets the `ClipboardManager` system service
+fn clipboard_manager<'local>(
+    env: &mut JNIEnv<'local>,
+    context: &JObject<'_>,
+) -> jni::errors::Result<JObject<'local>> {
+    let service_name = env.new_string("clipboard")?;
+    env.call_method(
+        context,
+        "getSystemService",
+        "(Ljava/lang/String;)Ljava/lang/Object;",
+        &[(&service_name).into()],
+    )?
+    .l()
+}
+
+fn register_clip_listener<'local>(
+    env: &mut JNIEnv<'local>,
+    context: &JObject<'_>,
+    class: &JClass<'_>,
+) -> jni::errors::Result<JObject<'local>> {
+    let listener = new_proxy(
+        env,
+        class,
+        "android/content/ClipboardManager$OnPrimaryClipChangedListener",
+    )?;
+    let clipboard_manager = clipboard_manager(env, context)?;
+    env.call_method(
+        &clipboard_manager,
+        "addPrimaryClipChangedListener",
+        "(Landroid/content/ClipboardManager$OnPrimaryClipChangedListener;)V",
+        &[(&listener).into()],
+    )?;
+    Ok(listener)
+}
+
+fn unregister_clip_listener(
+    env: &mut JNIEnv<'_>,
+    context: &JObject<'_>,
+    listener: &JObject<'_>,
+) -> jni::errors::Result<()> {
+    let clipboard_manager = clipboard_manager(env, context)?;
+    env.call_method(
+        &clipboard_manager,
+        "removePrimaryClipChangedListener",
+        "(Landroid/content/ClipboardManager$OnPrimaryClipChangedListener;)V",
+        &[listener.into()],
+    )?;
+    Ok(())
+}
+
+/// Gets the `InputManager` system service
+fn input_manager<'local>(
+    env: &mut JNIEnv<'local>,
+    context: &JObject<'_>,
+) -> jni::errors::Result<JObject<'local>> {
+    let service_name = env.new_string("input")?;
+    env.call_method(
+        context,
+        "getSystemService",
+        "(Ljava/lang/String;)Ljava/lang/Object;",
+        &[(&service_name).into()],
+    )?
+    .l()
+}
+
+fn register_input_device_listener<'local>(
+    env: &mut JNIEnv<'local>,
+    context: &JObject<'_>,
+    class: &JClass<'_>,
+) -> jni::errors::Result<JObject<'local>> {
+    let listener = new_proxy(
+        env,
+        class,
+        "android/hardware/input/InputManager$InputDeviceListener",
+    )?;
+    let input_manager = input_manager(env, context)?;
+    // A null `Handler` means the listener is called on this (the main) thread
+    env.call_method(
+        &input_manager,
+        "registerInputDeviceListener",
+        "(Landroid/hardware/input/InputManager$InputDeviceListener;Landroid/os/Handler;)V",
+        &[(&listener).into(), (&JObject::null()).into()],
+    )?;
+    Ok(listener)
+}
+
+fn unregister_input_device_listener(
+    env: &mut JNIEnv<'_>,
+    context: &JObject<'_>,
+    listener: &JObject<'_>,
+) -> jni::errors::Result<()> {
+    let input_manager = input_manager(env, context)?;
+    env.call_method(
+        &input_manager,
+        "unregisterInputDeviceListener",
+        "(Landroid/hardware/input/InputManager$InputDeviceListener;)V",
+        &[listener.into()],
+    )?;
+    Ok(())
+}
+
+/// Registers `ComponentCallbacks2` with the application context, for
+/// application-level configuration changes that aren't reported to the
+/// Activity via `onConfigurationChanged()`
+///
+/// Trim memory levels are ignored here, since they're already reported to the
+/// Activity.
+fn register_component_callbacks<'local>(
+    env: &mut JNIEnv<'local>,
+    context: &JObject<'_>,
+    class: &JClass<'_>,
+) -> jni::errors::Result<JObject<'local>> {
+    let callbacks = new_proxy(env, class, "android/content/ComponentCallbacks2")?;
+    env.call_method(
+        context,
+        "registerComponentCallbacks",
+        "(Landroid/content/ComponentCallbacks;)V",
+        &[(&callbacks).into()],
+    )?;
+    Ok(callbacks)
+}
+
+fn unregister_component_callbacks(
+    env: &mut JNIEnv<'_>,
+    context: &JObject<'_>,
+    callbacks: &JObject<'_>,
+) -> jni::errors::Result<()> {
+    env.call_method(
+        context,
+        "unregisterComponentCallbacks",
+        "(Landroid/content/ComponentCallbacks;)V",
+        &[callbacks.into()],
+    )?;
+    Ok(())
+}
+
+/// Maps a listener method's name to the notifications it should trigger
+fn method_notifications(name: &str) -> Notifications {
+    match name {
+        "onPrimaryClipChanged" => Notifications::CLIPBOARD,
+        "onInputDeviceAdded" | "onInputDeviceRemoved" | "onInputDeviceChanged" => {
+            Notifications::INPUT_DEVICES
+        }
+        "onConfigurationChanged" => Notifications::CONFIG,
+        _ => Notifications::empty(),
+    }
+}
+
+/// `AudioManager.VOLUME_CHANGED_ACTION`, which isn't part of the public SDK
+/// but has been broadcast by all versions of Android
+const VOLUME_CHANGED_ACTION: &str = "android.media.VOLUME_CHANGED_ACTION";
+
+fn register_volume_receiver<'local>(
+    env: &mut JNIEnv<'local>,
+    context: &JObject<'_>,
+    class: &JClass<'_>,
+) -> jni::errors::Result<JObject<'local>> {
+    register_receiver(env, context, class, &[VOLUME_CHANGED_ACTION])
+}
+
+/// Maps a broadcast's action to the notifications it should trigger
+fn action_notifications(action: &str) -> Notifications {
+    match action {
+        ACTION_SCREEN_ON | ACTION_SCREEN_OFF => Notifications::SCREEN_STATE,
+        VOLUME_CHANGED_ACTION => Notifications::VOLUME,
+        _ => Notifications::empty(),
+    }
+}
+
+/// `NativeListener.onReceive(Context, Intent)`
+extern "system" fn on_receive<'local>(
+    mut env: JNIEnv<'local>,
+    _this: JObject<'local>,
+    _context: JObject<'local>,
+    intent: JObject<'local>,
+) {
+    abort_on_panic(|| {
+        let action = env
+            .call_method(&intent, "getAction", "()Ljava/lang/String;", &[])
+            .and_then(|action| action.l())
+            .and_then(|action| {
+                if action.is_null() {
+                    return Ok(String::new());
+                }
+                Ok(env.get_string(&JString::from(action))?.into())
+            });
+        match action {
+            Ok(action) => {
+                let notifications = action_notifications(&action);
+                if !notifications.is_empty() {
+                    notify::notify_current(notifications);
+                }
+            }
+            Err(err) => {
+                let err = jni_utils::clear_and_map_exception_to_err(&mut env, err);
+                log::error!("Failed to get broadcast action: {err:?}");
+            }
+        }
+    })
+}
+
+/// `NativeListener.invoke(Object, Method, Object[])`, for listener interfaces
+/// that are implemented via `java.lang.reflect.Proxy`
+///
+/// Besides the listener methods, this has to implement the `Object` methods
+/// that a `Proxy` forwards (`equals()`, `hashCode()` and `toString()`), since
+/// listeners are kept in collections by the framework.
+extern "system" fn invoke<'local>(
+    mut env: JNIEnv<'local>,
+    _this: JObject<'local>,
+    proxy: JObject<'local>,
+    method: JObject<'local>,
+    args: JObjectArray<'local>,
+) -> jobject {
+    abort_on_panic(|| {
+        let result = (|| -> jni::errors::Result<JObject<'local>> {
+            let name: JString = env
+                .call_method(&method, "getName", "()Ljava/lang/String;", &[])?
+                .l()?
+                .into();
+            let name = String::from(env.get_string(&name)?);
+            match name.as_str() {
+                "equals" => {
+                    let other = env.get_object_array_element(&args, 0)?;
+                    let equal = env.is_same_object(&proxy, &other)?;
+                    env.call_static_method(
+                        "java/lang/Boolean",
+                        "valueOf",
+                        "(Z)Ljava/lang/Boolean;",
+                        &[JValue::Bool(equal.into())],
+                    )?
+                    .l()
+                }
+                "hashCode" => {
+                    let hash = env
+                        .call_static_method(
+                            "java/lang/System",
+                            "identityHashCode",
+                            "(Ljava/lang/Object;)I",
+                            &[(&proxy).into()],
+                        )?
+                        .i()?;
+                    env.call_static_method(
+                        "java/lang/Integer",
+                        "valueOf",
+                        "(I)Ljava/lang/Integer;",
+                        &[hash.into()],
+                    )?
+                    .l()
+                }
+                "toString" => Ok(env.new_string(NATIVE_LISTENER_CLASS)?.into()),
+                _ => {
+                    let notifications = method_notifications(&name);
+                    if !notifications.is_empty() {
+                        notify::notify_current(notifications);
+                    }
+                    Ok(JObject::null())
+                }
+            }
+        })();
+        match result {
+            Ok(result) => result.into_raw(),
+            Err(err) => {
+                let err = jni_utils::clear_and_map_exception_to_err(&mut env, err);
+                log::error!("Failed to handle Java listener call: {err:?}");
+                std::ptr::null_mut()
+            }
+        }
+    })
+}
@@ -0,0 +1,98 @@
+//! A minimal logcat backend for the [`log`] crate and a panic hook that
+//! reports panics to logcat
+//!
+//! [`init_logging()`] sets up both in a single call, as a sane baseline for
+//! new applications, but each piece can also be used individually by
+//! applications that want to compose their own logging setup.
+
+use std::ffi::CString;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use crate::util::{self, android_log};
+
+/// A [`log::Log`] implementation that writes to logcat
+///
+/// Each record is tagged with the name of the crate that logged it (the first
+/// segment of the record's target).
+#[derive(Debug)]
+pub struct AndroidLogger {
+    level: LevelFilter,
+}
+
+impl AndroidLogger {
+    /// Creates a logger that writes records up to `level` to logcat
+    pub fn new(level: LevelFilter) -> Self {
+        Self { level }
+    }
+}
+
+impl Log for AndroidLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let tag = record.target().split("::").next().unwrap_or_default();
+        let msg = record.args().to_string();
+        android_log(record.level(), &to_cstring(tag), &to_cstring(&msg));
+    }
+
+    fn flush(&self) {}
+}
+
+/// Converts to a `CString`, dropping any interior nul bytes instead of failing
+fn to_cstring(s: &str) -> CString {
+    CString::new(s.replace('\0', "")).unwrap_or_default()
+}
+
+/// Installs a panic hook that writes the panic message, location and a
+/// backtrace to logcat, at the `Error` level with a `RustPanic` tag
+///
+/// This replaces any previously installed panic hook, including the default
+/// hook that writes to stderr (which would otherwise be forwarded to logcat
+/// line-by-line, at the `Info` level).
+pub fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        let tag = to_cstring("RustPanic");
+        let thread = std::thread::current();
+        let thread = thread.name().unwrap_or("<unnamed>");
+        android_log(
+            Level::Error,
+            &tag,
+            &to_cstring(&format!("thread '{thread}' {info}")),
+        );
+
+        // logcat truncates long messages, so log the backtrace line-by-line
+        let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+        for line in backtrace.lines() {
+            android_log(Level::Error, &tag, &to_cstring(line));
+        }
+    }));
+}
+
+/// Sets up a baseline for logging and crash reporting, in a single call
+///
+/// This installs an [`AndroidLogger`] for records up to `default_level` and a
+/// panic hook (see [`install_panic_hook()`]). If `forward_stdio` is `false`
+/// then stdout and stderr will no longer be forwarded to logcat (see
+/// [`AndroidApp::pause_stdio_capture()`](crate::AndroidApp::pause_stdio_capture)).
+///
+/// Returns an error, without changing anything, if a logger has already been
+/// set.
+pub fn init_logging(
+    default_level: LevelFilter,
+    forward_stdio: bool,
+) -> Result<(), log::SetLoggerError> {
+    // Note: `log::set_boxed_logger()` would require the `std` feature of `log`
+    log::set_logger(Box::leak(Box::new(AndroidLogger::new(default_level))))?;
+    log::set_max_level(default_level);
+    install_panic_hook();
+    if !forward_stdio {
+        util::pause_stdio_capture();
+    }
+    Ok(())
+}
@@ -3,21 +3,33 @@
 //! synchronization between the two threads.
 
 use std::{
+    fmt,
     ops::Deref,
     panic::catch_unwind,
     ptr::{self, NonNull},
-    sync::{Arc, Condvar, Mutex, Weak},
+    sync::{Arc, Condvar, Mutex, MutexGuard, Weak},
+    time::{Duration, Instant},
 };
 
 use ndk::{configuration::Configuration, input_queue::InputQueue, native_window::NativeWindow};
+use thiserror::Error;
 
 use crate::{
     jni_utils::CloneJavaVM,
-    util::{abort_on_panic, forward_stdio_to_logcat, log_panic},
-    ConfigurationRef,
+    util::{abort_on_panic, drain_stdio_to_logcat, log_panic, TEARDOWN_TIMEOUT},
+    AnrRiskCallbackFn, BlockedOperation, ConfigurationRef,
 };
 
-use super::{AndroidApp, Rect};
+use super::{handoff::Handoff, AndroidApp, Rect};
+
+/// Errors that can occur while setting up the glue for a new `ANativeActivity`
+#[derive(Error, Debug)]
+pub enum GlueError {
+    #[error("Could not create Rust <-> Java IPC pipe: {0}")]
+    Pipe(std::io::Error),
+    #[error("Could not allocate AConfiguration")]
+    ConfigAlloc,
+}
 
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
 pub enum AppCmd {
@@ -37,7 +49,19 @@ pub enum AppCmd {
     Pause = 13,
     Stop = 14,
     Destroy = 15,
+    Restart = 16,
 }
+
+/// A command read from the command pipe
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum PipeCmd {
+    App(AppCmd),
+
+    /// A command from `AndroidApp::CUSTOM_CMD_MIN` upwards, which is passed to
+    /// any handler registered via `AndroidApp::set_custom_cmd_handler()`
+    Custom(u8),
+}
+
 impl TryFrom<i8> for AppCmd {
     type Error = ();
 
@@ -59,6 +83,7 @@ impl TryFrom<i8> for AppCmd {
             13 => Ok(AppCmd::Pause),
             14 => Ok(AppCmd::Stop),
             15 => Ok(AppCmd::Destroy),
+            16 => Ok(AppCmd::Restart),
             _ => Err(()),
         }
     }
@@ -73,12 +98,150 @@ pub enum State {
     Stop,
 }
 
+/// An application callback that's called if the JVM main thread has been
+/// blocked, waiting for the `android_main` thread, for longer than `threshold`
+#[derive(Clone)]
+pub struct AnrRiskCallback {
+    pub threshold: Duration,
+    pub callback: AnrRiskCallbackFn,
+}
+impl fmt::Debug for AnrRiskCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnrRiskCallback")
+            .field("threshold", &self.threshold)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Selects which optional `ANativeActivity` callbacks are registered when the
+/// `NativeActivity` is created
+///
+/// This is intended for specialized integrations where some other layer
+/// needs to handle some of the `ANativeActivity` callbacks itself (such as
+/// managing the native window surface).
+///
+/// The lifecycle callbacks (`onStart`, `onResume`, `onPause`, `onStop` and
+/// `onDestroy`) are always registered since they are required for the
+/// synchronization between the Java main thread and the `android_main` thread.
+///
+/// The registration is applied the next time that a `NativeActivity` is
+/// created. Since the first Activity within a process is created before
+/// `android_main` runs, an application (or the crate that builds it) can
+/// export an `android_pre_create` function, which is called on the Java main
+/// thread at the start of `ANativeActivity_onCreate`, before any callbacks are
+/// registered.
+///
+/// # Example
+///
+/// ```no_run
+/// # use android_activity::NativeActivityCallbacks;
+/// #[no_mangle]
+/// extern "C" fn android_pre_create() {
+///     NativeActivityCallbacks::all()
+///         .native_window(false)
+///         .register();
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NativeActivityCallbacks {
+    save_instance_state: bool,
+    configuration_changed: bool,
+    low_memory: bool,
+    window_focus_changed: bool,
+    native_window: bool,
+    input_queue: bool,
+    content_rect_changed: bool,
+}
+
+static NATIVE_ACTIVITY_CALLBACKS: Mutex<NativeActivityCallbacks> =
+    Mutex::new(NativeActivityCallbacks::all());
+
+impl Default for NativeActivityCallbacks {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl NativeActivityCallbacks {
+    /// Selects all callbacks (the default)
+    pub const fn all() -> Self {
+        Self {
+            save_instance_state: true,
+            configuration_changed: true,
+            low_memory: true,
+            window_focus_changed: true,
+            native_window: true,
+            input_queue: true,
+            content_rect_changed: true,
+        }
+    }
+
+    /// Whether to register `onSaveInstanceState`, for [`MainEvent::SaveState`](crate::MainEvent::SaveState) events
+    pub const fn save_instance_state(mut self, enabled: bool) -> Self {
+        self.save_instance_state = enabled;
+        self
+    }
+
+    /// Whether to register `onConfigurationChanged`, for [`MainEvent::ConfigChanged`](crate::MainEvent::ConfigChanged) events
+    pub const fn configuration_changed(mut self, enabled: bool) -> Self {
+        self.configuration_changed = enabled;
+        self
+    }
+
+    /// Whether to register `onLowMemory`, for [`MainEvent::LowMemory`](crate::MainEvent::LowMemory) events
+    pub const fn low_memory(mut self, enabled: bool) -> Self {
+        self.low_memory = enabled;
+        self
+    }
+
+    /// Whether to register `onWindowFocusChanged`, for [`MainEvent::GainedFocus`](crate::MainEvent::GainedFocus)
+    /// and [`MainEvent::LostFocus`](crate::MainEvent::LostFocus) events
+    pub const fn window_focus_changed(mut self, enabled: bool) -> Self {
+        self.window_focus_changed = enabled;
+        self
+    }
+
+    /// Whether to register the `onNativeWindow*` callbacks
+    ///
+    /// If disabled, [`AndroidApp::native_window()`] will always return `None`.
+    pub const fn native_window(mut self, enabled: bool) -> Self {
+        self.native_window = enabled;
+        self
+    }
+
+    /// Whether to register the `onInputQueue*` callbacks
+    ///
+    /// If disabled, no input events will be delivered to the application.
+    pub const fn input_queue(mut self, enabled: bool) -> Self {
+        self.input_queue = enabled;
+        self
+    }
+
+    /// Whether to register `onContentRectChanged`, for [`MainEvent::ContentRectChanged`](crate::MainEvent::ContentRectChanged) events
+    pub const fn content_rect_changed(mut self, enabled: bool) -> Self {
+        self.content_rect_changed = enabled;
+        self
+    }
+
+    /// Sets which callbacks will be registered the next time a `NativeActivity` is created
+    ///
+    /// See above for how to call this before the first `NativeActivity` is
+    /// created.
+    pub fn register(self) {
+        *NATIVE_ACTIVITY_CALLBACKS.lock().unwrap() = self;
+    }
+}
+
 #[derive(Debug)]
 pub struct WaitableNativeActivityState {
     pub activity: *mut ndk_sys::ANativeActivity,
 
     pub mutex: Mutex<NativeActivityState>,
     pub cond: Condvar,
+
+    /// Kept separate from `mutex` so that the callback can be invoked without
+    /// holding the state lock
+    anr_risk_callback: Mutex<Option<AnrRiskCallback>>,
 }
 
 #[derive(Debug, Clone)]
@@ -101,40 +264,56 @@ impl NativeActivityGlue {
         activity: *mut ndk_sys::ANativeActivity,
         saved_state: *const libc::c_void,
         saved_state_size: libc::size_t,
-    ) -> Self {
+    ) -> Result<Self, GlueError> {
         let glue = Self {
             inner: Arc::new(WaitableNativeActivityState::new(
                 activity,
                 saved_state,
                 saved_state_size,
-            )),
+            )?),
         };
 
+        let enabled = *NATIVE_ACTIVITY_CALLBACKS.lock().unwrap();
+
         let weak_ref = Arc::downgrade(&glue.inner);
         let weak_ptr = Weak::into_raw(weak_ref);
         unsafe {
             (*activity).instance = weak_ptr as *mut _;
 
-            (*(*activity).callbacks).onDestroy = Some(on_destroy);
-            (*(*activity).callbacks).onStart = Some(on_start);
-            (*(*activity).callbacks).onResume = Some(on_resume);
-            (*(*activity).callbacks).onSaveInstanceState = Some(on_save_instance_state);
-            (*(*activity).callbacks).onPause = Some(on_pause);
-            (*(*activity).callbacks).onStop = Some(on_stop);
-            (*(*activity).callbacks).onConfigurationChanged = Some(on_configuration_changed);
-            (*(*activity).callbacks).onLowMemory = Some(on_low_memory);
-            (*(*activity).callbacks).onWindowFocusChanged = Some(on_window_focus_changed);
-            (*(*activity).callbacks).onNativeWindowCreated = Some(on_native_window_created);
-            (*(*activity).callbacks).onNativeWindowResized = Some(on_native_window_resized);
-            (*(*activity).callbacks).onNativeWindowRedrawNeeded =
-                Some(on_native_window_redraw_needed);
-            (*(*activity).callbacks).onNativeWindowDestroyed = Some(on_native_window_destroyed);
-            (*(*activity).callbacks).onInputQueueCreated = Some(on_input_queue_created);
-            (*(*activity).callbacks).onInputQueueDestroyed = Some(on_input_queue_destroyed);
-            (*(*activity).callbacks).onContentRectChanged = Some(on_content_rect_changed);
+            let callbacks = &mut *(*activity).callbacks;
+            callbacks.onDestroy = Some(on_destroy);
+            callbacks.onStart = Some(on_start);
+            callbacks.onResume = Some(on_resume);
+            callbacks.onPause = Some(on_pause);
+            callbacks.onStop = Some(on_stop);
+            if enabled.save_instance_state {
+                callbacks.onSaveInstanceState = Some(on_save_instance_state);
+            }
+            if enabled.configuration_changed {
+                callbacks.onConfigurationChanged = Some(on_configuration_changed);
+            }
+            if enabled.low_memory {
+                callbacks.onLowMemory = Some(on_low_memory);
+            }
+            if enabled.window_focus_changed {
+                callbacks.onWindowFocusChanged = Some(on_window_focus_changed);
+            }
+            if enabled.native_window {
+                callbacks.onNativeWindowCreated = Some(on_native_window_created);
+                callbacks.onNativeWindowResized = Some(on_native_window_resized);
+                callbacks.onNativeWindowRedrawNeeded = Some(on_native_window_redraw_needed);
+                callbacks.onNativeWindowDestroyed = Some(on_native_window_destroyed);
+            }
+            if enabled.input_queue {
+                callbacks.onInputQueueCreated = Some(on_input_queue_created);
+                callbacks.onInputQueueDestroyed = Some(on_input_queue_destroyed);
+            }
+            if enabled.content_rect_changed {
+                callbacks.onContentRectChanged = Some(on_content_rect_changed);
+            }
         }
 
-        glue
+        Ok(glue)
     }
 
     /// Returns the file descriptor that needs to be polled by the Rust main thread
@@ -144,7 +323,7 @@ impl NativeActivityGlue {
     }
 
     /// For the Rust main thread to read a single pending command sent from the JVM main thread
-    pub fn read_cmd(&self) -> Option<AppCmd> {
+    pub fn read_cmd(&self) -> Option<PipeCmd> {
         self.inner.mutex.lock().unwrap().read_cmd()
     }
 
@@ -161,7 +340,8 @@ impl NativeActivityGlue {
     ) -> Option<InputQueue> {
         let mut guard = self.mutex.lock().unwrap();
 
-        if guard.input_queue.is_null() {
+        let input_queue = *guard.input_queue.current();
+        if input_queue.is_null() {
             return None;
         }
 
@@ -169,9 +349,18 @@ impl NativeActivityGlue {
             // Reattach the input queue to the looper so future input will again deliver an
             // `InputAvailable` event.
             guard.attach_input_queue_to_looper(looper, ident);
-            Some(InputQueue::from_ptr(NonNull::new_unchecked(
-                guard.input_queue,
-            )))
+            Some(InputQueue::from_ptr(NonNull::new_unchecked(input_queue)))
+        }
+    }
+
+    /// Re-attaches the current input queue (if there is one) to the given looper
+    pub fn attach_input_queue_to_looper(&self, looper: *mut ndk_sys::ALooper, ident: libc::c_int) {
+        unsafe {
+            self.inner
+                .mutex
+                .lock()
+                .unwrap()
+                .attach_input_queue_to_looper(looper, ident);
         }
     }
 
@@ -192,6 +381,10 @@ impl NativeActivityGlue {
     pub fn content_rect(&self) -> Rect {
         self.mutex.lock().unwrap().content_rect.into()
     }
+
+    pub fn set_anr_risk_callback(&self, callback: Option<AnrRiskCallback>) {
+        *self.anr_risk_callback.lock().unwrap() = callback;
+    }
 }
 
 /// The status of the native thread that's created to run
@@ -202,6 +395,8 @@ pub enum NativeThreadState {
     Init,
     /// The `android_main` thread has been spawned and started running
     Running,
+    /// `android_main` has returned and the thread is being torn down
+    Exiting,
     /// The `android_main` thread has finished
     Stopped,
 }
@@ -212,8 +407,10 @@ pub struct NativeActivityState {
     pub msg_write: libc::c_int,
     pub config: ConfigurationRef,
     pub saved_state: Vec<u8>,
-    pub input_queue: *mut ndk_sys::AInputQueue,
-    pub window: Option<NativeWindow>,
+    /// The input queue, handed over from `onInputQueueCreated` / `onInputQueueDestroyed`
+    pub input_queue: Handoff<*mut ndk_sys::AInputQueue>,
+    /// The window, handed over from `onNativeWindowCreated` / `onNativeWindowDestroyed`
+    pub window: Handoff<Option<NativeWindow>>,
     pub content_rect: ndk_sys::ARect,
     pub activity_state: State,
     pub destroy_requested: bool,
@@ -224,19 +421,20 @@ pub struct NativeActivityState {
     /// `onDestroyed` callback.
     pub destroyed: bool,
     pub redraw_needed: bool,
-    pub pending_input_queue: *mut ndk_sys::AInputQueue,
-    pub pending_window: Option<NativeWindow>,
 }
 
 impl NativeActivityState {
-    pub fn read_cmd(&mut self) -> Option<AppCmd> {
+    pub fn read_cmd(&mut self) -> Option<PipeCmd> {
         let mut cmd_i: i8 = 0;
         loop {
             match unsafe { libc::read(self.msg_read, &mut cmd_i as *mut _ as *mut _, 1) } {
                 1 => {
+                    if cmd_i >= AndroidApp::CUSTOM_CMD_MIN as i8 {
+                        return Some(PipeCmd::Custom(cmd_i as u8));
+                    }
                     let cmd = AppCmd::try_from(cmd_i);
                     return match cmd {
-                        Ok(cmd) => Some(cmd),
+                        Ok(cmd) => Some(PipeCmd::App(cmd)),
                         Err(_) => {
                             log::error!("Spurious, unknown NativeActivityGlue cmd: {}", cmd_i);
                             None
@@ -287,22 +485,18 @@ impl NativeActivityState {
         looper: *mut ndk_sys::ALooper,
         ident: libc::c_int,
     ) {
-        if !self.input_queue.is_null() {
+        let input_queue = *self.input_queue.current();
+        if !input_queue.is_null() {
             log::trace!("Attaching input queue to looper");
-            ndk_sys::AInputQueue_attachLooper(
-                self.input_queue,
-                looper,
-                ident,
-                None,
-                ptr::null_mut(),
-            );
+            ndk_sys::AInputQueue_attachLooper(input_queue, looper, ident, None, ptr::null_mut());
         }
     }
 
     pub unsafe fn detach_input_queue_from_looper(&mut self) {
-        if !self.input_queue.is_null() {
+        let input_queue = *self.input_queue.current();
+        if !input_queue.is_null() {
             log::trace!("Detaching input queue from looper");
-            ndk_sys::AInputQueue_detachLooper(self.input_queue);
+            ndk_sys::AInputQueue_detachLooper(input_queue);
         }
     }
 }
@@ -326,14 +520,23 @@ impl WaitableNativeActivityState {
         activity: *mut ndk_sys::ANativeActivity,
         saved_state_in: *const libc::c_void,
         saved_state_size: libc::size_t,
-    ) -> Self {
+    ) -> Result<Self, GlueError> {
+        // Note: the configuration is allocated before the pipe so that we don't
+        // leak the pipe's file descriptors if the allocation fails
+        let config = unsafe {
+            let config =
+                NonNull::new(ndk_sys::AConfiguration_new()).ok_or(GlueError::ConfigAlloc)?;
+            ndk_sys::AConfiguration_fromAssetManager(config.as_ptr(), (*activity).assetManager);
+
+            let config = super::ConfigurationRef::new(Configuration::from_ptr(config));
+            log::trace!("Config: {:#?}", config);
+            config
+        };
+
         let mut msgpipe: [libc::c_int; 2] = [-1, -1];
         unsafe {
             if libc::pipe(msgpipe.as_mut_ptr()) != 0 {
-                panic!(
-                    "could not create  Rust <-> Java IPC pipe: {}",
-                    std::io::Error::last_os_error()
-                );
+                return Err(GlueError::Pipe(std::io::Error::last_os_error()));
             }
         }
 
@@ -344,26 +547,15 @@ impl WaitableNativeActivityState {
                 .to_vec()
         };
 
-        let config = unsafe {
-            let config = ndk_sys::AConfiguration_new();
-            ndk_sys::AConfiguration_fromAssetManager(config, (*activity).assetManager);
-
-            let config = super::ConfigurationRef::new(Configuration::from_ptr(
-                NonNull::new_unchecked(config),
-            ));
-            log::trace!("Config: {:#?}", config);
-            config
-        };
-
-        Self {
+        Ok(Self {
             activity,
             mutex: Mutex::new(NativeActivityState {
                 msg_read: msgpipe[0],
                 msg_write: msgpipe[1],
                 config,
                 saved_state,
-                input_queue: ptr::null_mut(),
-                window: None,
+                input_queue: Handoff::new(ptr::null_mut()),
+                window: Handoff::new(None),
                 content_rect: Rect::empty().into(),
                 activity_state: State::Init,
                 destroy_requested: false,
@@ -371,11 +563,50 @@ impl WaitableNativeActivityState {
                 app_has_saved_state: false,
                 destroyed: false,
                 redraw_needed: false,
-                pending_input_queue: ptr::null_mut(),
-                pending_window: None,
             }),
             cond: Condvar::new(),
+            anr_risk_callback: Mutex::new(None),
+        })
+    }
+
+    /// Blocks the JVM main thread until `done` returns `true`
+    ///
+    /// If the application has registered an [`AnrRiskCallback`] then it will be
+    /// called (once) if the wait takes longer than the callback's threshold.
+    fn wait_for_main_thread<'a>(
+        &'a self,
+        mut guard: MutexGuard<'a, NativeActivityState>,
+        op: BlockedOperation,
+        mut done: impl FnMut(&NativeActivityState) -> bool,
+    ) -> MutexGuard<'a, NativeActivityState> {
+        let mut anr_risk = self.anr_risk_callback.lock().unwrap().clone();
+        let start = Instant::now();
+
+        while !done(&guard) {
+            match &anr_risk {
+                Some(AnrRiskCallback {
+                    threshold,
+                    callback,
+                }) => {
+                    let remaining = threshold.saturating_sub(start.elapsed());
+                    if remaining.is_zero() {
+                        log::warn!("JVM main thread blocked on {op:?} for more than {threshold:?}");
+                        // Don't hold the state lock while calling into the application
+                        drop(guard);
+                        callback(op);
+                        guard = self.mutex.lock().unwrap();
+                        anr_risk = None;
+                    } else {
+                        guard = self.cond.wait_timeout(guard, remaining).unwrap().0;
+                    }
+                }
+                None => {
+                    guard = self.cond.wait(guard).unwrap();
+                }
+            }
         }
+
+        guard
     }
 
     pub fn notify_destroyed(&self) {
@@ -384,8 +615,26 @@ impl WaitableNativeActivityState {
 
         unsafe {
             guard.write_cmd(AppCmd::Destroy);
+            guard = self.wait_for_main_thread(guard, BlockedOperation::Destroy, |state| {
+                matches!(
+                    state.thread_state,
+                    NativeThreadState::Exiting | NativeThreadState::Stopped
+                )
+            });
+
+            // Once `android_main` has returned we only give the thread a bounded
+            // amount of time to tear down (such as detaching from the JVM), so
+            // that a misbehaving component can't block the Activity from being
+            // destroyed indefinitely. Nothing is read from the command pipe
+            // after `android_main` returns, so it's safe to close it regardless.
+            let deadline = Instant::now() + TEARDOWN_TIMEOUT;
             while guard.thread_state != NativeThreadState::Stopped {
-                guard = self.cond.wait(guard).unwrap();
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    log::error!("android_main thread failed to finish tearing down within {TEARDOWN_TIMEOUT:?}; continuing to destroy Activity");
+                    break;
+                }
+                guard = self.cond.wait_timeout(guard, remaining).unwrap().0;
             }
 
             libc::close(guard.msg_read);
@@ -416,62 +665,56 @@ impl WaitableNativeActivityState {
 
     pub fn notify_window_resized(&self, native_window: *mut ndk_sys::ANativeWindow) {
         let mut guard = self.mutex.lock().unwrap();
-        // set_window always syncs .pending_window back to .window before returning. This callback
+        // set_window always finishes the window handoff before returning. This callback
         // from Android can never arrive at an interim state, and validates that Android:
         // 1. Only provides resizes in between onNativeWindowCreated and onNativeWindowDestroyed;
         // 2. Doesn't call it on a bogus window pointer that we don't know about.
-        debug_assert_eq!(guard.window.as_ref().unwrap().ptr().as_ptr(), native_window);
+        debug_assert_eq!(
+            guard.window.current().as_ref().unwrap().ptr().as_ptr(),
+            native_window
+        );
         guard.write_cmd(AppCmd::WindowResized);
     }
 
     pub fn notify_window_redraw_needed(&self, native_window: *mut ndk_sys::ANativeWindow) {
         let mut guard = self.mutex.lock().unwrap();
-        // set_window always syncs .pending_window back to .window before returning. This callback
+        // set_window always finishes the window handoff before returning. This callback
         // from Android can never arrive at an interim state, and validates that Android:
         // 1. Only provides resizes in between onNativeWindowCreated and onNativeWindowDestroyed;
         // 2. Doesn't call it on a bogus window pointer that we don't know about.
-        debug_assert_eq!(guard.window.as_ref().unwrap().ptr().as_ptr(), native_window);
+        debug_assert_eq!(
+            guard.window.current().as_ref().unwrap().ptr().as_ptr(),
+            native_window
+        );
         guard.write_cmd(AppCmd::WindowRedrawNeeded);
     }
 
     unsafe fn set_input(&self, input_queue: *mut ndk_sys::AInputQueue) {
         let mut guard = self.mutex.lock().unwrap();
 
-        // The pending_input_queue state should only be set while in this method, and since
-        // it doesn't allow re-entrance and is cleared before returning then we expect
-        // this to be null
-        debug_assert!(
-            guard.pending_input_queue.is_null(),
-            "InputQueue update clash"
-        );
-
-        guard.pending_input_queue = input_queue;
+        guard.input_queue.propose(input_queue);
         guard.write_cmd(AppCmd::InputQueueChanged);
-        while guard.input_queue != guard.pending_input_queue {
-            guard = self.cond.wait(guard).unwrap();
-        }
-        guard.pending_input_queue = ptr::null_mut();
+        guard = self.wait_for_main_thread(guard, BlockedOperation::InputQueueChanged, |state| {
+            state.input_queue.is_settled()
+        });
+        guard.input_queue.finish();
     }
 
     unsafe fn set_window(&self, window: Option<NativeWindow>) {
         let mut guard = self.mutex.lock().unwrap();
 
-        // The pending_window state should only be set while in this method, and since
-        // it doesn't allow re-entrance and is cleared before returning then we expect
-        // this to be None
-        debug_assert!(guard.pending_window.is_none(), "NativeWindow update clash");
-
-        if guard.window.is_some() {
+        if guard.window.current().is_some() {
             guard.write_cmd(AppCmd::TermWindow);
         }
-        guard.pending_window = window;
-        if guard.pending_window.is_some() {
+        let has_window = window.is_some();
+        guard.window.propose(window);
+        if has_window {
             guard.write_cmd(AppCmd::InitWindow);
         }
-        while guard.window != guard.pending_window {
-            guard = self.cond.wait(guard).unwrap();
-        }
-        guard.pending_window = None;
+        guard = self.wait_for_main_thread(guard, BlockedOperation::WindowChanged, |state| {
+            state.window.is_settled()
+        });
+        guard.window.finish();
     }
 
     unsafe fn set_content_rect(&self, rect: *const ndk_sys::ARect) {
@@ -483,20 +726,41 @@ impl WaitableNativeActivityState {
     unsafe fn set_activity_state(&self, state: State) {
         let mut guard = self.mutex.lock().unwrap();
 
-        let cmd = match state {
-            State::Init => panic!("Can't explicitly transition into 'init' state"),
-            State::Start => AppCmd::Start,
-            State::Resume => AppCmd::Resume,
-            State::Pause => AppCmd::Pause,
-            State::Stop => AppCmd::Stop,
+        let (cmd, op) = match state {
+            State::Init => {
+                // Panicking here would abort the process, since we're running on the JVM
+                // main thread
+                log::error!("Ignoring request to explicitly transition into 'init' state");
+                return;
+            }
+            State::Start => (AppCmd::Start, BlockedOperation::Start),
+            State::Resume => (AppCmd::Resume, BlockedOperation::Resume),
+            State::Pause => (AppCmd::Pause, BlockedOperation::Pause),
+            State::Stop => (AppCmd::Stop, BlockedOperation::Stop),
         };
-        guard.write_cmd(cmd);
 
-        while guard.activity_state != state {
-            guard = self.cond.wait(guard).unwrap();
+        // `ANativeActivityCallbacks` has no `onRestart` callback, but Android
+        // always calls `onRestart` before `onStart` when the Activity is being
+        // started again after being stopped
+        if state == State::Start && guard.activity_state == State::Stop {
+            guard.write_cmd(AppCmd::Restart);
         }
+        guard.write_cmd(cmd);
+
+        let _guard = self.wait_for_main_thread(guard, op, |s| s.activity_state == state);
     }
 
+    /// Blocks the JVM main thread until the application has handled a
+    /// `SaveState` event, and returns a copy of whatever state it saved.
+    ///
+    /// There is intentionally no timeout here: `onSaveInstanceState` can only
+    /// return the saved state synchronously, so giving up early would lose
+    /// the state. The process isn't suspended while the Java main thread is
+    /// blocked in this callback, so the application has until the system
+    /// decides the Activity isn't responding to finish any I/O.
+    ///
+    /// We only stop waiting early if the `android_main` thread has stopped
+    /// running, since it would otherwise never acknowledge the request.
     fn request_save_state(&self) -> (*mut libc::c_void, libc::size_t) {
         let mut guard = self.mutex.lock().unwrap();
 
@@ -505,8 +769,15 @@ impl WaitableNativeActivityState {
         // this to be None
         debug_assert!(!guard.app_has_saved_state, "SaveState request clash");
         guard.write_cmd(AppCmd::SaveState);
-        while !guard.app_has_saved_state {
-            guard = self.cond.wait(guard).unwrap();
+        guard = self.wait_for_main_thread(guard, BlockedOperation::SaveState, |state| {
+            state.app_has_saved_state
+                || matches!(
+                    state.thread_state,
+                    NativeThreadState::Exiting | NativeThreadState::Stopped
+                )
+        });
+        if !guard.app_has_saved_state {
+            log::warn!("android_main thread stopped before handling SaveState request");
         }
         guard.app_has_saved_state = false;
 
@@ -518,11 +789,13 @@ impl WaitableNativeActivityState {
             let saved_state_src_ptr = guard.saved_state.as_ptr();
             unsafe {
                 let saved_state = libc::malloc(saved_state_size);
-                assert!(
-                    !saved_state.is_null(),
-                    "Failed to allocate {} bytes for restoring saved application state",
-                    saved_state_size
-                );
+                if saved_state.is_null() {
+                    // Don't panic here, since we're called directly by the JVM
+                    log::error!(
+                        "Failed to allocate {saved_state_size} bytes for saving application state"
+                    );
+                    return (ptr::null_mut(), 0);
+                }
                 libc::memcpy(saved_state, saved_state_src_ptr as _, saved_state_size);
                 (saved_state, saved_state_size)
             }
@@ -540,13 +813,31 @@ impl WaitableNativeActivityState {
         }
     }
 
+    pub fn activity_state(&self) -> State {
+        self.mutex.lock().unwrap().activity_state
+    }
+
+    pub fn saved_state_len(&self) -> usize {
+        self.mutex.lock().unwrap().saved_state.len()
+    }
+
     pub fn set_saved_state(&self, state: &[u8]) {
         let mut guard = self.mutex.lock().unwrap();
 
+        // Note: this reuses the existing allocation if the new state fits
         guard.saved_state.clear();
         guard.saved_state.extend_from_slice(state);
     }
 
+    pub fn set_saved_state_with(&self, len: usize, write: impl FnOnce(&mut [u8])) {
+        // Take the buffer so we don't hold the lock while calling into the
+        // application
+        let mut saved_state = std::mem::take(&mut self.mutex.lock().unwrap().saved_state);
+        saved_state.resize(len, 0);
+        write(&mut saved_state);
+        self.mutex.lock().unwrap().saved_state = saved_state;
+    }
+
     ////////////////////////////
     // Rust-side event loop
     ////////////////////////////
@@ -557,6 +848,12 @@ impl WaitableNativeActivityState {
         self.cond.notify_one();
     }
 
+    pub fn notify_main_thread_exiting(&self) {
+        let mut guard = self.mutex.lock().unwrap();
+        guard.thread_state = NativeThreadState::Exiting;
+        self.cond.notify_one();
+    }
+
     pub fn notify_main_thread_stopped_running(&self) {
         let mut guard = self.mutex.lock().unwrap();
         guard.thread_state = NativeThreadState::Stopped;
@@ -574,15 +871,15 @@ impl WaitableNativeActivityState {
             AppCmd::InputQueueChanged => {
                 let mut guard = self.mutex.lock().unwrap();
                 guard.detach_input_queue_from_looper();
-                guard.input_queue = guard.pending_input_queue;
-                if !guard.input_queue.is_null() {
+                guard.input_queue.apply();
+                if !guard.input_queue.current().is_null() {
                     guard.attach_input_queue_to_looper(looper, input_queue_ident);
                 }
                 self.cond.notify_one();
             }
             AppCmd::InitWindow => {
                 let mut guard = self.mutex.lock().unwrap();
-                guard.window = guard.pending_window.clone();
+                guard.window.apply();
                 self.cond.notify_one();
             }
             AppCmd::Resume | AppCmd::Start | AppCmd::Pause | AppCmd::Stop => {
@@ -617,7 +914,7 @@ impl WaitableNativeActivityState {
         match cmd {
             AppCmd::TermWindow => {
                 let mut guard = self.mutex.lock().unwrap();
-                guard.window = None;
+                guard.window.set_current(None);
                 self.cond.notify_one();
             }
             AppCmd::SaveState => {
@@ -655,6 +952,10 @@ unsafe extern "C" fn on_destroy(activity: *mut ndk_sys::ANativeActivity) {
         try_with_waitable_activity_ref(activity, |waitable_activity| {
             waitable_activity.notify_destroyed()
         });
+
+        // The `android_main` thread has stopped by now, but other threads may
+        // still be using the `ndk_context` up until the Activity is destroyed
+        ndk_context::release_android_context();
     })
 }
 
@@ -824,6 +1125,41 @@ unsafe extern "C" fn on_content_rect_changed(
     });
 }
 
+/// The name of an optional function that applications can export, which is
+/// called at the start of `ANativeActivity_onCreate`, such as for registering
+/// [`NativeActivityCallbacks`]
+const PRE_CREATE_SYMBOL: &[u8] = b"android_pre_create\0";
+
+/// Calls the application's `android_pre_create` function, if it exports one
+///
+/// The function is looked up in our own library (instead of via
+/// `RTLD_DEFAULT`), since libraries loaded by `System.loadLibrary()` aren't
+/// part of the global symbol scope.
+fn call_pre_create_hook() {
+    unsafe {
+        let mut info: libc::Dl_info = std::mem::zeroed();
+        if libc::dladdr(ANativeActivity_onCreate as *const libc::c_void, &mut info) == 0
+            || info.dli_fname.is_null()
+        {
+            log::warn!("Failed to find our own library to look up android_pre_create");
+            return;
+        }
+        let lib = libc::dlopen(info.dli_fname, libc::RTLD_NOW | libc::RTLD_NOLOAD);
+        if lib.is_null() {
+            log::warn!("Failed to open our own library to look up android_pre_create");
+            return;
+        }
+        let hook = libc::dlsym(lib, PRE_CREATE_SYMBOL.as_ptr() as _);
+        // The library stays loaded, since `RTLD_NOLOAD` only added a reference
+        libc::dlclose(lib);
+        if !hook.is_null() {
+            log::trace!("Calling android_pre_create");
+            let hook: extern "C" fn() = std::mem::transmute(hook);
+            hook();
+        }
+    }
+}
+
 /// This is the native entrypoint for our cdylib library that `ANativeActivity` will look for via `dlsym`
 #[no_mangle]
 extern "C" fn ANativeActivity_onCreate(
@@ -832,7 +1168,24 @@ extern "C" fn ANativeActivity_onCreate(
     saved_state_size: libc::size_t,
 ) {
     abort_on_panic(|| {
-        let _join_log_forwarder = forward_stdio_to_logcat();
+        #[cfg(feature = "native-stdout-stderr-logging")]
+        let _join_log_forwarder = crate::util::forward_stdio_to_logcat();
+
+        // We're running on the Java main thread, which lets us register a
+        // callback with its looper
+        crate::ui_thread::init();
+
+        // The `ndk_context` is associated with the lifetime of the Activity (not
+        // the `android_main` thread) and is released in `on_destroy`, so that it
+        // remains valid for other threads even if `android_main` returns early
+        unsafe {
+            let jvm: *mut jni_sys::JavaVM = (*activity).vm;
+            let activity = (*activity).clazz; // Completely bogus name; this is the _instance_ not class pointer
+            ndk_context::initialize_android_context(jvm.cast(), activity.cast());
+        }
+
+        // Called after initializing the `ndk_context`, so the hook can use it
+        call_pre_create_hook();
 
         log::trace!(
             "Creating: {:p}, saved_state = {:p}, save_state_size = {}",
@@ -843,7 +1196,20 @@ extern "C" fn ANativeActivity_onCreate(
 
         // Conceptually we associate a glue reference with the JVM main thread, and another
         // reference with the Rust main thread
-        let jvm_glue = NativeActivityGlue::new(activity, saved_state, saved_state_size);
+        let jvm_glue = match NativeActivityGlue::new(activity, saved_state, saved_state_size) {
+            Ok(glue) => glue,
+            Err(err) => {
+                // Bail before spawning the `android_main` thread. None of our
+                // callbacks have been registered, so the JVM won't call back
+                // into us for this Activity
+                log::error!("Failed to create NativeActivity glue: {err}; finishing Activity");
+                unsafe {
+                    ndk_context::release_android_context();
+                    ndk_sys::ANativeActivity_finish(activity);
+                }
+                return;
+            }
+        };
 
         let rust_glue = jvm_glue.clone();
         // Let us Send the NativeActivity pointer to the Rust main() thread without a wrapper type
@@ -854,19 +1220,25 @@ extern "C" fn ANativeActivity_onCreate(
             let activity: *mut ndk_sys::ANativeActivity = activity_ptr as *mut _;
 
             let jvm = abort_on_panic(|| unsafe {
-                let na = activity;
-                let jvm: *mut jni_sys::JavaVM = (*na).vm;
-                let activity = (*na).clazz; // Completely bogus name; this is the _instance_ not class pointer
-                ndk_context::initialize_android_context(jvm.cast(), activity.cast());
-
-                let jvm = CloneJavaVM::from_raw(jvm).unwrap();
-                // Since this is a newly spawned thread then the JVM hasn't been attached
-                // to the thread yet. Attach before calling the applications main function
-                // so they can safely make JNI calls
-                jvm.attach_current_thread_permanently().unwrap();
-                jvm
+                let jvm: *mut jni_sys::JavaVM = (*activity).vm;
+                CloneJavaVM::from_raw(jvm).unwrap()
             });
 
+            // Since this is a newly spawned thread then the JVM hasn't been attached
+            // to the thread yet. Attach before calling the applications main function
+            // so they can safely make JNI calls
+            if !jvm.attach_main_thread() {
+                // Without JNI we can't even create the `AndroidApp`, so we finish the
+                // Activity instead of running `android_main`
+                log::error!("JNI is unavailable for the android_main thread; finishing Activity without running android_main");
+                rust_glue.notify_main_thread_running();
+                unsafe {
+                    ndk_sys::ANativeActivity_finish(activity);
+                }
+                rust_glue.notify_main_thread_stopped_running();
+                return;
+            }
+
             let app = AndroidApp::new(rust_glue.clone(), jvm.clone());
 
             rust_glue.notify_main_thread_running();
@@ -892,17 +1264,19 @@ extern "C" fn ANativeActivity_onCreate(
                 })
                 .unwrap_or_else(log_panic);
 
+                rust_glue.notify_main_thread_exiting();
+
                 // Let JVM know that our Activity can be destroyed before detaching from the JVM
                 //
                 // "Note that this method can be called from any thread; it will send a message
                 //  to the main thread of the process where the Java finish call will take place"
                 ndk_sys::ANativeActivity_finish(activity);
 
+                drain_stdio_to_logcat(TEARDOWN_TIMEOUT);
+
                 // This should detach automatically but lets detach explicitly to avoid depending
                 // on the TLS trickery in `jni-rs`
                 jvm.detach_current_thread();
-
-                ndk_context::release_android_context();
             }
 
             rust_glue.notify_main_thread_stopped_running();
@@ -0,0 +1,117 @@
+/// A value that's handed over from the Java main thread to the `android_main`
+/// thread
+///
+/// The Java main thread [proposes](Handoff::propose) a new value, notifies the
+/// `android_main` thread (by writing a command to the pipe), and then waits
+/// until [`Handoff::is_settled()`] before [finishing](Handoff::finish) the
+/// handoff. The `android_main` thread [applies](Handoff::apply) the proposed
+/// value while handling the command.
+///
+/// A `Handoff` doesn't do any synchronization itself and is expected to be
+/// protected by the same mutex (and condition variable) as the rest of the
+/// `NativeActivityState`.
+#[derive(Debug)]
+pub struct Handoff<T> {
+    current: T,
+    pending: Option<T>,
+}
+
+impl<T: Clone + PartialEq> Handoff<T> {
+    pub fn new(current: T) -> Self {
+        Self {
+            current,
+            pending: None,
+        }
+    }
+
+    /// The value that has been applied by the `android_main` thread
+    pub fn current(&self) -> &T {
+        &self.current
+    }
+
+    /// Starts a handoff of `value`, from the Java main thread
+    pub fn propose(&mut self, value: T) {
+        // Handoffs don't allow re-entrance and are always finished before the
+        // Java main thread returns from the callback that started them
+        debug_assert!(self.pending.is_none(), "Handoff clash");
+        self.pending = Some(value);
+    }
+
+    /// Applies any pending value, from the `android_main` thread
+    pub fn apply(&mut self) {
+        if let Some(pending) = &self.pending {
+            self.current = pending.clone();
+        }
+    }
+
+    /// Directly sets the current value, from the `android_main` thread
+    ///
+    /// This is for when the `android_main` thread clears a value as part of a
+    /// handoff (such as terminating the old window before the new window is
+    /// applied).
+    pub fn set_current(&mut self, value: T) {
+        self.current = value;
+    }
+
+    /// Returns `true` if there's no pending value, or the pending value has
+    /// been applied
+    pub fn is_settled(&self) -> bool {
+        self.pending
+            .as_ref()
+            .map_or(true, |pending| *pending == self.current)
+    }
+
+    /// Ends the handoff, from the Java main thread
+    pub fn finish(&mut self) {
+        self.pending = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proposed_value_settles_once_applied() {
+        let mut handoff = Handoff::new(None);
+        assert!(handoff.is_settled());
+
+        handoff.propose(Some(1));
+        assert!(!handoff.is_settled());
+        assert_eq!(handoff.current(), &None);
+
+        handoff.apply();
+        assert!(handoff.is_settled());
+        assert_eq!(handoff.current(), &Some(1));
+
+        handoff.finish();
+        assert!(handoff.is_settled());
+        assert_eq!(handoff.current(), &Some(1));
+    }
+
+    #[test]
+    fn replacing_value_waits_for_new_value() {
+        let mut handoff = Handoff::new(Some(1));
+
+        handoff.propose(Some(2));
+        // Terminating the old value isn't enough to settle the handoff
+        handoff.set_current(None);
+        assert!(!handoff.is_settled());
+        handoff.apply();
+        assert!(handoff.is_settled());
+        handoff.finish();
+
+        // Removing the value settles once it's cleared
+        handoff.propose(None);
+        assert!(!handoff.is_settled());
+        handoff.set_current(None);
+        assert!(handoff.is_settled());
+    }
+
+    #[test]
+    fn apply_without_proposal_keeps_current_value() {
+        let mut handoff = Handoff::new(Some(1));
+        handoff.apply();
+        assert_eq!(handoff.current(), &Some(1));
+    }
+}
@@ -1,9 +1,32 @@
+use std::ffi::{c_void, CStr};
 use std::marker::PhantomData;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
 
 use crate::input::{
-    Axis, Button, ButtonState, EdgeFlags, KeyAction, Keycode, MetaState, MotionAction,
-    MotionEventFlags, Pointer, PointersIter, Source, ToolType,
+    Axis, Button, ButtonState, DecodedKeyAction, DecodedMotionAction, EdgeFlags, KeyAction,
+    KeyEventFlags, Keycode, MetaState, MotionAction, MotionClassification, MotionEventFlags,
+    MotionSample, MotionSamplesIter, Pointer, PointersIter, Source, ToolType,
 };
+use crate::input_devices;
+use crate::util;
+
+type GetClassification = unsafe extern "C" fn(*const ndk_sys::AInputEvent) -> i32;
+
+/// Looks up `AMotionEvent_getClassification()` (API level 33) at runtime, so
+/// that we can still be loaded on older versions of Android
+fn get_classification_fn() -> Option<GetClassification> {
+    static GET_CLASSIFICATION: AtomicPtr<c_void> = AtomicPtr::new(ptr::null_mut());
+    let mut symbol = GET_CLASSIFICATION.load(Ordering::Relaxed);
+    if symbol.is_null() {
+        symbol = util::libandroid_symbol(
+            CStr::from_bytes_with_nul(b"AMotionEvent_getClassification\0").unwrap(),
+        );
+        GET_CLASSIFICATION.store(symbol, Ordering::Relaxed);
+    }
+    (!symbol.is_null())
+        .then(|| unsafe { std::mem::transmute::<*mut c_void, GetClassification>(symbol) })
+}
 
 /// A motion event
 ///
@@ -28,6 +51,7 @@ impl<'a> MotionEvent<'a> {
 
     /// Get the source of the event.
     ///
+    /// The source may be a combination of sources (see [`Source::contains()`]).
     #[inline]
     pub fn source(&self) -> Source {
         // XXX: we use `AInputEvent_getSource` directly (instead of calling
@@ -46,6 +70,12 @@ impl<'a> MotionEvent<'a> {
         self.ndk_event.device_id()
     }
 
+    /// Get the id of the display that the event was targeted at (see [`DEFAULT_DISPLAY_ID`](crate::input::DEFAULT_DISPLAY_ID))
+    #[inline]
+    pub fn display_id(&self) -> i32 {
+        crate::input::DEFAULT_DISPLAY_ID
+    }
+
     /// Returns the motion action associated with the event.
     ///
     /// See [the MotionEvent docs](https://developer.android.com/reference/android/view/MotionEvent#getActionMasked())
@@ -86,6 +116,17 @@ impl<'a> MotionEvent<'a> {
         self.ndk_event.pointer_index()
     }
 
+    /// Returns the motion action, decoded together with the index of the
+    /// pointer for [`MotionAction::PointerDown`] and [`MotionAction::PointerUp`]
+    /// actions
+    ///
+    /// This saves having to separately call [`Self::pointer_index()`], which
+    /// is meaningless for other actions, when tracking multi-touch pointers.
+    #[inline]
+    pub fn decoded_action(&self) -> DecodedMotionAction {
+        DecodedMotionAction::new(self.action(), self.pointer_index())
+    }
+
     /*
     /// Returns the pointer id associated with the given pointer index.
     ///
@@ -128,6 +169,149 @@ impl<'a> MotionEvent<'a> {
         }
     }
 
+    /// The pointer that went down or up, for [`Down`](MotionAction::Down),
+    /// [`Up`](MotionAction::Up), [`PointerDown`](MotionAction::PointerDown) and
+    /// [`PointerUp`](MotionAction::PointerUp) actions
+    ///
+    /// This is the pointer at [`Self::pointer_index()`], whose
+    /// [`pointer_id()`](Pointer::pointer_id) can be used to track the pointer
+    /// across subsequent events. For other actions this is the first pointer.
+    #[inline]
+    pub fn action_pointer(&self) -> Pointer<'_> {
+        self.pointer_at_index(self.pointer_index())
+    }
+
+    /// Finds the pointer with the given (stable) pointer ID in this event
+    ///
+    /// Unlike pointer indices, which can change from one event to the next,
+    /// a pointer's ID stays the same for as long as it's down. Returns `None`
+    /// if there's no such pointer in this event (such as after it has gone
+    /// up).
+    #[inline]
+    pub fn pointer_by_id(&self, pointer_id: i32) -> Option<Pointer<'_>> {
+        self.pointers()
+            .find(|pointer| pointer.pointer_id() == pointer_id)
+    }
+
+    /// Returns the pressure of the pointer at the given index, normally in
+    /// the range `0.0` to `1.0`
+    ///
+    /// Returns `None` if the event's device doesn't report
+    /// [`Axis::Pressure`] for the event's source (such as for a mouse), instead
+    /// of a meaningless value. If the device can't be queried then this falls
+    /// back to returning `None` for mouse and unknown tools.
+    ///
+    /// _Note: touchscreens that don't sense pressure may still report a
+    /// nominal pressure of `1.0` for fingers, which can't be distinguished
+    /// here._
+    ///
+    /// Panics if the pointer index is out of bounds.
+    #[inline]
+    pub fn pressure(&self, pointer_index: usize) -> Option<f32> {
+        let pointer = self.pointer_at_index(pointer_index);
+        let has_pressure = input_devices::has_pressure_axis(self.device_id(), self.source().into())
+            .unwrap_or_else(|| !matches!(pointer.tool_type(), ToolType::Mouse | ToolType::Unknown));
+        has_pressure.then(|| pointer.pressure())
+    }
+
+    /// Returns the tilt of the stylus at the given index away from the
+    /// perpendicular, in radians (see [`Pointer::tilt()`])
+    ///
+    /// Panics if the pointer index is out of bounds.
+    #[inline]
+    pub fn tilt(&self, pointer_index: usize) -> f32 {
+        self.pointer_at_index(pointer_index).tilt()
+    }
+
+    /// Returns the orientation of the pointer at the given index, in radians
+    ///
+    /// For a stylus this is the direction that it's pointing in, where `0.0`
+    /// is "up" and positive values are clockwise.
+    ///
+    /// Panics if the pointer index is out of bounds.
+    #[inline]
+    pub fn orientation(&self, pointer_index: usize) -> f32 {
+        self.pointer_at_index(pointer_index).orientation()
+    }
+
+    /// Returns the value of the given axis for the pointer at the given index
+    ///
+    /// For [`Source::Joystick`] events from a gamepad, the left stick is
+    /// reported via [`Axis::X`] and [`Axis::Y`], the right stick usually via
+    /// [`Axis::Z`] and [`Axis::Rz`], the D-pad via [`Axis::HatX`] and
+    /// [`Axis::HatY`] and the triggers via [`Axis::Ltrigger`] and
+    /// [`Axis::Rtrigger`] (or [`Axis::Brake`] and [`Axis::Gas`]). See
+    /// [`InputDeviceInfo::motion_range()`](crate::input::InputDeviceInfo::motion_range)
+    /// for each axis's dead zone.
+    ///
+    /// Panics if the pointer index is out of bounds.
+    #[inline]
+    pub fn axis_value(&self, axis: Axis, pointer_index: usize) -> f32 {
+        self.pointer_at_index(pointer_index).axis_value(axis)
+    }
+
+    /// Returns the `(horizontal, vertical)` scroll amounts of a mouse wheel
+    /// (or touchpad scroll gesture), for [`MotionAction::Scroll`] events
+    ///
+    /// These are the [`Axis::Hscroll`] and [`Axis::Vscroll`] axes, which are
+    /// relative values, normally between `-1.0` and `1.0`. A positive vertical
+    /// value means scrolling up (such as to zoom in).
+    #[inline]
+    pub fn scroll(&self) -> (f32, f32) {
+        let pointer = self.pointer_at_index(0);
+        (
+            pointer.axis_value(Axis::Hscroll),
+            pointer.axis_value(Axis::Vscroll),
+        )
+    }
+
+    /// Returns `true` if this event reports relative motion, such as from a
+    /// trackball ([`Source::Trackball`]) or a relative mouse
+    /// ([`Source::MouseRelative`]), rather than absolute positions
+    #[inline]
+    pub fn is_relative(&self) -> bool {
+        self.source().is_trackball_class()
+    }
+
+    /// Returns `true` if this event was delivered while the pointer is captured
+    ///
+    /// While the pointer is captured (see
+    /// [`AndroidApp::request_pointer_capture()`](crate::AndroidApp::request_pointer_capture))
+    /// mouse events have a [`Source::MouseRelative`] source and touchpad events
+    /// have a [`Source::Touchpad`] source, and neither are delivered otherwise.
+    #[inline]
+    pub fn is_captured(&self) -> bool {
+        matches!(self.source(), Source::MouseRelative | Source::Touchpad)
+    }
+
+    /// Returns the relative `(x, y)` motion of the pointer at the given index
+    ///
+    /// For relative events (see [`MotionEvent::is_relative()`]) this is the
+    /// event's X and Y axes, which are already relative. Otherwise this is the
+    /// [`Axis::RelativeX`] and [`Axis::RelativeY`] axes, which report the
+    /// motion of a mouse since the last event (see [`Pointer::relative_x()`]),
+    /// such as for mouse-look in a first-person game.
+    ///
+    /// Panics if the pointer index is out of bounds.
+    #[inline]
+    pub fn relative_axis_value(&self, pointer_index: usize) -> (f32, f32) {
+        let pointer = self.pointer_at_index(pointer_index);
+        if self.is_relative() {
+            (pointer.x(), pointer.y())
+        } else {
+            (pointer.relative_x(), pointer.relative_y())
+        }
+    }
+
+    /// Returns the type of tool, such as a finger, stylus or eraser, for the
+    /// pointer at the given index
+    ///
+    /// Panics if the pointer index is out of bounds.
+    #[inline]
+    pub fn tool_type(&self, pointer_index: usize) -> ToolType {
+        self.pointer_at_index(pointer_index).tool_type()
+    }
+
     /*
     XXX: Not currently supported with GameActivity so we don't currently expose for NativeActivity
     either, for consistency.
@@ -176,6 +360,13 @@ impl<'a> MotionEvent<'a> {
         self.ndk_event.down_time()
     }
 
+    /// Returns [`Self::down_time()`] as a [`Duration`](std::time::Duration) in the
+    /// [`AndroidApp::uptime()`](crate::AndroidApp::uptime) (`CLOCK_MONOTONIC`) time base
+    #[inline]
+    pub fn down_uptime(&self) -> std::time::Duration {
+        crate::util::event_time_to_duration(self.down_time())
+    }
+
     /// Returns a bitfield indicating which edges were touched by this event.
     ///
     /// See [the NDK
@@ -187,6 +378,9 @@ impl<'a> MotionEvent<'a> {
 
     /// Returns the time of this event, in the `java.lang.System.nanoTime()` time base
     ///
+    /// See [`AndroidApp::input_event_age()`](crate::AndroidApp::input_event_age) for
+    /// calculating how long ago the event occurred.
+    ///
     /// See [the NDK
     /// docs](https://developer.android.com/ndk/reference/group/input#amotionevent_geteventtime)
     #[inline]
@@ -194,6 +388,13 @@ impl<'a> MotionEvent<'a> {
         self.ndk_event.event_time()
     }
 
+    /// Returns [`Self::event_time()`] as a [`Duration`](std::time::Duration) in the
+    /// [`AndroidApp::uptime()`](crate::AndroidApp::uptime) (`CLOCK_MONOTONIC`) time base
+    #[inline]
+    pub fn event_uptime(&self) -> std::time::Duration {
+        crate::util::event_time_to_duration(self.event_time())
+    }
+
     /// The flags associated with a motion event.
     ///
     /// See [the NDK
@@ -203,6 +404,120 @@ impl<'a> MotionEvent<'a> {
         self.ndk_event.flags().into()
     }
 
+    /// Returns `true` if this event was delivered while the window was
+    /// (partially) obscured by another visible window above it.
+    ///
+    /// Security-sensitive UIs (e.g. for payments or passwords) can use this
+    /// to ignore touches that may have been induced by an overlay
+    /// ("tapjacking").
+    ///
+    /// This is a shorthand for `self.flags().window_is_obscured()`.
+    #[inline]
+    pub fn is_obscured(&self) -> bool {
+        self.flags().window_is_obscured()
+    }
+
+    /// Returns the classification of the gesture that this event is part of,
+    /// such as a deep press or an ambiguous gesture.
+    ///
+    /// Always returns [`MotionClassification::None`] on Android versions older
+    /// than 13 (API level 33), where the NDK doesn't support this.
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#amotionevent_getclassification)
+    #[inline]
+    pub fn classification(&self) -> MotionClassification {
+        if crate::AndroidApp::sdk_version() < 33 {
+            return MotionClassification::None;
+        }
+        let Some(get_classification) = get_classification_fn() else {
+            return MotionClassification::None;
+        };
+        let classification = unsafe { get_classification(self.ndk_event.ptr().as_ptr()) as u32 };
+        classification.into()
+    }
+
+    /// Returns the number of historical samples that were batched into this
+    /// event, not counting the event's current sample
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#amotionevent_gethistorysize)
+    #[inline]
+    pub fn history_size(&self) -> usize {
+        self.ndk_event.history_size()
+    }
+
+    /// An iterator over every sample of every pointer in this event, including
+    /// the historical samples that were batched into it
+    ///
+    /// High-frequency input, such as touch drawing, may be reported at a
+    /// higher rate than events are delivered, in which case the intermediate
+    /// samples are batched into the event's history. This yields the historical
+    /// samples, oldest first, followed by the event's current sample.
+    #[inline]
+    pub fn history_iter(&self) -> MotionSamplesIter<'_> {
+        MotionSamplesIter::new(self)
+    }
+
+    pub(crate) fn sample(
+        &self,
+        history_index: Option<usize>,
+        pointer_index: usize,
+    ) -> MotionSample {
+        let ptr = self.ndk_event.ptr().as_ptr();
+        let pointer_id = unsafe { ndk_sys::AMotionEvent_getPointerId(ptr, pointer_index) };
+        unsafe {
+            match history_index {
+                Some(history_index) => MotionSample {
+                    pointer_index,
+                    pointer_id,
+                    history_index: Some(history_index),
+                    event_time: ndk_sys::AMotionEvent_getHistoricalEventTime(ptr, history_index),
+                    x: ndk_sys::AMotionEvent_getHistoricalX(ptr, pointer_index, history_index),
+                    y: ndk_sys::AMotionEvent_getHistoricalY(ptr, pointer_index, history_index),
+                    pressure: ndk_sys::AMotionEvent_getHistoricalPressure(
+                        ptr,
+                        pointer_index,
+                        history_index,
+                    ),
+                    size: ndk_sys::AMotionEvent_getHistoricalSize(
+                        ptr,
+                        pointer_index,
+                        history_index,
+                    ),
+                    touch_major: ndk_sys::AMotionEvent_getHistoricalTouchMajor(
+                        ptr,
+                        pointer_index,
+                        history_index,
+                    ),
+                    touch_minor: ndk_sys::AMotionEvent_getHistoricalTouchMinor(
+                        ptr,
+                        pointer_index,
+                        history_index,
+                    ),
+                    orientation: ndk_sys::AMotionEvent_getHistoricalOrientation(
+                        ptr,
+                        pointer_index,
+                        history_index,
+                    ),
+                },
+                None => MotionSample {
+                    pointer_index,
+                    pointer_id,
+                    history_index: None,
+                    event_time: self.event_time(),
+                    x: ndk_sys::AMotionEvent_getX(ptr, pointer_index),
+                    y: ndk_sys::AMotionEvent_getY(ptr, pointer_index),
+                    pressure: ndk_sys::AMotionEvent_getPressure(ptr, pointer_index),
+                    size: ndk_sys::AMotionEvent_getSize(ptr, pointer_index),
+                    touch_major: ndk_sys::AMotionEvent_getTouchMajor(ptr, pointer_index),
+                    touch_minor: ndk_sys::AMotionEvent_getTouchMinor(ptr, pointer_index),
+                    orientation: ndk_sys::AMotionEvent_getOrientation(ptr, pointer_index),
+                },
+            }
+        }
+    }
+
     /* Missing from GameActivity currently...
     /// Returns the offset in the x direction between the coordinates and the raw coordinates
     ///
@@ -332,6 +647,7 @@ impl<'a> KeyEvent<'a> {
 
     /// Get the source of the event.
     ///
+    /// The source may be a combination of sources (see [`Source::contains()`]).
     #[inline]
     pub fn source(&self) -> Source {
         // XXX: we use `AInputEvent_getSource` directly (instead of calling
@@ -350,6 +666,12 @@ impl<'a> KeyEvent<'a> {
         self.ndk_event.device_id()
     }
 
+    /// Get the id of the display that the event was targeted at (see [`DEFAULT_DISPLAY_ID`](crate::input::DEFAULT_DISPLAY_ID))
+    #[inline]
+    pub fn display_id(&self) -> i32 {
+        crate::input::DEFAULT_DISPLAY_ID
+    }
+
     /// Returns the key action associated with the event.
     ///
     /// See [the KeyEvent docs](https://developer.android.com/reference/android/view/KeyEvent#getAction())
@@ -362,6 +684,17 @@ impl<'a> KeyEvent<'a> {
         action.into()
     }
 
+    /// Returns the key action, decoded together with the repeat count for
+    /// [`KeyAction::Multiple`] events.
+    ///
+    /// Text handling applications should handle [`DecodedKeyAction::Multiple`]
+    /// events (by repeating the key `count` times) to avoid dropping characters
+    /// from some input methods.
+    #[inline]
+    pub fn decoded_action(&self) -> DecodedKeyAction {
+        DecodedKeyAction::new(self.action(), self.repeat_count())
+    }
+
     /// Returns the last time the key was pressed.  This is on the scale of
     /// `java.lang.System.nanoTime()`, which has nanosecond precision, but no defined start time.
     ///
@@ -372,9 +705,19 @@ impl<'a> KeyEvent<'a> {
         self.ndk_event.down_time()
     }
 
+    /// Returns [`Self::down_time()`] as a [`Duration`](std::time::Duration) in the
+    /// [`AndroidApp::uptime()`](crate::AndroidApp::uptime) (`CLOCK_MONOTONIC`) time base
+    #[inline]
+    pub fn down_uptime(&self) -> std::time::Duration {
+        crate::util::event_time_to_duration(self.down_time())
+    }
+
     /// Returns the time this event occured.  This is on the scale of
     /// `java.lang.System.nanoTime()`, which has nanosecond precision, but no defined start time.
     ///
+    /// See [`AndroidApp::input_event_age()`](crate::AndroidApp::input_event_age) for
+    /// calculating how long ago the event occurred.
+    ///
     /// See [the NDK
     /// docs](https://developer.android.com/ndk/reference/group/input#akeyevent_geteventtime)
     #[inline]
@@ -382,6 +725,13 @@ impl<'a> KeyEvent<'a> {
         self.ndk_event.event_time()
     }
 
+    /// Returns [`Self::event_time()`] as a [`Duration`](std::time::Duration) in the
+    /// [`AndroidApp::uptime()`](crate::AndroidApp::uptime) (`CLOCK_MONOTONIC`) time base
+    #[inline]
+    pub fn event_uptime(&self) -> std::time::Duration {
+        crate::util::event_time_to_duration(self.event_time())
+    }
+
     /// Returns the keycode associated with this key event
     ///
     /// See [the NDK
@@ -405,8 +755,22 @@ impl<'a> KeyEvent<'a> {
         self.ndk_event.repeat_count()
     }
 
+    /// Returns `true` if this is an auto-repeat of a key that's being held down
+    ///
+    /// This is equivalent to `repeat_count() > 0`. Games may want to ignore
+    /// repeats for movement keys, while text fields should normally honor
+    /// them.
+    #[inline]
+    pub fn is_repeat(&self) -> bool {
+        self.repeat_count() > 0
+    }
+
     /// Returns the hardware keycode of a key.  This varies from device to device.
     ///
+    /// Unlike [`Self::key_code()`], which depends on the keyboard layout, the scan code
+    /// identifies the physical key that was pressed, which can be useful for mapping
+    /// keys by position (such as for emulators or key remapping).
+    ///
     /// See [the NDK
     /// docs](https://developer.android.com/ndk/reference/group/input#akeyevent_getscancode)
     #[inline]
@@ -414,6 +778,14 @@ impl<'a> KeyEvent<'a> {
         self.ndk_event.scan_code()
     }
 
+    /// Flags associated with this [`KeyEvent`].
+    ///
+    /// See [the NDK docs](https://developer.android.com/ndk/reference/group/input#akeyevent_getflags)
+    #[inline]
+    pub fn flags(&self) -> KeyEventFlags {
+        self.ndk_event.flags().into()
+    }
+
     /// Returns the state of the modifiers during this key event, represented by a bitmask.
     ///
     /// See [the NDK
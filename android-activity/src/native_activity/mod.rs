@@ -5,25 +5,39 @@ use std::marker::PhantomData;
 use std::panic::AssertUnwindSafe;
 use std::ptr;
 use std::ptr::NonNull;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, RwLock, Weak};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use libc::c_void;
 use log::{error, trace};
+use ndk::configuration::Configuration;
 use ndk::input_queue::InputQueue;
 use ndk::{asset::AssetManager, native_window::NativeWindow};
 
 use crate::error::InternalResult;
+use crate::frame_callback::FrameCallbacks;
 use crate::input::{Axis, KeyCharacterMap, KeyCharacterMapBinding};
 use crate::input::{TextInputState, TextSpan};
+use crate::input_devices::InputDeviceTracker;
 use crate::jni_utils::{self, CloneJavaVM};
+use crate::keep_screen_on::KeepScreenOn;
+use crate::listener::JavaListeners;
+use crate::notify::{LooperNotifier, Notifications, LOOPER_ID_NOTIFY};
+use crate::stats::LoopStatsCounters;
+use crate::visibility::VisibilityState;
+use crate::window_size::WindowSizeTracker;
 use crate::{
-    util, AndroidApp, ConfigurationRef, InputStatus, MainEvent, PollEvent, Rect, WindowManagerFlags,
+    util, AndroidApp, AnrRiskCallbackFn, Backend, ConfigChange, ConfigurationRef, CustomCmdHandler,
+    CustomCmdHandlerFn, DisplayMode, InputStatus, LifecycleState, MainEvent, PollEvent, Rect,
+    StreamType, TrimMemoryLevel, Visibility, WindowInsets, WindowManagerFlags,
 };
 
 pub mod input;
 
 mod glue;
+mod handoff;
+pub use self::glue::NativeActivityCallbacks;
 use self::glue::NativeActivityGlue;
 
 pub const LOOPER_ID_MAIN: libc::c_int = 1;
@@ -44,6 +58,17 @@ impl<'a> StateSaver<'a> {
     pub fn store(&self, state: &'a [u8]) {
         self.app.native_activity.set_saved_state(state);
     }
+
+    /// Stores `len` bytes of state that are written in place by `write`, such
+    /// that it will be available to load the next time that the application
+    /// resumes.
+    ///
+    /// The state buffer is reused between [MainEvent::SaveState] events, so
+    /// this avoids allocating (and copying) when saving state of the same size
+    /// repeatedly, such as on every pause.
+    pub fn store_with(&self, len: usize, write: impl FnOnce(&mut [u8])) {
+        self.app.native_activity.set_saved_state_with(len, write);
+    }
 }
 
 /// An interface for loading application state during [MainEvent::Resume] events
@@ -58,6 +83,12 @@ impl<'a> StateLoader<'a> {
     pub fn load(&self) -> Option<Vec<u8>> {
         self.app.native_activity.saved_state()
     }
+
+    /// Returns the length of whatever state was saved during the last
+    /// [MainEvent::SaveState] event, without copying it
+    pub fn saved_state_len(&self) -> usize {
+        self.app.native_activity.saved_state_len()
+    }
 }
 
 /// A means to wake up the main thread while it is blocked waiting for I/O
@@ -96,40 +127,66 @@ impl AndroidApp {
             }
         };
 
-        let app = Self {
+        let font_scale = Self::read_font_scale(&jvm, &native_activity);
+        native_activity.config().set_font_scale(font_scale);
+
+        let main_fd = native_activity.cmd_read_fd();
+        let (looper, notifier) = unsafe {
+            let looper = ndk_sys::ALooper_prepare(
+                ndk_sys::ALOOPER_PREPARE_ALLOW_NON_CALLBACKS as libc::c_int,
+            );
+            ndk_sys::ALooper_addFd(
+                looper,
+                main_fd,
+                LOOPER_ID_MAIN,
+                ndk_sys::ALOOPER_EVENT_INPUT as libc::c_int,
+                None,
+                //&mut guard.cmd_poll_source as *mut _ as *mut _);
+                ptr::null_mut(),
+            );
+            (looper, LooperNotifier::new(looper))
+        };
+        let java_listeners =
+            JavaListeners::register(&jvm, unsafe { (*native_activity.activity).clazz as _ });
+
+        Self {
             inner: Arc::new(RwLock::new(AndroidAppInner {
                 jvm,
                 native_activity,
-                looper: Looper {
-                    ptr: ptr::null_mut(),
-                },
+                looper: Looper { ptr: looper },
                 key_map_binding: Arc::new(key_map_binding),
                 key_maps: Mutex::new(HashMap::new()),
                 input_receiver: Mutex::new(None),
+                stats: Default::default(),
+                start_uptime: crate::AndroidApp::uptime(),
+                keep_screen_on: Default::default(),
+                visibility: Default::default(),
+                terminated_window_size: Mutex::new(None),
+                window_size: Default::default(),
+                input_devices: Default::default(),
+                frame_callbacks: FrameCallbacks::new(notifier.clone()),
+                notifier,
+                java_listeners,
+                display_mode: Mutex::new(None),
+                custom_cmd_handler: RwLock::new(None),
+                interactive: AtomicBool::new(true),
+                pointer_capture_requested: AtomicBool::new(false),
+                clip_timestamp: Mutex::new(None),
+                stream_volumes: Mutex::new(None),
+                window_insets: Mutex::new(None),
             })),
-        };
-
-        {
-            let mut guard = app.inner.write().unwrap();
+        }
+    }
 
-            let main_fd = guard.native_activity.cmd_read_fd();
-            unsafe {
-                guard.looper.ptr = ndk_sys::ALooper_prepare(
-                    ndk_sys::ALOOPER_PREPARE_ALLOW_NON_CALLBACKS as libc::c_int,
-                );
-                ndk_sys::ALooper_addFd(
-                    guard.looper.ptr,
-                    main_fd,
-                    LOOPER_ID_MAIN,
-                    ndk_sys::ALOOPER_EVENT_INPUT as libc::c_int,
-                    None,
-                    //&mut guard.cmd_poll_source as *mut _ as *mut _);
-                    ptr::null_mut(),
-                );
+    fn read_font_scale(jvm: &CloneJavaVM, native_activity: &NativeActivityGlue) -> f32 {
+        let activity = unsafe { (*native_activity.activity).clazz };
+        match jni_utils::activity_font_scale(jvm, activity as _) {
+            Ok(scale) => scale,
+            Err(err) => {
+                error!("Failed to query font scale: {err:?}");
+                1.0
             }
         }
-
-        app
     }
 }
 
@@ -159,6 +216,64 @@ pub(crate) struct AndroidAppInner {
     /// InputReceiver reference which we track to ensure
     /// we don't hand out more than one receiver at a time
     input_receiver: Mutex<Option<Weak<InputReceiver>>>,
+
+    /// Event loop statistics, for profiling
+    pub(crate) stats: Arc<LoopStatsCounters>,
+
+    /// The [`AndroidApp::uptime()`] when this `AndroidApp` was created, as a
+    /// base for `event_time_since_start()`
+    pub(crate) start_uptime: Duration,
+
+    /// Tracks when to set `FLAG_KEEP_SCREEN_ON` for `set_keep_screen_on()`
+    keep_screen_on: KeepScreenOn,
+
+    /// Lifecycle and focus state for `visibility()`
+    visibility: VisibilityState,
+
+    /// The size of the last terminated window, for synthesizing `SurfaceRecreated`
+    /// events when a new window replaces it
+    terminated_window_size: Mutex<Option<(i32, i32)>>,
+
+    /// The last window size reported to the application, for coalescing
+    /// resizes that come with a configuration change
+    window_size: WindowSizeTracker,
+
+    /// The attached input devices, for `InputDeviceAdded`, `InputDeviceRemoved`
+    /// and `InputDeviceChanged` events
+    input_devices: InputDeviceTracker,
+
+    /// Requested vsync callbacks, for `MainEvent::Frame` events
+    frame_callbacks: Arc<FrameCallbacks>,
+
+    /// Notifies the looper of frame callbacks and other synthesized events
+    notifier: Arc<LooperNotifier>,
+
+    /// Java listeners that notify the looper, such as for `ScreenStateChanged`
+    /// events
+    java_listeners: JavaListeners,
+
+    /// The last known display mode, for `DisplayModeChanged` events
+    display_mode: Mutex<Option<DisplayMode>>,
+
+    /// Handler for command bytes from `AndroidApp::CUSTOM_CMD_MIN` upwards
+    custom_cmd_handler: RwLock<Option<CustomCmdHandler>>,
+
+    /// The last known `PowerManager.isInteractive()` state, for `ScreenStateChanged` events
+    interactive: AtomicBool,
+
+    /// Whether pointer capture has been requested (and not released), for
+    /// `PointerCaptureChanged` events when the system releases it
+    pub(crate) pointer_capture_requested: AtomicBool,
+
+    /// The last known timestamp of the primary clip (`0` if there was none), for
+    /// `ClipboardChanged` events, or `None` before the window has first gained focus
+    clip_timestamp: Mutex<Option<i64>>,
+
+    /// The last known volumes of `StreamType::TRACKED`, for `VolumeChanged` events
+    stream_volumes: Mutex<Option<Vec<i32>>>,
+
+    /// The last known window insets, for synthesizing `InsetsChanged` events
+    window_insets: Mutex<Option<WindowInsets>>,
 }
 
 impl AndroidAppInner {
@@ -166,6 +281,10 @@ impl AndroidAppInner {
         unsafe { (*self.native_activity.activity).vm as _ }
     }
 
+    pub fn native_activity_sdk_version(&self) -> i32 {
+        unsafe { (*self.native_activity.activity).sdkVersion }
+    }
+
     pub(crate) fn activity_as_ptr(&self) -> *mut c_void {
         // "clazz" is a completely bogus name; this is the _instance_ not class pointer
         unsafe { (*self.native_activity.activity).clazz as _ }
@@ -180,7 +299,13 @@ impl AndroidAppInner {
     }
 
     pub fn native_window(&self) -> Option<NativeWindow> {
-        self.native_activity.mutex.lock().unwrap().window.clone()
+        self.native_activity
+            .mutex
+            .lock()
+            .unwrap()
+            .window
+            .current()
+            .clone()
     }
 
     pub fn poll_events<F>(&self, timeout: Option<Duration>, mut callback: F)
@@ -205,12 +330,17 @@ impl AndroidAppInner {
                 !ndk_sys::ALooper_forThread().is_null(),
                 "Application tried to poll events from non-main thread"
             );
+            self.frame_callbacks.post_requested();
+            let poll_start = Instant::now();
             let id = ndk_sys::ALooper_pollAll(
                 timeout_milliseconds,
                 &mut fd,
                 &mut events,
                 &mut source as *mut *mut c_void,
             );
+            if timeout_milliseconds != 0 {
+                self.stats.blocked(poll_start.elapsed());
+            }
             trace!("pollAll id = {id}");
             match id {
                 ndk_sys::ALOOPER_POLL_WAKE => {
@@ -235,7 +365,17 @@ impl AndroidAppInner {
                     match id {
                         LOOPER_ID_MAIN => {
                             trace!("ALooper_pollAll returned ID_MAIN");
-                            if let Some(ipc_cmd) = self.native_activity.read_cmd() {
+                            let ipc_cmd = match self.native_activity.read_cmd() {
+                                Some(glue::PipeCmd::App(ipc_cmd)) => Some(ipc_cmd),
+                                Some(glue::PipeCmd::Custom(cmd)) => {
+                                    self.stats.command_processed();
+                                    self.handle_custom_cmd(cmd);
+                                    None
+                                }
+                                None => None,
+                            };
+                            if let Some(ipc_cmd) = ipc_cmd {
+                                self.stats.command_processed();
                                 let main_cmd = match ipc_cmd {
                                     // We don't forward info about the AInputQueue to apps since it's
                                     // an implementation details that's also not compatible with
@@ -244,9 +384,8 @@ impl AndroidAppInner {
 
                                     glue::AppCmd::InitWindow => Some(MainEvent::InitWindow {}),
                                     glue::AppCmd::TermWindow => Some(MainEvent::TerminateWindow {}),
-                                    glue::AppCmd::WindowResized => {
-                                        Some(MainEvent::WindowResized {})
-                                    }
+                                    // Created below, once the new window size is known
+                                    glue::AppCmd::WindowResized => None,
                                     glue::AppCmd::WindowRedrawNeeded => {
                                         Some(MainEvent::RedrawNeeded {})
                                     }
@@ -255,10 +394,10 @@ impl AndroidAppInner {
                                     }
                                     glue::AppCmd::GainedFocus => Some(MainEvent::GainedFocus),
                                     glue::AppCmd::LostFocus => Some(MainEvent::LostFocus),
-                                    glue::AppCmd::ConfigChanged => {
-                                        Some(MainEvent::ConfigChanged {})
-                                    }
+                                    // Created after `pre_exec_cmd` has loaded the new configuration
+                                    glue::AppCmd::ConfigChanged => None,
                                     glue::AppCmd::LowMemory => Some(MainEvent::LowMemory),
+                                    glue::AppCmd::Restart => Some(MainEvent::Restart),
                                     glue::AppCmd::Start => Some(MainEvent::Start),
                                     glue::AppCmd::Resume => Some(MainEvent::Resume {
                                         loader: StateLoader { app: self },
@@ -271,22 +410,214 @@ impl AndroidAppInner {
                                     glue::AppCmd::Destroy => Some(MainEvent::Destroy),
                                 };
 
+                                let previous_config = (ipc_cmd == glue::AppCmd::ConfigChanged)
+                                    .then(|| self.config().copy());
+
                                 trace!("Calling pre_exec_cmd({ipc_cmd:#?})");
                                 self.native_activity.pre_exec_cmd(
                                     ipc_cmd,
                                     self.looper(),
                                     LOOPER_ID_INPUT,
                                 );
+                                match ipc_cmd {
+                                    glue::AppCmd::Start => self.visibility.set_started(true),
+                                    glue::AppCmd::Stop => self.visibility.set_started(false),
+                                    glue::AppCmd::GainedFocus => self.visibility.set_focused(true),
+                                    glue::AppCmd::LostFocus => self.visibility.set_focused(false),
+                                    _ => {}
+                                }
+                                let keep_screen_on_flags = match ipc_cmd {
+                                    glue::AppCmd::Resume => self.keep_screen_on.set_resumed(true),
+                                    glue::AppCmd::Pause => self.keep_screen_on.set_resumed(false),
+                                    glue::AppCmd::GainedFocus => {
+                                        self.keep_screen_on.set_focused(true)
+                                    }
+                                    glue::AppCmd::LostFocus => {
+                                        self.keep_screen_on.set_focused(false)
+                                    }
+                                    _ => None,
+                                };
+                                if let Some((add_flags, remove_flags)) = keep_screen_on_flags {
+                                    self.set_window_flags(add_flags, remove_flags);
+                                }
+                                let config_changes = match &previous_config {
+                                    Some(previous_config) => {
+                                        self.config().diff(previous_config)
+                                            | self.update_font_scale()
+                                    }
+                                    None => ConfigChange::empty(),
+                                };
+
+                                let window_size = self
+                                    .native_window()
+                                    .map(|window| (window.width(), window.height()));
+                                let mut config_resized = None;
+                                let main_cmd = match ipc_cmd {
+                                    glue::AppCmd::InitWindow => {
+                                        self.window_size.window_initialized(window_size);
+                                        main_cmd
+                                    }
+                                    glue::AppCmd::TermWindow => {
+                                        self.window_size.window_terminated();
+                                        main_cmd
+                                    }
+                                    glue::AppCmd::WindowResized => {
+                                        if self.window_size.window_resized(window_size) {
+                                            let (width, height) = window_size.unwrap_or_default();
+                                            Some(MainEvent::WindowResized { width, height })
+                                        } else {
+                                            trace!("Ignoring WindowResized already reported with ConfigChanged");
+                                            None
+                                        }
+                                    }
+                                    glue::AppCmd::ConfigChanged => {
+                                        if self.window_size.config_changed(window_size) {
+                                            let (width, height) = window_size.unwrap_or_default();
+                                            config_resized =
+                                                Some(MainEvent::WindowResized { width, height });
+                                        }
+                                        let config = self.config();
+                                        Some(MainEvent::ConfigChanged {
+                                            changes: config_changes,
+                                            screen_width_dp: config.screen_width_dp(),
+                                            screen_height_dp: config.screen_height_dp(),
+                                        })
+                                    }
+                                    _ => main_cmd,
+                                };
+
+                                let surface_recreated = match ipc_cmd {
+                                    glue::AppCmd::InitWindow => self.track_window_recreation(true),
+                                    glue::AppCmd::TermWindow => self.track_window_recreation(false),
+                                    glue::AppCmd::Destroy => {
+                                        *self.terminated_window_size.lock().unwrap() = None;
+                                        self.java_listeners
+                                            .unregister(&self.jvm, self.activity_as_ptr() as _);
+                                        None
+                                    }
+                                    _ => None,
+                                };
+
+                                let screen_state_changed = match ipc_cmd {
+                                    glue::AppCmd::Start
+                                    | glue::AppCmd::Stop
+                                    | glue::AppCmd::Pause
+                                    | glue::AppCmd::Resume
+                                    | glue::AppCmd::LostFocus
+                                    | glue::AppCmd::GainedFocus => self.check_screen_state(),
+                                    _ => None,
+                                };
+                                if let Some(screen_state_changed) = screen_state_changed {
+                                    trace!(
+                                        "Invoking callback for synthesized ScreenStateChanged event"
+                                    );
+                                    callback(PollEvent::Main(screen_state_changed));
+                                }
+
+                                let clipboard_changed = match ipc_cmd {
+                                    glue::AppCmd::GainedFocus => self.check_clipboard(),
+                                    _ => None,
+                                };
+
+                                // The system releases pointer capture when the window loses focus
+                                let pointer_capture_lost = ipc_cmd == glue::AppCmd::LostFocus
+                                    && self.pointer_capture_requested.swap(false, Ordering::SeqCst);
+
+                                let volumes_changed = match ipc_cmd {
+                                    glue::AppCmd::Resume | glue::AppCmd::GainedFocus => {
+                                        self.check_volumes()
+                                    }
+                                    _ => Vec::new(),
+                                };
+
+                                let input_devices_changed = match ipc_cmd {
+                                    glue::AppCmd::Resume | glue::AppCmd::GainedFocus => {
+                                        self.check_input_devices()
+                                    }
+                                    _ => Vec::new(),
+                                };
+
+                                let display_mode_changed = match ipc_cmd {
+                                    glue::AppCmd::Resume
+                                    | glue::AppCmd::GainedFocus
+                                    | glue::AppCmd::ConfigChanged
+                                    | glue::AppCmd::WindowResized => self.check_display_mode(),
+                                    _ => None,
+                                };
+
+                                let config_changed = match ipc_cmd {
+                                    glue::AppCmd::Resume | glue::AppCmd::GainedFocus => {
+                                        self.check_config()
+                                    }
+                                    _ => None,
+                                };
+
+                                let insets_changed = match ipc_cmd {
+                                    glue::AppCmd::ContentRectChanged => self.check_insets(),
+                                    _ => Vec::new(),
+                                };
 
                                 if let Some(main_cmd) = main_cmd {
                                     trace!("Invoking callback for ID_MAIN command = {main_cmd:?}");
                                     callback(PollEvent::Main(main_cmd));
                                 }
+                                if ipc_cmd == glue::AppCmd::LowMemory {
+                                    trace!("Invoking callback for synthesized TrimMemory event");
+                                    callback(PollEvent::Main(MainEvent::TrimMemory {
+                                        level: TrimMemoryLevel::Complete,
+                                    }));
+                                }
+                                for insets_changed in insets_changed {
+                                    trace!("Invoking callback for synthesized {insets_changed:?} event");
+                                    callback(PollEvent::Main(insets_changed));
+                                }
+                                if let Some(config_resized) = config_resized {
+                                    trace!("Invoking callback for synthesized WindowResized event");
+                                    callback(PollEvent::Main(config_resized));
+                                }
+                                if let Some(config_changed) = config_changed {
+                                    trace!("Invoking callback for synthesized ConfigChanged event");
+                                    callback(PollEvent::Main(config_changed));
+                                }
+                                for volume_changed in volumes_changed {
+                                    trace!("Invoking callback for synthesized VolumeChanged event");
+                                    callback(PollEvent::Main(volume_changed));
+                                }
+                                for input_device_changed in input_devices_changed {
+                                    trace!("Invoking callback for synthesized {input_device_changed:?} event");
+                                    callback(PollEvent::Main(input_device_changed));
+                                }
+                                if let Some(display_mode_changed) = display_mode_changed {
+                                    trace!("Invoking callback for synthesized DisplayModeChanged event");
+                                    callback(PollEvent::Main(display_mode_changed));
+                                }
+                                if pointer_capture_lost {
+                                    trace!("Invoking callback for synthesized PointerCaptureChanged event");
+                                    callback(PollEvent::Main(MainEvent::PointerCaptureChanged {
+                                        captured: false,
+                                    }));
+                                }
+                                if let Some(clipboard_changed) = clipboard_changed {
+                                    trace!(
+                                        "Invoking callback for synthesized ClipboardChanged event"
+                                    );
+                                    callback(PollEvent::Main(clipboard_changed));
+                                }
+                                if let Some(surface_recreated) = surface_recreated {
+                                    trace!(
+                                        "Invoking callback for synthesized SurfaceRecreated event"
+                                    );
+                                    callback(PollEvent::Main(surface_recreated));
+                                }
 
                                 trace!("Calling post_exec_cmd({ipc_cmd:#?})");
                                 self.native_activity.post_exec_cmd(ipc_cmd);
                             }
                         }
+                        LOOPER_ID_NOTIFY => {
+                            trace!("ALooper_pollAll returned ID_NOTIFY");
+                            self.handle_notifications(&mut callback);
+                        }
                         LOOPER_ID_INPUT => {
                             trace!("ALooper_pollAll returned ID_INPUT");
 
@@ -309,6 +640,83 @@ impl AndroidAppInner {
         }
     }
 
+    /// Delivers the events for notifications from other threads, such as frame
+    /// callbacks, after the looper has returned `LOOPER_ID_NOTIFY`
+    fn handle_notifications<F>(&self, callback: &mut F)
+    where
+        F: FnMut(PollEvent<'_>),
+    {
+        let notifications = self.notifier.take();
+        if notifications.contains(Notifications::SCREEN_STATE) {
+            if let Some(screen_state_changed) = self.check_screen_state() {
+                trace!("Invoking callback for synthesized ScreenStateChanged event");
+                callback(PollEvent::Main(screen_state_changed));
+            }
+        }
+        if notifications.contains(Notifications::CONFIG) {
+            if let Some(config_changed) = self.check_config() {
+                trace!("Invoking callback for synthesized ConfigChanged event");
+                callback(PollEvent::Main(config_changed));
+            }
+        }
+        if notifications.contains(Notifications::INPUT_DEVICES) {
+            for input_device_changed in self.check_input_devices() {
+                trace!("Invoking callback for synthesized {input_device_changed:?} event");
+                callback(PollEvent::Main(input_device_changed));
+            }
+        }
+        if notifications.contains(Notifications::VOLUME) {
+            for volume_changed in self.check_volumes() {
+                trace!("Invoking callback for synthesized VolumeChanged event");
+                callback(PollEvent::Main(volume_changed));
+            }
+        }
+        if notifications.contains(Notifications::CLIPBOARD) {
+            trace!("Invoking callback for synthesized ClipboardChanged event");
+            callback(PollEvent::Main(self.on_clip_changed()));
+        }
+        if notifications.contains(Notifications::REFRESH_RATE) {
+            if let Some(display_mode_changed) = self.check_display_mode() {
+                trace!("Invoking callback for synthesized DisplayModeChanged event");
+                callback(PollEvent::Main(display_mode_changed));
+            }
+        }
+        if notifications.contains(Notifications::FRAME) {
+            if let Some(frame_time_nanos) = self.frame_callbacks.take_frame() {
+                trace!("Invoking callback for synthesized Frame event");
+                callback(PollEvent::Main(MainEvent::Frame { frame_time_nanos }));
+            }
+        }
+    }
+
+    pub fn set_anr_risk_callback(&self, callback: Option<(Duration, AnrRiskCallbackFn)>) {
+        self.native_activity
+            .set_anr_risk_callback(callback.map(|(threshold, callback)| glue::AnrRiskCallback {
+                threshold,
+                callback,
+            }));
+    }
+
+    pub fn set_custom_cmd_handler(&self, handler: Option<CustomCmdHandlerFn>) {
+        *self.custom_cmd_handler.write().unwrap() = handler.map(CustomCmdHandler);
+    }
+
+    fn handle_custom_cmd(&self, cmd: u8) {
+        // Clone the handler so it's not called while holding the lock, in case
+        // it (un)registers a handler itself
+        let handler = self.custom_cmd_handler.read().unwrap().clone();
+        if let Some(handler) = handler {
+            trace!("Invoking custom command handler for cmd = {cmd}");
+            (handler.0)(cmd);
+        } else {
+            error!("Ignoring custom command {cmd} with no registered handler");
+        }
+    }
+
+    pub fn backend(&self) -> Backend {
+        Backend::NativeActivity
+    }
+
     pub fn create_waker(&self) -> AndroidAppWaker {
         unsafe {
             // From the application's pov we assume the looper pointer has a static
@@ -319,14 +727,53 @@ impl AndroidAppInner {
         }
     }
 
+    pub fn wake(&self) {
+        unsafe {
+            ndk_sys::ALooper_wake(self.looper.ptr);
+        }
+    }
+
+    pub fn request_frame_callback(&self) -> bool {
+        if !self.frame_callbacks.request() {
+            return false;
+        }
+        // The request is posted from `poll_events()`, so wake up the main
+        // thread if it may be blocked waiting for events
+        if unsafe { ndk_sys::ALooper_forThread() }.is_null() {
+            self.wake();
+        }
+        true
+    }
+
     pub fn config(&self) -> ConfigurationRef {
         self.native_activity.config()
     }
 
+    pub fn font_scale(&self) -> f32 {
+        self.config().font_scale()
+    }
+
     pub fn content_rect(&self) -> Rect {
         self.native_activity.content_rect()
     }
 
+    pub fn ime_insets(&self) -> Rect {
+        if AndroidApp::sdk_version() < 30 {
+            return Rect::empty();
+        }
+        match jni_utils::window_insets(
+            &self.jvm,
+            self.activity_as_ptr() as _,
+            AndroidApp::sdk_version(),
+        ) {
+            Ok(insets) => insets.ime,
+            Err(err) => {
+                error!("Failed to query IME insets: {err:?}");
+                Rect::empty()
+            }
+        }
+    }
+
     pub fn asset_manager(&self) -> AssetManager {
         unsafe {
             let activity_ptr = self.native_activity.activity;
@@ -351,29 +798,263 @@ impl AndroidAppInner {
         }
     }
 
+    pub fn set_keep_screen_on(&self, keep_on: bool) {
+        if let Some((add_flags, remove_flags)) = self.keep_screen_on.set_requested(keep_on) {
+            self.set_window_flags(add_flags, remove_flags);
+        }
+    }
+
+    pub fn visibility(&self) -> Visibility {
+        self.visibility.get()
+    }
+
+    pub fn is_window_focused(&self) -> bool {
+        self.visibility.is_focused()
+    }
+
+    pub fn lifecycle_state(&self) -> LifecycleState {
+        match self.native_activity.activity_state() {
+            glue::State::Init => LifecycleState::Init,
+            glue::State::Start => LifecycleState::Start,
+            glue::State::Resume => LifecycleState::Resume,
+            glue::State::Pause => LifecycleState::Pause,
+            glue::State::Stop => LifecycleState::Stop,
+        }
+    }
+
+    /// Tracks window termination + re-initialization so we can synthesize a
+    /// `SurfaceRecreated` event after an `InitWindow` that replaces a window
+    fn track_window_recreation(&self, init: bool) -> Option<MainEvent<'static>> {
+        let mut terminated_size = self.terminated_window_size.lock().unwrap();
+        let window_size = self
+            .native_window()
+            .map(|window| (window.width(), window.height()));
+        if init {
+            let old_size = terminated_size.take()?;
+            let new_size = window_size?;
+            Some(MainEvent::SurfaceRecreated { old_size, new_size })
+        } else {
+            *terminated_size = window_size;
+            None
+        }
+    }
+
+    /// Re-reads the configuration from the `AssetManager` so we can synthesize
+    /// a `ConfigChanged` event for changes that weren't reported via
+    /// `onConfigurationChanged`
+    ///
+    /// This is checked when our `ComponentCallbacks2` are notified of an
+    /// application-level change, and whenever the Activity resumes or gains
+    /// focus.
+    fn check_config(&self) -> Option<MainEvent<'static>> {
+        let latest = Configuration::from_asset_manager(&self.asset_manager());
+        let config = self.config();
+        let changes = config.diff(&latest) | self.update_font_scale();
+        if changes.is_empty() {
+            return None;
+        }
+        config.replace(latest);
+        Some(MainEvent::ConfigChanged {
+            changes,
+            screen_width_dp: config.screen_width_dp(),
+            screen_height_dp: config.screen_height_dp(),
+        })
+    }
+
+    /// Re-reads the font scale after a configuration change, returning
+    /// `ConfigChange::FONT_SCALE` if it changed
+    fn update_font_scale(&self) -> ConfigChange {
+        let font_scale = AndroidApp::read_font_scale(&self.jvm, &self.native_activity);
+        self.config().set_font_scale(font_scale)
+    }
+
+    /// Checks the attached input devices so we can synthesize
+    /// `InputDeviceAdded`, `InputDeviceRemoved` and `InputDeviceChanged` events
+    fn check_input_devices(&self) -> Vec<MainEvent<'static>> {
+        match jni_utils::input_device_sources(&self.jvm) {
+            Ok(devices) => self.input_devices.update(devices),
+            Err(err) => {
+                error!("Failed to query input devices: {err:?}");
+                Vec::new()
+            }
+        }
+    }
+
+    /// Checks the display's current mode so we can synthesize
+    /// `DisplayModeChanged` events
+    fn check_display_mode(&self) -> Option<MainEvent<'static>> {
+        if AndroidApp::sdk_version() < 23 {
+            return None;
+        }
+        let mode = match jni_utils::display_mode(&self.jvm, self.activity_as_ptr() as _) {
+            Ok(mode) => mode,
+            Err(err) => {
+                error!("Failed to query display mode: {err:?}");
+                return None;
+            }
+        };
+        let previous = self.display_mode.lock().unwrap().replace(mode.clone());
+        match previous {
+            Some(previous) if previous != mode => Some(MainEvent::DisplayModeChanged { mode }),
+            _ => None,
+        }
+    }
+
+    /// Checks the volume of each `StreamType::TRACKED` stream so we can
+    /// synthesize `VolumeChanged` events
+    fn check_volumes(&self) -> Vec<MainEvent<'static>> {
+        let streams = StreamType::TRACKED.map(i32::from);
+        let volumes =
+            match jni_utils::stream_volumes(&self.jvm, self.activity_as_ptr() as _, &streams) {
+                Ok(volumes) => volumes,
+                Err(err) => {
+                    error!("Failed to query stream volumes: {err:?}");
+                    return Vec::new();
+                }
+            };
+        let previous = self.stream_volumes.lock().unwrap().replace(volumes.clone());
+        let Some(previous) = previous else {
+            return Vec::new();
+        };
+        StreamType::TRACKED
+            .iter()
+            .zip(previous.iter().zip(&volumes))
+            .filter(|(_, (previous, volume))| previous != volume)
+            .map(|(&stream, _)| MainEvent::VolumeChanged { stream })
+            .collect()
+    }
+
+    /// Checks for changes to the window insets, since NativeActivity doesn't
+    /// have a callback for them, returning an `InsetsChanged` event, followed
+    /// by an `ImeVisibilityChanged` event if the IME insets changed
+    fn check_insets(&self) -> Vec<MainEvent<'static>> {
+        if AndroidApp::sdk_version() < 23 {
+            return Vec::new();
+        }
+        let insets = match jni_utils::window_insets(
+            &self.jvm,
+            self.activity_as_ptr() as _,
+            AndroidApp::sdk_version(),
+        ) {
+            Ok(insets) => insets,
+            Err(err) => {
+                error!("Failed to query window insets: {err:?}");
+                return Vec::new();
+            }
+        };
+        let previous = self.window_insets.lock().unwrap().replace(insets.clone());
+        let mut events = Vec::new();
+        if previous.as_ref() != Some(&insets) {
+            events.push(MainEvent::InsetsChanged {});
+        }
+        let previous_ime = previous.map_or_else(Rect::empty, |previous| previous.ime);
+        if previous_ime != insets.ime {
+            events.push(MainEvent::ImeVisibilityChanged {
+                visible: insets.ime != Rect::empty(),
+                insets: insets.ime,
+            });
+        }
+        events
+    }
+
+    /// Checks for changes to the primary clip, which is only accessible while
+    /// the window is focused
+    fn check_clipboard(&self) -> Option<MainEvent<'static>> {
+        if AndroidApp::sdk_version() < 26 {
+            return None;
+        }
+        let timestamp =
+            match jni_utils::primary_clip_timestamp(&self.jvm, self.activity_as_ptr() as _) {
+                Ok(timestamp) => timestamp.unwrap_or(0),
+                Err(err) => {
+                    error!("Failed to query primary clip: {err:?}");
+                    return None;
+                }
+            };
+        let previous = self.clip_timestamp.lock().unwrap().replace(timestamp);
+        matches!(previous, Some(previous) if previous != timestamp)
+            .then_some(MainEvent::ClipboardChanged)
+    }
+
+    /// Handles a notification from our `OnPrimaryClipChangedListener`
+    ///
+    /// The new timestamp is recorded (if it's accessible) so that the change
+    /// isn't reported again when the window next gains focus.
+    fn on_clip_changed(&self) -> MainEvent<'static> {
+        if AndroidApp::sdk_version() >= 26 {
+            match jni_utils::primary_clip_timestamp(&self.jvm, self.activity_as_ptr() as _) {
+                Ok(timestamp) => {
+                    *self.clip_timestamp.lock().unwrap() = Some(timestamp.unwrap_or(0));
+                }
+                Err(err) => error!("Failed to query primary clip: {err:?}"),
+            }
+        }
+        MainEvent::ClipboardChanged
+    }
+
+    /// Checks `PowerManager.isInteractive()` so we can synthesize a
+    /// `ScreenStateChanged` event if the screen has turned off or on
+    fn check_screen_state(&self) -> Option<MainEvent<'static>> {
+        let interactive = match jni_utils::is_interactive(&self.jvm, self.activity_as_ptr() as _) {
+            Ok(interactive) => interactive,
+            Err(err) => {
+                error!("Failed to query whether the screen is interactive: {err:?}");
+                return None;
+            }
+        };
+        let previous = self.interactive.swap(interactive, Ordering::SeqCst);
+        (previous != interactive).then_some(MainEvent::ScreenStateChanged { interactive })
+    }
+
     // TODO: move into a trait
     pub fn show_soft_input(&self, show_implicit: bool) {
-        let na = self.native_activity();
-        unsafe {
-            let flags = if show_implicit {
-                ndk_sys::ANATIVEACTIVITY_SHOW_SOFT_INPUT_IMPLICIT
-            } else {
-                0
-            };
-            ndk_sys::ANativeActivity_showSoftInput(na as *mut _, flags);
+        let flags = if show_implicit {
+            ndk_sys::ANATIVEACTIVITY_SHOW_SOFT_INPUT_IMPLICIT
+        } else {
+            0
+        };
+        // `ANativeActivity_showSoftInput()` only works if the `NativeActivity`'s
+        // content view has focus, so we call `InputMethodManager` directly (on
+        // the Java main thread), and only fall back to the NDK API if that
+        // fails, doesn't complete in time or returns `false`
+        let shown = jni_utils::set_soft_input_visible(
+            &self.jvm,
+            self.activity_as_ptr() as _,
+            true,
+            flags as i32,
+        )
+        .unwrap_or_else(|err| {
+            error!("Failed to show soft input via InputMethodManager: {err:?}");
+            None
+        });
+        if shown != Some(true) {
+            unsafe {
+                ndk_sys::ANativeActivity_showSoftInput(self.native_activity() as *mut _, flags);
+            }
         }
     }
 
     // TODO: move into a trait
     pub fn hide_soft_input(&self, hide_implicit_only: bool) {
-        let na = self.native_activity();
-        unsafe {
-            let flags = if hide_implicit_only {
-                ndk_sys::ANATIVEACTIVITY_HIDE_SOFT_INPUT_IMPLICIT_ONLY
-            } else {
-                0
-            };
-            ndk_sys::ANativeActivity_hideSoftInput(na as *mut _, flags);
+        let flags = if hide_implicit_only {
+            ndk_sys::ANATIVEACTIVITY_HIDE_SOFT_INPUT_IMPLICIT_ONLY
+        } else {
+            0
+        };
+        let hidden = jni_utils::set_soft_input_visible(
+            &self.jvm,
+            self.activity_as_ptr() as _,
+            false,
+            flags as i32,
+        )
+        .unwrap_or_else(|err| {
+            error!("Failed to hide soft input via InputMethodManager: {err:?}");
+            None
+        });
+        if hidden != Some(true) {
+            unsafe {
+                ndk_sys::ANativeActivity_hideSoftInput(self.native_activity() as *mut _, flags);
+            }
         }
     }
 
@@ -410,6 +1091,11 @@ impl AndroidAppInner {
         Ok(key_map)
     }
 
+    pub fn reattach_input(&self) {
+        self.native_activity
+            .attach_input_queue_to_looper(self.looper(), LOOPER_ID_INPUT);
+    }
+
     pub fn enable_motion_axis(&self, _axis: Axis) {
         // NOP - The InputQueue API doesn't let us optimize which axis values are read
     }
@@ -490,6 +1176,13 @@ impl<'a> InputIteratorInner<'a> {
             return false;
         };
 
+        // Avoid calling into the queue at all when it's empty (or when
+        // `AInputQueue_hasEvents` returns a negative error, which
+        // `InputQueue::has_events()` would panic on)
+        if unsafe { ndk_sys::AInputQueue_hasEvents(queue.ptr().as_ptr()) } <= 0 {
+            return false;
+        }
+
         // Note: we basically ignore errors from event() currently. Looking at the source code for
         // Android's InputQueue, the only error that can be returned here is 'WOULD_BLOCK', which we
         // want to just treat as meaning the queue is empty.
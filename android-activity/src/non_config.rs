@@ -0,0 +1,22 @@
+//! An in-process equivalent of `Activity.getLastNonConfigurationInstance()`
+//!
+//! When an Activity is recreated (such as after a rotation), `android_main`
+//! is run again for the new Activity but the process, and its memory, stays
+//! the same. This lets an application hand over objects that are expensive to
+//! recreate (such as GPU resources or caches) to the next `android_main`
+//! without having to serialize them like the [`MainEvent::SaveState`](crate::MainEvent::SaveState)
+//! state.
+
+use std::{any::Any, sync::Mutex};
+
+static NON_CONFIG_INSTANCE: Mutex<Option<Box<dyn Any + Send>>> = Mutex::new(None);
+
+/// Stores `instance`, returning any instance that was previously stored
+pub(crate) fn set(instance: Box<dyn Any + Send>) -> Option<Box<dyn Any + Send>> {
+    NON_CONFIG_INSTANCE.lock().unwrap().replace(instance)
+}
+
+/// Takes the stored instance, leaving the slot empty
+pub(crate) fn take() -> Option<Box<dyn Any + Send>> {
+    NON_CONFIG_INSTANCE.lock().unwrap().take()
+}
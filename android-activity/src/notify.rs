@@ -0,0 +1,168 @@
+//! Some events are synthesized from notifications that arrive on other threads
+//! (such as choreographer callbacks or Java listeners on the Java main thread)
+//! and so we need to wake up the `android_main` thread's looper to handle them.
+//!
+//! Instead of using `ALooper_wake()`, which is reserved for
+//! [`AndroidAppWaker`](crate::AndroidAppWaker) and is reported as
+//! `PollEvent::Wake`, notifications are signalled via an `eventfd` that's added
+//! to the looper with its own ident, so the two can't be confused.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+use bitflags::bitflags;
+
+/// The looper ident for notifications
+///
+/// This is distinct from the `LOOPER_ID_MAIN` and `LOOPER_ID_INPUT` idents
+/// used by the glue layers, and from `LOOPER_ID_USER` (`3`) and above, which
+/// applications conventionally use for their own file descriptors.
+pub(crate) const LOOPER_ID_NOTIFY: libc::c_int = 0;
+
+bitflags! {
+    /// Notifications that are pending for the `android_main` thread
+    #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+    pub(crate) struct Notifications: u32 {
+        /// A requested frame callback has run
+        const FRAME = 1 << 0;
+        /// The display's refresh rate has changed
+        const REFRESH_RATE = 1 << 1;
+        /// The screen has been turned off or on
+        const SCREEN_STATE = 1 << 2;
+        /// The primary clip has changed
+        const CLIPBOARD = 1 << 3;
+        /// The volume of an audio stream has changed
+        const VOLUME = 1 << 4;
+        /// An input device has been added, removed or changed
+        const INPUT_DEVICES = 1 << 5;
+        /// The application's configuration has changed
+        const CONFIG = 1 << 6;
+    }
+}
+
+/// The notifier of the most recently created `AndroidApp`, for callbacks that
+/// don't have any data of their own
+static CURRENT_NOTIFIER: Mutex<Option<Weak<LooperNotifier>>> = Mutex::new(None);
+
+#[derive(Debug)]
+pub(crate) struct LooperNotifier {
+    /// The looper that the `eventfd` was added to (which we hold a reference to)
+    looper: *mut ndk_sys::ALooper,
+    /// The `eventfd`, or `-1` if it couldn't be created
+    fd: libc::c_int,
+    pending: AtomicU32,
+}
+// The ALooper API is thread safe
+unsafe impl Send for LooperNotifier {}
+unsafe impl Sync for LooperNotifier {}
+
+impl LooperNotifier {
+    /// Creates a notifier and adds it to the given `looper`, with the
+    /// [`LOOPER_ID_NOTIFY`] ident
+    ///
+    /// This also makes it the current notifier for [`notify_current()`].
+    ///
+    /// # Safety
+    ///
+    /// `looper` must be a valid `ALooper` pointer
+    pub(crate) unsafe fn new(looper: *mut ndk_sys::ALooper) -> Arc<Self> {
+        let fd = libc::eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK);
+        if fd == -1 {
+            log::error!(
+                "Failed to create eventfd for looper notifications: {}",
+                std::io::Error::last_os_error()
+            );
+        } else {
+            ndk_sys::ALooper_acquire(looper);
+            ndk_sys::ALooper_addFd(
+                looper,
+                fd,
+                LOOPER_ID_NOTIFY,
+                ndk_sys::ALOOPER_EVENT_INPUT as libc::c_int,
+                None,
+                std::ptr::null_mut(),
+            );
+        }
+
+        let notifier = Arc::new(Self {
+            looper,
+            fd,
+            pending: AtomicU32::new(0),
+        });
+        *CURRENT_NOTIFIER.lock().unwrap() = Some(Arc::downgrade(&notifier));
+        notifier
+    }
+
+    /// Adds to the pending notifications and wakes up the looper
+    ///
+    /// This may be called from any thread.
+    pub(crate) fn notify(&self, notifications: Notifications) {
+        self.pending
+            .fetch_or(notifications.bits(), Ordering::SeqCst);
+        if self.fd == -1 {
+            return;
+        }
+        let value = 1u64;
+        let written = unsafe {
+            libc::write(
+                self.fd,
+                &value as *const u64 as *const libc::c_void,
+                std::mem::size_of::<u64>(),
+            )
+        };
+        if written == -1 {
+            let err = std::io::Error::last_os_error();
+            // The counter can only overflow if nothing is reading it, in which
+            // case the looper is awake anyway
+            if err.kind() != std::io::ErrorKind::WouldBlock {
+                log::error!("Failed to notify looper: {err}");
+            }
+        }
+    }
+
+    /// Takes the pending notifications, after the looper has returned
+    /// [`LOOPER_ID_NOTIFY`]
+    pub(crate) fn take(&self) -> Notifications {
+        if self.fd != -1 {
+            // Reset the counter before taking the notifications so that any
+            // later notification will wake the looper again
+            let mut value = 0u64;
+            unsafe {
+                libc::read(
+                    self.fd,
+                    &mut value as *mut u64 as *mut libc::c_void,
+                    std::mem::size_of::<u64>(),
+                );
+            }
+        }
+        Notifications::from_bits_truncate(self.pending.swap(0, Ordering::SeqCst))
+    }
+}
+
+impl Drop for LooperNotifier {
+    fn drop(&mut self) {
+        if self.fd != -1 {
+            unsafe {
+                ndk_sys::ALooper_removeFd(self.looper, self.fd);
+                ndk_sys::ALooper_release(self.looper);
+                libc::close(self.fd);
+            }
+        }
+    }
+}
+
+/// Notifies the current notifier (of the most recently created `AndroidApp`),
+/// if it's still alive
+///
+/// This is for callbacks that outlive any `AndroidApp` they could refer to,
+/// such as a refresh rate callback that can't be unregistered.
+pub(crate) fn notify_current(notifications: Notifications) {
+    let notifier = CURRENT_NOTIFIER
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(Weak::upgrade);
+    if let Some(notifier) = notifier {
+        notifier.notify(notifications);
+    }
+}
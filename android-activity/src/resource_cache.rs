@@ -0,0 +1,138 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use ndk::configuration::Configuration;
+
+use crate::{ConfigChange, ConfigurationRef};
+
+#[derive(Debug)]
+struct CacheEntry<V> {
+    depends_on: ConfigChange,
+    value: V,
+}
+
+/// A cache for configuration-specific resources (such as textures or
+/// localized strings) that need to be reloaded when the configuration changes
+///
+/// Each entry is inserted along with the set of configuration qualifiers that
+/// it depends on (for example, a texture chosen based on the screen density
+/// would depend on [`ConfigChange::DENSITY`]).
+///
+/// After a [`MainEvent::ConfigChanged`](crate::MainEvent::ConfigChanged) event,
+/// calling [`ResourceCache::update_config()`] will invalidate just the entries
+/// that depend on a qualifier that changed, so they can be reloaded.
+///
+/// # Example
+///
+/// ```no_run
+/// # use android_activity::{AndroidApp, ConfigChange, MainEvent, PollEvent, ResourceCache};
+/// # fn load_texture(app: &AndroidApp, name: &str) -> Vec<u8> { unimplemented!() }
+/// # fn example(app: AndroidApp) {
+/// let mut textures = ResourceCache::new(&app.config());
+/// app.poll_events(None, |event| {
+///     if let PollEvent::Main(MainEvent::ConfigChanged { .. }) = event {
+///         textures.update_config(&app.config());
+///     }
+/// });
+/// let logo = textures.get_or_insert_with("logo", ConfigChange::DENSITY, || {
+///     load_texture(&app, "logo")
+/// });
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ResourceCache<K, V> {
+    config: Configuration,
+    entries: HashMap<K, CacheEntry<V>>,
+}
+
+impl<K: Eq + Hash, V> ResourceCache<K, V> {
+    /// Creates an empty cache for resources that are loaded based on the given
+    /// configuration
+    pub fn new(config: &ConfigurationRef) -> Self {
+        Self {
+            config: config.copy(),
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Returns the cached value for `key`, if present
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entries.get(key).map(|entry| &entry.value)
+    }
+
+    /// Inserts a value that depends on the given configuration qualifiers,
+    /// returning any previous value for the same `key`
+    pub fn insert(&mut self, key: K, depends_on: ConfigChange, value: V) -> Option<V> {
+        self.entries
+            .insert(key, CacheEntry { depends_on, value })
+            .map(|entry| entry.value)
+    }
+
+    /// Returns the cached value for `key`, or else loads and inserts a new
+    /// value that depends on the given configuration qualifiers
+    pub fn get_or_insert_with<F>(&mut self, key: K, depends_on: ConfigChange, load: F) -> &V
+    where
+        F: FnOnce() -> V,
+    {
+        &self
+            .entries
+            .entry(key)
+            .or_insert_with(|| CacheEntry {
+                depends_on,
+                value: load(),
+            })
+            .value
+    }
+
+    /// Removes the value for `key`, if present
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entries.remove(key).map(|entry| entry.value)
+    }
+
+    /// Removes all entries that depend on any of the given qualifiers, and
+    /// returns the number of entries removed
+    pub fn invalidate(&mut self, changes: ConfigChange) -> usize {
+        let before = self.entries.len();
+        self.entries
+            .retain(|_, entry| !entry.depends_on.intersects(changes));
+        before - self.entries.len()
+    }
+
+    /// Compares the given configuration with the configuration that cached
+    /// resources were loaded for and invalidates any entries that depend
+    /// on qualifiers that have changed
+    ///
+    /// Returns the set of qualifiers that changed.
+    pub fn update_config(&mut self, config: &ConfigurationRef) -> ConfigChange {
+        let changes = config.diff(&self.config);
+        if !changes.is_empty() {
+            self.invalidate(changes);
+            self.config = config.copy();
+        }
+        changes
+    }
+
+    /// Removes all entries
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the number of cached entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there are no cached entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
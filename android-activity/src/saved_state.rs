@@ -0,0 +1,78 @@
+//! Typed saved state, serialized with `bincode`
+//!
+//! This builds on the raw byte API of [`StateSaver`] and [`StateLoader`],
+//! which remains available for applications that want to control the format
+//! of their saved state.
+
+use bincode::Options as _;
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+use crate::{StateLoader, StateSaver};
+
+/// The maximum size, in bytes, of state saved via [`StateSaver::store_typed()`]
+///
+/// Saved state is passed to the system via a `Bundle`, which is subject to
+/// the (process-wide) 1MB limit on Binder transactions, so state should be
+/// kept much smaller than this where possible.
+pub const MAX_SAVED_STATE_LEN: usize = 512 * 1024;
+
+/// An error from [`StateSaver::store_typed()`]
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum SaveStateError {
+    #[error("Saved state is larger than {MAX_SAVED_STATE_LEN} bytes")]
+    TooLarge,
+
+    #[error("Failed to serialize saved state: {0}")]
+    Serialize(String),
+}
+
+impl From<bincode::Error> for SaveStateError {
+    fn from(err: bincode::Error) -> Self {
+        match *err {
+            bincode::ErrorKind::SizeLimit => SaveStateError::TooLarge,
+            err => SaveStateError::Serialize(err.to_string()),
+        }
+    }
+}
+
+fn options() -> impl bincode::Options {
+    bincode::DefaultOptions::new().with_limit(MAX_SAVED_STATE_LEN as u64)
+}
+
+impl<'a> StateSaver<'a> {
+    /// Serializes `state` such that it will be available to load, via
+    /// [`StateLoader::load_typed()`], the next time that the application
+    /// resumes
+    ///
+    /// Returns an error if the serialized state would be larger than
+    /// [`MAX_SAVED_STATE_LEN`], or if serialization fails, in which case any
+    /// previously stored state is left unchanged.
+    pub fn store_typed<T: Serialize + ?Sized>(&self, state: &T) -> Result<(), SaveStateError> {
+        // Serialize into a temporary buffer first, so a failure part way
+        // through doesn't leave a partially written state behind
+        let buf = options().serialize(state)?;
+        self.store_with(buf.len(), |dst| dst.copy_from_slice(&buf));
+        Ok(())
+    }
+}
+
+impl<'a> StateLoader<'a> {
+    /// Deserializes whatever state was saved via [`StateSaver::store_typed()`]
+    /// during the last [`MainEvent::SaveState`](crate::MainEvent::SaveState) event
+    ///
+    /// Returns `None` if there is no saved state, or if it can't be
+    /// deserialized as a `T` (such as if the type of the state has changed
+    /// since it was saved).
+    pub fn load_typed<T: DeserializeOwned>(&self) -> Option<T> {
+        let state = self.load()?;
+        match options().deserialize(&state) {
+            Ok(state) => Some(state),
+            Err(err) => {
+                log::warn!("Failed to deserialize saved state: {err}");
+                None
+            }
+        }
+    }
+}
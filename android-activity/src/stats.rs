@@ -0,0 +1,65 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// A snapshot of event loop statistics, for profiling
+///
+/// See [`AndroidApp::stats()`](crate::AndroidApp::stats)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LoopStats {
+    /// The number of lifecycle / window commands processed from the Java main thread
+    pub commands_processed: u64,
+
+    /// The number of input events passed to the application via an
+    /// [`InputIterator`](crate::input::InputIterator)
+    pub input_events_handled: u64,
+
+    /// The number of times [`AndroidApp::poll_events()`](crate::AndroidApp::poll_events)
+    /// was called with a non-zero timeout, and could block
+    pub times_blocked: u64,
+
+    /// The total time spent waiting for events within
+    /// [`AndroidApp::poll_events()`](crate::AndroidApp::poll_events)
+    pub total_blocked: Duration,
+}
+
+/// Cheap, thread-safe counters that back [`LoopStats`]
+#[derive(Debug, Default)]
+pub(crate) struct LoopStatsCounters {
+    commands_processed: AtomicU64,
+    input_events_handled: AtomicU64,
+    times_blocked: AtomicU64,
+    blocked_nanos: AtomicU64,
+}
+
+impl LoopStatsCounters {
+    pub(crate) fn command_processed(&self) {
+        self.commands_processed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn input_event_handled(&self) {
+        self.input_events_handled.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn blocked(&self, duration: Duration) {
+        self.times_blocked.fetch_add(1, Ordering::Relaxed);
+        self.blocked_nanos
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> LoopStats {
+        LoopStats {
+            commands_processed: self.commands_processed.load(Ordering::Relaxed),
+            input_events_handled: self.input_events_handled.load(Ordering::Relaxed),
+            times_blocked: self.times_blocked.load(Ordering::Relaxed),
+            total_blocked: Duration::from_nanos(self.blocked_nanos.load(Ordering::Relaxed)),
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        self.commands_processed.store(0, Ordering::Relaxed);
+        self.input_events_handled.store(0, Ordering::Relaxed);
+        self.times_blocked.store(0, Ordering::Relaxed);
+        self.blocked_nanos.store(0, Ordering::Relaxed);
+    }
+}
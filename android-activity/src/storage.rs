@@ -0,0 +1,98 @@
+//! Metadata about the device's shared storage volumes
+//!
+//! Applications that manage media across multiple volumes (such as an SD card
+//! or USB drive, in addition to the primary, internal shared storage) need to
+//! know which volumes are available and whether they are currently mounted.
+
+use std::path::PathBuf;
+
+/// The state of a storage volume
+///
+/// See [the `Environment` docs](https://developer.android.com/reference/android/os/Environment#MEDIA_MOUNTED)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StorageVolumeState {
+    /// The volume is mounted with read/write access
+    Mounted,
+    /// The volume is mounted with read-only access
+    MountedReadOnly,
+    /// The volume is present but not mounted
+    Unmounted,
+    /// The volume is being disk-checked
+    Checking,
+    /// The volume is in the process of being ejected
+    Ejecting,
+    /// The volume is not present
+    Removed,
+    /// The volume was removed before it was unmounted
+    BadRemoval,
+    /// The volume is present but can't be mounted, typically because its
+    /// filesystem is corrupt
+    Unmountable,
+    /// The volume is present but blank, or has an unsupported filesystem
+    NoFilesystem,
+    /// The volume is present, not mounted and shared via USB mass storage
+    Shared,
+    /// The state is unknown
+    Unknown,
+
+    /// A state string that's not recognised by this crate
+    Other(String),
+}
+
+impl StorageVolumeState {
+    pub(crate) fn from_java(state: &str) -> Self {
+        match state {
+            "mounted" => Self::Mounted,
+            "mounted_ro" => Self::MountedReadOnly,
+            "unmounted" => Self::Unmounted,
+            "checking" => Self::Checking,
+            "ejecting" => Self::Ejecting,
+            "removed" => Self::Removed,
+            "bad_removal" => Self::BadRemoval,
+            "unmountable" => Self::Unmountable,
+            "nofs" => Self::NoFilesystem,
+            "shared" => Self::Shared,
+            "unknown" => Self::Unknown,
+            other => Self::Other(other.to_string()),
+        }
+    }
+
+    /// Returns `true` if the volume is mounted and readable (either
+    /// [`Self::Mounted`] or [`Self::MountedReadOnly`])
+    pub fn is_mounted(&self) -> bool {
+        matches!(self, Self::Mounted | Self::MountedReadOnly)
+    }
+}
+
+/// A shared storage volume, such as the primary internal storage, an SD card or USB drive
+///
+/// See [`AndroidApp::storage_volumes()`](crate::AndroidApp::storage_volumes)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct StorageVolume {
+    /// The volume's root directory
+    ///
+    /// This is only available on Android 11 (API level 30) and newer and is
+    /// also `None` if the volume isn't mounted.
+    pub path: Option<PathBuf>,
+
+    /// The volume's filesystem UUID, if it has one (the primary volume
+    /// typically doesn't)
+    pub uuid: Option<String>,
+
+    /// A user-visible description of the volume, such as "SanDisk SD card"
+    pub description: Option<String>,
+
+    /// Whether this is the primary shared storage volume
+    pub is_primary: bool,
+
+    /// Whether the volume is physically removable
+    pub is_removable: bool,
+
+    /// Whether the volume is emulated, backed by a private data partition
+    pub is_emulated: bool,
+
+    /// The current state of the volume
+    pub state: StorageVolumeState,
+}
@@ -0,0 +1,214 @@
+//! Some Android APIs (in particular anything that touches a `View`) may only be
+//! called from the Java main thread (aka the UI thread), but applications run
+//! on a separate `android_main` thread.
+//!
+//! This module lets us queue closures to run on the Java main thread by
+//! registering a pipe with the main thread's `ALooper` while the Activity is
+//! being created.
+//!
+//! The Java main thread (and its `Looper`) lives as long as the process, so we
+//! only ever register a single, process-global queue.
+
+use std::{
+    collections::VecDeque,
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::{mpsc, Mutex},
+    time::Duration,
+};
+
+use jni::objects::{GlobalRef, JObject};
+
+use crate::{
+    error::InternalResult,
+    jni_utils::{self, CloneJavaVM},
+    util::log_panic,
+};
+
+type UiThreadTask = Box<dyn FnOnce() + Send>;
+
+struct UiThreadQueue {
+    write_fd: libc::c_int,
+    tasks: VecDeque<UiThreadTask>,
+}
+
+static UI_THREAD_QUEUE: Mutex<Option<UiThreadQueue>> = Mutex::new(None);
+
+/// Registers a callback with the `ALooper` for the current thread that will
+/// run closures queued via [`run_on_ui_thread`]
+///
+/// This must be called on the Java main thread (such as from an Activity's
+/// `onCreate` callback) and it's fine to call it multiple times.
+pub(crate) fn init() {
+    let mut guard = UI_THREAD_QUEUE.lock().unwrap();
+    if guard.is_some() {
+        return;
+    }
+
+    unsafe {
+        let looper = ndk_sys::ALooper_forThread();
+        if looper.is_null() {
+            log::error!("Failed to find ALooper for Java main thread");
+            return;
+        }
+
+        let mut pipe: [libc::c_int; 2] = [-1, -1];
+        if libc::pipe2(pipe.as_mut_ptr(), libc::O_CLOEXEC) != 0 {
+            log::error!(
+                "Failed to create pipe for Java main thread callbacks: {}",
+                std::io::Error::last_os_error()
+            );
+            return;
+        }
+
+        ndk_sys::ALooper_acquire(looper);
+        ndk_sys::ALooper_addFd(
+            looper,
+            pipe[0],
+            ndk_sys::ALOOPER_POLL_CALLBACK,
+            ndk_sys::ALOOPER_EVENT_INPUT as libc::c_int,
+            Some(on_ui_thread_fd_event),
+            std::ptr::null_mut(),
+        );
+
+        *guard = Some(UiThreadQueue {
+            write_fd: pipe[1],
+            tasks: VecDeque::new(),
+        });
+    }
+}
+
+unsafe extern "C" fn on_ui_thread_fd_event(
+    fd: libc::c_int,
+    _events: libc::c_int,
+    _data: *mut libc::c_void,
+) -> libc::c_int {
+    let mut byte = 0u8;
+    if libc::read(fd, &mut byte as *mut _ as *mut _, 1) != 1 {
+        log::error!(
+            "Failure reading Java main thread callback notification: {}",
+            std::io::Error::last_os_error()
+        );
+        return 1;
+    }
+
+    // Don't hold the lock while running the task, in case it queues more work
+    let task = UI_THREAD_QUEUE
+        .lock()
+        .unwrap()
+        .as_mut()
+        .and_then(|queue| queue.tasks.pop_front());
+    if let Some(task) = task {
+        // We must not unwind into the (Java) looper
+        catch_unwind(AssertUnwindSafe(task)).unwrap_or_else(log_panic);
+    }
+
+    // Keep receiving callbacks
+    1
+}
+
+/// Queues a closure to run on the Java main thread
+///
+/// Returns `false` if the Java main thread callback couldn't be registered
+/// when the Activity was created.
+pub(crate) fn run_on_ui_thread<F>(task: F) -> bool
+where
+    F: FnOnce() + Send + 'static,
+{
+    let mut guard = UI_THREAD_QUEUE.lock().unwrap();
+    let Some(queue) = guard.as_mut() else {
+        log::error!("Can't run task on Java main thread: no callback registered");
+        return false;
+    };
+
+    queue.tasks.push_back(Box::new(task));
+    let byte = 0u8;
+    loop {
+        match unsafe { libc::write(queue.write_fd, &byte as *const _ as *const _, 1) } {
+            1 => return true,
+            -1 => {
+                let err = std::io::Error::last_os_error();
+                if err.kind() != std::io::ErrorKind::Interrupted {
+                    log::error!("Failure notifying Java main thread of new task: {err}");
+                    queue.tasks.pop_back();
+                    return false;
+                }
+            }
+            count => {
+                log::error!("Spurious write of {count} bytes while notifying Java main thread");
+                queue.tasks.pop_back();
+                return false;
+            }
+        }
+    }
+}
+
+/// Queues a JNI call against the given `Activity` to run on the Java main thread
+///
+/// A new global reference to the `Activity` is taken before returning so it
+/// will remain valid until the closure has run.
+///
+/// Since the closure runs asynchronously then any error it returns (including
+/// Java exceptions) will be logged, and cleared.
+pub(crate) fn run_with_activity_on_ui_thread<F>(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+    task: F,
+) -> InternalResult<()>
+where
+    F: for<'local> FnOnce(&mut jni::JNIEnv<'local>, &JObject<'_>) -> jni::errors::Result<()>
+        + Send
+        + 'static,
+{
+    let env = jvm.attach_current_thread_permanently()?;
+    let activity: GlobalRef = env.new_global_ref(unsafe { JObject::from_raw(activity) })?;
+
+    let jvm = jvm.clone();
+    run_on_ui_thread(move || {
+        // The Java main thread is always attached to the JVM
+        let mut env = match jvm.get_env() {
+            Ok(env) => env,
+            Err(err) => {
+                log::error!("Failed to get JNIEnv for Java main thread: {err:?}");
+                return;
+            }
+        };
+        let result = env.with_local_frame(10, |env| task(env, activity.as_obj()));
+        if let Err(err) = result {
+            let err = jni_utils::clear_and_map_exception_to_err(&mut env, err);
+            log::error!("JNI call on Java main thread failed: {err:?}");
+        }
+    });
+
+    Ok(())
+}
+
+/// Runs a JNI call against the given `Activity` on the Java main thread and
+/// waits (up to `timeout`) for its result
+///
+/// Returns `None` if the call failed (the error will have been logged) or
+/// didn't complete in time.
+///
+/// This must not be called on the Java main thread itself, and the timeout
+/// ensures we can't deadlock if the Java main thread is blocked waiting for
+/// the `android_main` thread.
+pub(crate) fn call_with_activity_on_ui_thread<F, R>(
+    jvm: &CloneJavaVM,
+    activity: jni_sys::jobject,
+    timeout: Duration,
+    task: F,
+) -> InternalResult<Option<R>>
+where
+    F: for<'local> FnOnce(&mut jni::JNIEnv<'local>, &JObject<'_>) -> jni::errors::Result<R>
+        + Send
+        + 'static,
+    R: Send + 'static,
+{
+    let (sender, receiver) = mpsc::sync_channel(1);
+    run_with_activity_on_ui_thread(jvm, activity, move |env, activity| {
+        let result = task(env, activity)?;
+        let _ = sender.send(result);
+        Ok(())
+    })?;
+
+    Ok(receiver.recv_timeout(timeout).ok())
+}
@@ -2,13 +2,19 @@ use log::{error, Level};
 use std::{
     ffi::{CStr, CString},
     fs::File,
-    io::{BufRead as _, BufReader, Result},
+    io::{BufRead as _, BufReader, Result, Write as _},
     os::{
         fd::{FromRawFd as _, RawFd},
         raw::c_char,
     },
+    sync::Mutex,
+    time::{Duration, Instant},
 };
 
+/// The maximum time that each step of tearing down the `android_main` thread,
+/// after `android_main` has returned, may block the Java main thread or process exit
+pub(crate) const TEARDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
 pub fn try_get_path_from_ptr(path: *const c_char) -> Option<std::path::PathBuf> {
     if path.is_null() {
         return None;
@@ -23,6 +29,60 @@ pub fn try_get_path_from_ptr(path: *const c_char) -> Option<std::path::PathBuf>
     Some(std::path::PathBuf::from(cstr))
 }
 
+/// Reads the given clock via `clock_gettime()`
+pub(crate) fn clock_gettime(clock: libc::clockid_t) -> std::time::Duration {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    let ret = unsafe { libc::clock_gettime(clock, &mut ts) };
+    assert_eq!(ret, 0, "clock_gettime({clock}) failed");
+    std::time::Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+}
+
+/// Converts an input event timestamp, in nanoseconds in the `CLOCK_MONOTONIC`
+/// time base, into a `Duration`, clamping (invalid) negative times to zero
+pub(crate) fn event_time_to_duration(event_time: i64) -> std::time::Duration {
+    std::time::Duration::from_nanos(event_time.max(0) as u64)
+}
+
+/// Converts an offset into `text` in UTF-16 code units (as used by Java
+/// strings) into a byte offset, clamped to the length of `text`
+///
+/// An offset that falls within a surrogate pair is rounded up to the end of the
+/// character.
+#[cfg(feature = "game-activity")]
+pub(crate) fn utf16_to_byte_offset(text: &str, utf16_offset: usize) -> usize {
+    let mut utf16_pos = 0;
+    for (byte_pos, c) in text.char_indices() {
+        if utf16_pos >= utf16_offset {
+            return byte_pos;
+        }
+        utf16_pos += c.len_utf16();
+    }
+    text.len()
+}
+
+/// Looks up a function in `libandroid.so` at runtime, returning null if it
+/// doesn't exist
+///
+/// This is for NDK functions that were added after our minimum supported API
+/// level, since linking them directly would stop our library from loading on
+/// older versions of Android.
+#[cfg(feature = "native-activity")]
+pub(crate) fn libandroid_symbol(name: &CStr) -> *mut libc::c_void {
+    unsafe {
+        let lib = libc::dlopen(
+            b"libandroid.so\0".as_ptr() as _,
+            libc::RTLD_NOW | libc::RTLD_NOLOAD,
+        );
+        if lib.is_null() {
+            return std::ptr::null_mut();
+        }
+        libc::dlsym(lib, name.as_ptr())
+    }
+}
+
 pub(crate) fn android_log(level: Level, tag: &CStr, msg: &CStr) {
     let prio = match level {
         Level::Error => ndk_sys::android_LogPriority::ANDROID_LOG_ERROR,
@@ -36,15 +96,84 @@ pub(crate) fn android_log(level: Level, tag: &CStr, msg: &CStr) {
     }
 }
 
+/// The file descriptors needed to pause and resume forwarding stdout/stderr to logcat
+#[derive(Debug)]
+struct StdioCapture {
+    /// The write end of the pipe that's read by the `stdio-to-logcat` thread
+    pipe_write: RawFd,
+    /// Duplicates of the original stdout/stderr, from before they were redirected
+    original_stdout: RawFd,
+    original_stderr: RawFd,
+    paused: bool,
+}
+
+static STDIO_CAPTURE: Mutex<StdioCapture> = Mutex::new(StdioCapture {
+    pipe_write: -1,
+    original_stdout: -1,
+    original_stderr: -1,
+    paused: false,
+});
+
+/// The default logcat tag for forwarded stdout/stderr output
+const DEFAULT_STDIO_LOG_TAG: &str = "RustStdoutStderr";
+
+/// The environment variable that can override [`DEFAULT_STDIO_LOG_TAG`]
+const STDIO_LOG_TAG_ENV: &str = "ANDROID_ACTIVITY_LOG_TAG";
+
+/// The logcat tag for forwarded stdout/stderr output, or `None` for the default
+static STDIO_LOG_TAG: Mutex<Option<CString>> = Mutex::new(None);
+
+/// Validates a logcat tag as being non-empty and free of NUL bytes
+fn valid_log_tag(tag: &str) -> Option<CString> {
+    if tag.is_empty() {
+        return None;
+    }
+    CString::new(tag).ok()
+}
+
+/// Sets the logcat tag for forwarded stdout/stderr output
+///
+/// Returns `false`, leaving the tag unchanged, if `tag` is empty or contains
+/// a NUL byte
+pub(crate) fn set_stdio_log_tag(tag: &str) -> bool {
+    match valid_log_tag(tag) {
+        Some(tag) => {
+            *STDIO_LOG_TAG.lock().unwrap() = Some(tag);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg_attr(not(feature = "native-stdout-stderr-logging"), allow(dead_code))]
 pub(crate) fn forward_stdio_to_logcat() -> std::thread::JoinHandle<Result<()>> {
-    // XXX: make this stdout/stderr redirection an optional / opt-in feature?...
+    if let Ok(tag) = std::env::var(STDIO_LOG_TAG_ENV) {
+        if !set_stdio_log_tag(&tag) {
+            log::warn!("Ignoring invalid {STDIO_LOG_TAG_ENV} logcat tag {tag:?}");
+        }
+    }
 
     let file = unsafe {
+        let mut capture = STDIO_CAPTURE.lock().unwrap();
+        if capture.original_stdout == -1 {
+            capture.original_stdout = libc::fcntl(libc::STDOUT_FILENO, libc::F_DUPFD_CLOEXEC, 0);
+            capture.original_stderr = libc::fcntl(libc::STDERR_FILENO, libc::F_DUPFD_CLOEXEC, 0);
+        }
+
         let mut logpipe: [RawFd; 2] = Default::default();
         libc::pipe2(logpipe.as_mut_ptr(), libc::O_CLOEXEC);
-        libc::dup2(logpipe[1], libc::STDOUT_FILENO);
-        libc::dup2(logpipe[1], libc::STDERR_FILENO);
-        libc::close(logpipe[1]);
+        if !capture.paused {
+            libc::dup2(logpipe[1], libc::STDOUT_FILENO);
+            libc::dup2(logpipe[1], libc::STDERR_FILENO);
+        }
+
+        // Keep the write end open so that capturing can be resumed after being
+        // paused. Closing any previous write end lets a previous forwarder
+        // thread see EOF and exit.
+        if capture.pipe_write != -1 {
+            libc::close(capture.pipe_write);
+        }
+        capture.pipe_write = logpipe[1];
 
         File::from_raw_fd(logpipe[0])
     };
@@ -52,7 +181,7 @@ pub(crate) fn forward_stdio_to_logcat() -> std::thread::JoinHandle<Result<()>> {
     std::thread::Builder::new()
         .name("stdio-to-logcat".to_string())
         .spawn(move || -> Result<()> {
-            let tag = CStr::from_bytes_with_nul(b"RustStdoutStderr\0").unwrap();
+            let default_tag = valid_log_tag(DEFAULT_STDIO_LOG_TAG).unwrap();
             let mut reader = BufReader::new(file);
             let mut buffer = String::new();
             loop {
@@ -67,13 +196,93 @@ pub(crate) fn forward_stdio_to_logcat() -> std::thread::JoinHandle<Result<()>> {
                 if len == 0 {
                     break Ok(());
                 } else if let Ok(msg) = CString::new(buffer.clone()) {
-                    android_log(Level::Info, tag, &msg);
+                    // Note: the tag is read for each line so that it can be
+                    // changed after forwarding has started
+                    let tag = STDIO_LOG_TAG.lock().unwrap();
+                    android_log(Level::Info, tag.as_ref().unwrap_or(&default_tag), &msg);
                 }
             }
         })
         .expect("Failed to start stdout/stderr to logcat forwarder thread")
 }
 
+/// Temporarily restores the original stdout/stderr file descriptors, so that
+/// writes are no longer forwarded to logcat
+///
+/// Returns `false` if stdout/stderr aren't currently being forwarded
+pub(crate) fn pause_stdio_capture() -> bool {
+    let mut capture = STDIO_CAPTURE.lock().unwrap();
+    if capture.paused || capture.pipe_write == -1 {
+        return false;
+    }
+
+    // Make sure anything buffered is forwarded before switching
+    let _ = std::io::stdout().flush();
+    let _ = std::io::stderr().flush();
+
+    // Note: `dup2` atomically replaces the file descriptors so this doesn't
+    // race with the forwarder thread, which only reads from the other end of
+    // the pipe (which we keep open while paused)
+    unsafe {
+        libc::dup2(capture.original_stdout, libc::STDOUT_FILENO);
+        libc::dup2(capture.original_stderr, libc::STDERR_FILENO);
+    }
+    capture.paused = true;
+    true
+}
+
+/// Resumes forwarding stdout/stderr to logcat after [`pause_stdio_capture()`]
+///
+/// Returns `false` if forwarding wasn't paused
+pub(crate) fn resume_stdio_capture() -> bool {
+    let mut capture = STDIO_CAPTURE.lock().unwrap();
+    if !capture.paused {
+        return false;
+    }
+
+    let _ = std::io::stdout().flush();
+    let _ = std::io::stderr().flush();
+
+    unsafe {
+        libc::dup2(capture.pipe_write, libc::STDOUT_FILENO);
+        libc::dup2(capture.pipe_write, libc::STDERR_FILENO);
+    }
+    capture.paused = false;
+    true
+}
+
+/// Flushes stdout/stderr and waits for the `stdio-to-logcat` thread to read
+/// any output that's still buffered in its pipe, for up to `timeout`
+///
+/// Returns `false` (after logging a warning) if the forwarder thread didn't
+/// keep up, such as if it's wedged, so that teardown can continue regardless.
+pub(crate) fn drain_stdio_to_logcat(timeout: Duration) -> bool {
+    let _ = std::io::stdout().flush();
+    let _ = std::io::stderr().flush();
+
+    let pipe_write = STDIO_CAPTURE.lock().unwrap().pipe_write;
+    if pipe_write == -1 {
+        return true;
+    }
+
+    let start = Instant::now();
+    loop {
+        // Note: FIONREAD reports the number of unread bytes in the pipe from
+        // either end
+        let mut pending: libc::c_int = 0;
+        if unsafe { libc::ioctl(pipe_write, libc::FIONREAD as _, &mut pending) } != 0
+            || pending == 0
+        {
+            return true;
+        }
+        if start.elapsed() >= timeout {
+            log::warn!("Timed out after {timeout:?} waiting for stdout/stderr to be forwarded to logcat ({pending} bytes pending)");
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
 pub(crate) fn log_panic(panic: Box<dyn std::any::Any + Send>) {
     let rust_panic = unsafe { CStr::from_bytes_with_nul_unchecked(b"RustPanic\0") };
 
@@ -111,3 +320,21 @@ pub(crate) fn abort_on_panic<R>(f: impl FnOnce() -> R) -> R {
         std::process::abort();
     })
 }
+
+#[cfg(all(test, feature = "game-activity"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf16_offsets_map_to_char_boundaries() {
+        let text = "a\u{e9}\u{1f600}b";
+        assert_eq!(utf16_to_byte_offset(text, 0), 0);
+        assert_eq!(utf16_to_byte_offset(text, 1), 1);
+        assert_eq!(utf16_to_byte_offset(text, 2), 3);
+        // Within the surrogate pair of the emoji
+        assert_eq!(utf16_to_byte_offset(text, 3), 7);
+        assert_eq!(utf16_to_byte_offset(text, 4), 7);
+        assert_eq!(utf16_to_byte_offset(text, 5), 8);
+        assert_eq!(utf16_to_byte_offset(text, 100), 8);
+    }
+}
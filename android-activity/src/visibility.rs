@@ -0,0 +1,36 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::Visibility;
+
+/// Tracks the lifecycle and focus state that [`Visibility`] is derived from
+///
+/// See [`AndroidApp::visibility()`](crate::AndroidApp::visibility)
+#[derive(Debug, Default)]
+pub(crate) struct VisibilityState {
+    started: AtomicBool,
+    focused: AtomicBool,
+}
+
+impl VisibilityState {
+    pub(crate) fn set_started(&self, started: bool) {
+        self.started.store(started, Ordering::SeqCst);
+    }
+
+    pub(crate) fn set_focused(&self, focused: bool) {
+        self.focused.store(focused, Ordering::SeqCst);
+    }
+
+    pub(crate) fn is_focused(&self) -> bool {
+        self.focused.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn get(&self) -> Visibility {
+        if !self.started.load(Ordering::SeqCst) {
+            Visibility::Hidden
+        } else if !self.focused.load(Ordering::SeqCst) {
+            Visibility::Visible
+        } else {
+            Visibility::Focused
+        }
+    }
+}
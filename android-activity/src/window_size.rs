@@ -0,0 +1,101 @@
+use std::sync::Mutex;
+
+#[derive(Debug, Default)]
+struct State {
+    /// The last window size that the application was notified of
+    reported: Option<(i32, i32)>,
+    /// Set when a `WindowResized` event was synthesized for a configuration
+    /// change, before the platform's own resize notification arrived
+    synthesized: bool,
+}
+
+/// Tracks the window size that was last reported to the application, so that
+/// a configuration change (such as folding or unfolding a device) results in
+/// a single, coherent [`MainEvent::WindowResized`](crate::MainEvent::WindowResized)
+/// event
+///
+/// Depending on the device, the platform's window resize notification may be
+/// delivered before or after `onConfigurationChanged`. If the window already
+/// has its new size when the configuration changes then a resize event is
+/// synthesized immediately after the `ConfigChanged` event, and the platform's
+/// own notification for that same size is dropped when it arrives.
+#[derive(Debug, Default)]
+pub(crate) struct WindowSizeTracker {
+    state: Mutex<State>,
+}
+
+impl WindowSizeTracker {
+    pub(crate) fn window_initialized(&self, size: Option<(i32, i32)>) {
+        let mut state = self.state.lock().unwrap();
+        state.reported = size;
+        state.synthesized = false;
+    }
+
+    pub(crate) fn window_terminated(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.reported = None;
+        state.synthesized = false;
+    }
+
+    /// Returns `true` if a `WindowResized` event should be synthesized after
+    /// a `ConfigChanged` event, given the current window size
+    pub(crate) fn config_changed(&self, size: Option<(i32, i32)>) -> bool {
+        let mut state = self.state.lock().unwrap();
+        match (state.reported, size) {
+            (Some(reported), Some(size)) if reported != size => {
+                state.reported = Some(size);
+                state.synthesized = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the platform's notification that the window was
+    /// resized should be forwarded to the application
+    pub(crate) fn window_resized(&self, size: Option<(i32, i32)>) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let synthesized = std::mem::take(&mut state.synthesized);
+        if synthesized && size.is_some() && size == state.reported {
+            return false;
+        }
+        if size.is_some() {
+            state.reported = size;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fold_transition_reports_single_resize() {
+        let tracker = WindowSizeTracker::default();
+        tracker.window_initialized(Some((1080, 2340)));
+
+        // Unfold: the window already has its new size when the configuration
+        // changes, so the resize is reported once, with the config change
+        assert!(tracker.config_changed(Some((2208, 1840))));
+        assert!(!tracker.window_resized(Some((2208, 1840))));
+
+        // Fold: the configuration changes before the window is resized, so
+        // the platform's own notification is forwarded
+        assert!(!tracker.config_changed(Some((2208, 1840))));
+        assert!(tracker.window_resized(Some((1080, 2340))));
+
+        // Unrelated resizes are always forwarded, even with the same size
+        assert!(tracker.window_resized(Some((1080, 2340))));
+    }
+
+    #[test]
+    fn no_resize_synthesized_without_window() {
+        let tracker = WindowSizeTracker::default();
+        assert!(!tracker.config_changed(Some((2208, 1840))));
+
+        tracker.window_initialized(Some((1080, 2340)));
+        tracker.window_terminated();
+        assert!(!tracker.config_changed(Some((2208, 1840))));
+    }
+}